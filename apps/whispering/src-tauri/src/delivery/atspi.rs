@@ -0,0 +1,151 @@
+// Direct text insertion through AT-SPI (Linux).
+//
+// Finds the focused editable object on the accessibility bus and calls
+// EditableText.InsertText at its caret. No keys or clipboard are involved, so it also
+// works where key injection is blocked (Wayland without Xwayland focus, some password
+// prompts). Apps that do not expose the Collection interface are skipped.
+
+use crate::delivery::strategy::DeliveryFailure;
+use std::collections::HashMap;
+use std::time::Duration;
+use zbus::blocking::{connection, Connection};
+use zbus::zvariant::{OwnedObjectPath, OwnedValue};
+
+const REGISTRY: &str = "org.a11y.atspi.Registry";
+const REGISTRY_ROOT: &str = "/org/a11y/atspi/accessible/root";
+
+/// AtspiStateType bits
+const STATE_EDITABLE: u32 = 7;
+const STATE_FOCUSED: u32 = 12;
+/// ATSPI_Collection_MATCH_ALL
+const MATCH_ALL: i32 = 1;
+/// ATSPI_Collection_SORT_ORDER_CANONICAL
+const SORT_CANONICAL: u32 = 1;
+
+/// Unresponsive apps must not hold up dictation
+const CALL_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// (bus name, object path) of an accessible
+type ObjectRef = (String, OwnedObjectPath);
+
+/// states, state match, attributes, attribute match, roles, role match, interfaces,
+/// interface match, invert
+type MatchRule = (
+    Vec<i32>,
+    i32,
+    HashMap<String, String>,
+    i32,
+    Vec<i32>,
+    i32,
+    Vec<String>,
+    i32,
+    bool,
+);
+
+/// Insert `text` at the caret of the focused text field
+pub fn insert_text(text: &str) -> Result<(), DeliveryFailure> {
+    let bus = a11y_bus()?;
+    let (name, path) = focused_editable(&bus)?
+        .ok_or_else(|| "No focused text field found through AT-SPI".to_string())?;
+
+    let caret = caret_offset(&bus, &name, &path)?;
+    if caret < 0 {
+        return Err(DeliveryFailure::new("The focused text field has no caret"));
+    }
+
+    let length =
+        i32::try_from(text.chars().count()).map_err(|_| "Text too long for AT-SPI".to_string())?;
+    let inserted: bool = bus
+        .call_method(
+            Some(name.as_str()),
+            path.as_str(),
+            Some("org.a11y.atspi.EditableText"),
+            "InsertText",
+            &(caret, text, length),
+        )
+        .and_then(|reply| reply.body().deserialize())
+        .map_err(|e| format!("Failed to insert text through AT-SPI: {}", e))?;
+    if !inserted {
+        return Err(DeliveryFailure::new(
+            "The focused text field refused the insertion",
+        ));
+    }
+    Ok(())
+}
+
+/// Connection to the accessibility bus, whose address the session bus hands out
+fn a11y_bus() -> Result<Connection, String> {
+    let session =
+        Connection::session().map_err(|e| format!("Failed to connect to session bus: {}", e))?;
+    let address: String = session
+        .call_method(
+            Some("org.a11y.Bus"),
+            "/org/a11y/bus",
+            Some("org.a11y.Bus"),
+            "GetAddress",
+            &(),
+        )
+        .and_then(|reply| reply.body().deserialize())
+        .map_err(|e| format!("Accessibility bus unavailable: {}", e))?;
+
+    connection::Builder::address(address.as_str())
+        .and_then(|builder| builder.method_timeout(CALL_TIMEOUT).build())
+        .map_err(|e| format!("Failed to connect to accessibility bus: {}", e))
+}
+
+/// First focused, editable object among the registered applications
+fn focused_editable(bus: &Connection) -> Result<Option<ObjectRef>, String> {
+    let apps: Vec<ObjectRef> = bus
+        .call_method(
+            Some(REGISTRY),
+            REGISTRY_ROOT,
+            Some("org.a11y.atspi.Accessible"),
+            "GetChildren",
+            &(),
+        )
+        .and_then(|reply| reply.body().deserialize())
+        .map_err(|e| format!("Failed to list accessible applications: {}", e))?;
+
+    let states = (1i32 << STATE_EDITABLE) | (1i32 << STATE_FOCUSED);
+    let rule: MatchRule = (
+        vec![states, 0],
+        MATCH_ALL,
+        HashMap::new(),
+        MATCH_ALL,
+        Vec::new(),
+        MATCH_ALL,
+        Vec::new(),
+        MATCH_ALL,
+        false,
+    );
+
+    for (app, root) in apps {
+        let matches: Result<Vec<ObjectRef>, _> = bus
+            .call_method(
+                Some(app.as_str()),
+                root.as_str(),
+                Some("org.a11y.atspi.Collection"),
+                "GetMatches",
+                &(&rule, SORT_CANONICAL, 1i32, true),
+            )
+            .and_then(|reply| reply.body().deserialize());
+        if let Some(found) = matches.ok().and_then(|matches| matches.into_iter().next()) {
+            return Ok(Some(found));
+        }
+    }
+    Ok(None)
+}
+
+fn caret_offset(bus: &Connection, name: &str, path: &OwnedObjectPath) -> Result<i32, String> {
+    let value: OwnedValue = bus
+        .call_method(
+            Some(name),
+            path.as_str(),
+            Some("org.freedesktop.DBus.Properties"),
+            "Get",
+            &("org.a11y.atspi.Text", "CaretOffset"),
+        )
+        .and_then(|reply| reply.body().deserialize())
+        .map_err(|e| format!("Failed to read caret position: {}", e))?;
+    i32::try_from(value).map_err(|e| format!("Unexpected caret position: {}", e))
+}
@@ -0,0 +1,218 @@
+// Clipboard preservation around dictation paste.
+//
+// Pasting puts the transcript on the clipboard, which throws away whatever the user had
+// copied. When asked to, `write_text` snapshots the clipboard first and puts it back
+// once the target app has had time to read the paste - unless the user copied
+// something new in the meantime.
+
+use arboard::{Clipboard, ImageData};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Longest accepted restore delay
+const MAX_RESTORE_DELAY: Duration = Duration::from_secs(10);
+
+/// What was on the clipboard. Formats arboard cannot write back (file lists,
+/// app-private types) are not kept.
+pub struct ClipboardSnapshot {
+    text: Option<String>,
+    html: Option<String>,
+    image: Option<ImageData<'static>>,
+}
+
+impl ClipboardSnapshot {
+    pub fn capture(clipboard: &mut Clipboard) -> Self {
+        Self {
+            text: clipboard.get_text().ok(),
+            html: clipboard.get().html().ok(),
+            image: clipboard.get_image().ok(),
+        }
+    }
+
+    /// Put the richest captured format back (HTML keeps its plain-text alternative)
+    fn restore(self, clipboard: &mut Clipboard) -> Result<(), arboard::Error> {
+        match self {
+            Self {
+                html: Some(html),
+                text,
+                ..
+            } => clipboard.set_html(html, text),
+            Self {
+                image: Some(image), ..
+            } => clipboard.set_image(image),
+            Self {
+                text: Some(text), ..
+            } => clipboard.set_text(text),
+            _ => clipboard.clear(),
+        }
+    }
+}
+
+struct PendingRestore<S> {
+    id: u64,
+    snapshot: S,
+    pasted: String, // Still on the clipboard unless the user copied something else
+}
+
+/// The restore waiting for its delay, if any. Knows nothing of the system clipboard.
+struct RestoreSlot<S> {
+    pending: Mutex<Option<PendingRestore<S>>>,
+    next_id: AtomicU64,
+}
+
+impl<S> Default for RestoreSlot<S> {
+    fn default() -> Self {
+        Self {
+            pending: Mutex::new(None),
+            next_id: AtomicU64::new(0),
+        }
+    }
+}
+
+impl<S> RestoreSlot<S> {
+    /// Cancel the pending restore. Its snapshot is handed over when the clipboard text
+    /// (`current`) is still its paste.
+    fn take_over(&self, current: Option<&str>) -> Option<S> {
+        let pending = self.pending.lock().ok()?.take()?;
+        (current == Some(pending.pasted.as_str())).then_some(pending.snapshot)
+    }
+
+    /// Make `snapshot` the pending restore, replacing any earlier one. Returns its id.
+    fn schedule(&self, snapshot: S, pasted: String) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut pending) = self.pending.lock() {
+            *pending = Some(PendingRestore {
+                id,
+                snapshot,
+                pasted,
+            });
+        }
+        id
+    }
+
+    /// Run `restore` on restore `id` unless a later paste took it over. Holds the slot
+    /// meanwhile, so a paste starting now snapshots the restored clipboard.
+    fn finish(&self, id: u64, restore: impl FnOnce(PendingRestore<S>)) {
+        let Ok(mut pending) = self.pending.lock() else {
+            return;
+        };
+        if pending.as_ref().map(|restore| restore.id) != Some(id) {
+            return;
+        }
+        if let Some(pending) = pending.take() {
+            restore(pending);
+        }
+    }
+}
+
+/// Tauri state: the restore waiting for its delay, if any
+#[derive(Default)]
+pub struct ClipboardPreservation {
+    slot: Arc<RestoreSlot<ClipboardSnapshot>>,
+}
+
+impl ClipboardPreservation {
+    /// Snapshot of the user's clipboard, taken before a transcript overwrites it.
+    ///
+    /// When an earlier paste is still waiting to restore, the clipboard holds that
+    /// transcript - its snapshot is taken over instead, and its restore cancelled.
+    pub fn take_snapshot(&self, clipboard: &mut Clipboard) -> ClipboardSnapshot {
+        self.slot
+            .take_over(clipboard.get_text().ok().as_deref())
+            .unwrap_or_else(|| ClipboardSnapshot::capture(clipboard))
+    }
+
+    /// Restore `snapshot` after `delay`, if the clipboard still holds `pasted` by then
+    pub fn schedule_restore(&self, snapshot: ClipboardSnapshot, pasted: String, delay: Duration) {
+        let id = self.slot.schedule(snapshot, pasted);
+
+        let slot = self.slot.clone();
+        thread::spawn(move || {
+            thread::sleep(delay);
+
+            slot.finish(id, |restore| {
+                let result = Clipboard::new().and_then(|mut clipboard| {
+                    if clipboard.get_text().ok() != Some(restore.pasted) {
+                        println!("[Clipboard] Clipboard changed since the paste, not restoring");
+                        return Ok(());
+                    }
+                    restore.snapshot.restore(&mut clipboard)
+                });
+                if let Err(e) = result {
+                    eprintln!("[Clipboard] Failed to restore clipboard: {}", e);
+                }
+            });
+        });
+    }
+}
+
+/// Validate the restore delay passed by the frontend
+pub fn restore_delay(ms: u64) -> Result<Duration, String> {
+    let delay = Duration::from_millis(ms);
+    if delay > MAX_RESTORE_DELAY {
+        return Err(format!(
+            "Clipboard restore delay must be at most {} ms, got {}",
+            MAX_RESTORE_DELAY.as_millis(),
+            ms
+        ));
+    }
+    Ok(delay)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Snapshots restored by finishing `ids`, in order
+    fn finish_all(slot: &RestoreSlot<&'static str>, ids: &[u64]) -> Vec<&'static str> {
+        let mut restored = Vec::new();
+        for id in ids {
+            slot.finish(*id, |restore| restored.push(restore.snapshot));
+        }
+        restored
+    }
+
+    #[test]
+    fn caps_the_restore_delay() {
+        assert_eq!(restore_delay(0), Ok(Duration::ZERO));
+        assert_eq!(restore_delay(10_000), Ok(MAX_RESTORE_DELAY));
+        assert!(restore_delay(10_001).unwrap_err().contains("10000 ms"));
+    }
+
+    #[test]
+    fn restores_once_after_the_delay() {
+        let slot = RestoreSlot::default();
+        let id = slot.schedule("copied", "transcript".to_string());
+        assert_eq!(finish_all(&slot, &[id, id]), ["copied"]);
+    }
+
+    #[test]
+    fn a_later_paste_takes_the_snapshot_over() {
+        let slot = RestoreSlot::default();
+        let first = slot.schedule("copied", "first transcript".to_string());
+
+        // The clipboard still holds the first transcript, not the user's copy
+        assert_eq!(slot.take_over(Some("first transcript")), Some("copied"));
+        let second = slot.schedule("copied", "second transcript".to_string());
+
+        assert_eq!(finish_all(&slot, &[first, second]), ["copied"]);
+    }
+
+    #[test]
+    fn a_new_copy_cancels_the_pending_restore() {
+        let slot = RestoreSlot::default();
+        let id = slot.schedule("copied", "transcript".to_string());
+
+        assert_eq!(slot.take_over(Some("copied later")), None);
+        assert_eq!(slot.take_over(None), None);
+        assert!(finish_all(&slot, &[id]).is_empty());
+    }
+
+    #[test]
+    fn nothing_to_take_over_when_idle() {
+        let slot = RestoreSlot::<&'static str>::default();
+        assert_eq!(slot.take_over(Some("transcript")), None);
+    }
+}
@@ -0,0 +1,220 @@
+use crate::delivery::clipboard::{restore_delay, ClipboardPreservation};
+use crate::delivery::injector::KeyInjector;
+use crate::delivery::paste_key::PasteKeys;
+use crate::delivery::profile::{find_profile, DeliveryProfile};
+use crate::delivery::strategy::{DeliveryFailure, DeliveryOptions, TextDeliveryStrategy};
+use crate::delivery::target::focused_app;
+use arboard::Clipboard;
+use std::time::Duration;
+use tauri::Manager;
+
+/// Inserts text at the cursor of the focused application.
+///
+/// `delivery` picks the strategy (clipboard paste by default, simulated typing, or AT-SPI
+/// direct insertion on Linux); when it fails, the others are tried in turn unless
+/// `fallback` is off. Text that may have been partly inserted is never delivered twice.
+/// Returns the strategy that delivered the text.
+///
+/// The first of `profiles` naming the focused application overrides `delivery`, and
+/// sets the paste shortcut and whether Return is pressed afterwards (see `profile`).
+///
+/// With `restore_clipboard_after_ms`, the clipboard is snapshotted before the transcript
+/// goes on it and restored that long after a successful paste (see `clipboard`).
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn write_text(
+    text: String,
+    app_handle: tauri::AppHandle,
+    preservation: tauri::State<ClipboardPreservation>,
+    keep_window_visible: Option<bool>,
+    refocus_window: Option<bool>,
+    restore_clipboard_after_ms: Option<u64>,
+    delivery: Option<DeliveryOptions>,
+    profiles: Option<Vec<DeliveryProfile>>,
+) -> Result<TextDeliveryStrategy, String> {
+    let restore_after = restore_clipboard_after_ms.map(restore_delay).transpose()?;
+    let mut delivery = delivery.unwrap_or_default();
+    delivery.validate()?;
+
+    // Looked up before our window hides, while the target is still the frontmost app
+    let profiles = profiles.unwrap_or_default();
+    let profile = if profiles.is_empty() {
+        None
+    } else {
+        match focused_app() {
+            Ok(Some(app)) => {
+                let profile = find_profile(&profiles, &app);
+                if let Some(profile) = profile {
+                    println!(
+                        "[Delivery] Using profile '{}' for {}",
+                        profile.name, app.app_id
+                    );
+                }
+                profile
+            }
+            Ok(None) => None,
+            Err(e) => {
+                println!("[Delivery] Could not detect the focused app: {}", e);
+                None
+            }
+        }
+    };
+    if let Some(profile) = profile {
+        profile.apply(&mut delivery);
+    }
+    let paste_keys = profile
+        .map(|profile| profile.paste_keys)
+        .unwrap_or_default();
+
+    // Hide our main window if it's visible to avoid interfering with focus
+    // Unless keep_window_visible is true (used during onboarding when pasting into the app itself)
+    let should_hide = !keep_window_visible.unwrap_or(false);
+    let should_refocus = refocus_window.unwrap_or(false);
+
+    if should_hide {
+        if let Some(main_window) = app_handle.get_webview_window("main") {
+            if let Ok(is_visible) = main_window.is_visible() {
+                if is_visible {
+                    let _ = main_window.hide();
+                }
+            }
+        }
+    }
+
+    // When pasting into the app itself (onboarding), refocus the window
+    if should_refocus {
+        if let Some(main_window) = app_handle.get_webview_window("main") {
+            let _ = main_window.set_focus();
+            // Give focus time to settle
+            std::thread::sleep(std::time::Duration::from_millis(200));
+        }
+    }
+
+    // Only paste and typing need it; direct insertion still works without
+    let mut injector = KeyInjector::new();
+    if let Err(e) = &injector {
+        println!("[Delivery] {}", e);
+    }
+
+    let mut errors = Vec::new();
+    for strategy in delivery.chain() {
+        let result = match strategy {
+            TextDeliveryStrategy::ClipboardPaste => match &mut injector {
+                Ok(injector) => paste_via_clipboard(
+                    &app_handle,
+                    &preservation,
+                    injector,
+                    &text,
+                    paste_keys,
+                    restore_after,
+                ),
+                Err(e) => Err(DeliveryFailure::new(e.clone())),
+            },
+            TextDeliveryStrategy::Typing => match &mut injector {
+                Ok(injector) => injector.type_text(&text, &delivery.typing),
+                Err(e) => Err(DeliveryFailure::new(e.clone())),
+            },
+            TextDeliveryStrategy::DirectInsert => direct_insert(&text),
+        };
+
+        match result {
+            Ok(()) => {
+                if !errors.is_empty() {
+                    println!("[Delivery] Delivered with {:?}", strategy);
+                }
+                if profile.is_some_and(|profile| profile.trailing_newline) {
+                    let pressed = injector
+                        .as_mut()
+                        .map_err(|e| e.clone())
+                        .and_then(|injector| injector.press_return());
+                    if let Err(e) = pressed {
+                        println!("[Delivery] {}", e);
+                    }
+                }
+                return Ok(strategy);
+            }
+            Err(failure) => {
+                println!("[Delivery] {:?} failed: {}", strategy, failure.message);
+                errors.push(format!("{:?}: {}", strategy, failure.message));
+                if failure.partial {
+                    break;
+                }
+            }
+        }
+    }
+
+    Err(format!("Failed to insert text ({})", errors.join("; ")))
+}
+
+/// Put `text` on the clipboard and press the paste shortcut
+fn paste_via_clipboard(
+    app_handle: &tauri::AppHandle,
+    preservation: &ClipboardPreservation,
+    injector: &mut KeyInjector,
+    text: &str,
+    keys: PasteKeys,
+    restore_after: Option<Duration>,
+) -> Result<(), DeliveryFailure> {
+    // Keep what the user had copied when it is to be restored after the paste
+    let snapshot = match restore_after {
+        Some(_) => {
+            let mut clipboard =
+                Clipboard::new().map_err(|e| format!("Failed to open clipboard: {}", e))?;
+            Some(preservation.take_snapshot(&mut clipboard))
+        }
+        None => None,
+    };
+
+    // Tauri's clipboard manager handles UTF-8 properly
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+    app_handle
+        .clipboard()
+        .write_text(text.to_string())
+        .map_err(|e| format!("Failed to write to clipboard: {}", e))?;
+
+    let pasted = injector.paste(keys);
+
+    if let (Some(snapshot), Some(delay)) = (snapshot, restore_after) {
+        // Another strategy takes over: give the user's clipboard back right away
+        let delay = if pasted.is_ok() {
+            delay
+        } else {
+            Duration::ZERO
+        };
+        preservation.schedule_restore(snapshot, text.to_string(), delay);
+    }
+
+    pasted.map_err(DeliveryFailure::new)
+}
+
+#[cfg(target_os = "linux")]
+fn direct_insert(text: &str) -> Result<(), DeliveryFailure> {
+    crate::delivery::atspi::insert_text(text)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn direct_insert(_text: &str) -> Result<(), DeliveryFailure> {
+    Err(DeliveryFailure::new(
+        "Direct insertion is only available on Linux",
+    ))
+}
+
+/// Simulates a paste operation (Cmd+V on macOS, Ctrl+V elsewhere).
+///
+/// **Important**: This assumes text is already in the system clipboard. Call your
+/// clipboard service to copy text before using this function.
+///
+/// The V key is looked up in the active keyboard layout, so Dvorak, Colemak and AZERTY
+/// users get a real paste (see `paste_key`). Wayland sessions go through wtype, ydotool
+/// or uinput (see `wayland`).
+#[tauri::command]
+pub fn paste() -> Result<(), String> {
+    KeyInjector::new()?.paste(PasteKeys::Standard)
+}
+
+/// Name of the backend sending keys in this session ("enigo", "wtype", "ydotool",
+/// "uinput"), or why there is none
+#[tauri::command]
+pub fn get_text_input_backend() -> Result<String, String> {
+    KeyInjector::new().map(|injector| injector.name().to_string())
+}
@@ -0,0 +1,99 @@
+// Where synthesized keys go: enigo on macOS, Windows and X11, a Wayland backend in
+// Wayland sessions (see `wayland`), or enigo for Xwayland windows when there is none.
+
+use crate::delivery::paste_key::{send_paste, PasteKeys};
+use crate::delivery::strategy::{DeliveryFailure, TypingOptions};
+#[cfg(target_os = "linux")]
+use crate::delivery::typing::check_keystrokes;
+use crate::delivery::typing::type_text;
+#[cfg(target_os = "linux")]
+use crate::delivery::wayland::{is_wayland_session, WaylandBackend};
+use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+use std::time::Duration;
+
+pub enum KeyInjector {
+    Enigo(Box<Enigo>),
+    #[cfg(target_os = "linux")]
+    Wayland(WaylandBackend),
+}
+
+impl KeyInjector {
+    /// The injector for this session, or why keys cannot be sent
+    pub fn new() -> Result<Self, String> {
+        #[cfg(target_os = "linux")]
+        if is_wayland_session() {
+            match WaylandBackend::detect() {
+                Ok(backend) => return Ok(Self::Wayland(backend)),
+                // XTest still reaches Xwayland windows
+                Err(wayland_error) => {
+                    println!("[Delivery] {}; falling back to enigo", wayland_error);
+                    return create_enigo()
+                        .map(|enigo| Self::Enigo(Box::new(enigo)))
+                        .map_err(|e| format!("{}. {}", wayland_error, e));
+                }
+            }
+        }
+        create_enigo().map(|enigo| Self::Enigo(Box::new(enigo)))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Enigo(_) => "enigo",
+            #[cfg(target_os = "linux")]
+            Self::Wayland(backend) => backend.name(),
+        }
+    }
+
+    pub fn paste(&mut self, keys: PasteKeys) -> Result<(), String> {
+        match self {
+            Self::Enigo(enigo) => send_paste(enigo, keys),
+            #[cfg(target_os = "linux")]
+            Self::Wayland(backend) => backend.paste(keys),
+        }
+    }
+
+    pub fn type_text(
+        &mut self,
+        text: &str,
+        options: &TypingOptions,
+    ) -> Result<(), DeliveryFailure> {
+        match self {
+            Self::Enigo(enigo) => type_text(enigo, text, options),
+            #[cfg(target_os = "linux")]
+            Self::Wayland(backend) => {
+                check_keystrokes(text, options)?;
+                backend.type_text(text, options)
+            }
+        }
+    }
+
+    pub fn press_return(&mut self) -> Result<(), String> {
+        match self {
+            Self::Enigo(enigo) => enigo
+                .key(Key::Return, Direction::Click)
+                .map_err(|e| format!("Failed to press Return: {}", e)),
+            #[cfg(target_os = "linux")]
+            Self::Wayland(backend) => backend.press_return(),
+        }
+    }
+}
+
+/// Enigo instance, retried once for permission issues
+fn create_enigo() -> Result<Enigo, String> {
+    let mut last_error = String::new();
+
+    for attempt in 1..=2 {
+        match Enigo::new(&Settings::default()) {
+            Ok(enigo) => return Ok(enigo),
+            Err(e) => {
+                last_error = format!("Failed to create Enigo instance: {}", e);
+
+                if last_error.contains("permission") && attempt == 1 {
+                    std::thread::sleep(Duration::from_millis(500));
+                }
+            }
+        }
+    }
+
+    Err(last_error)
+}
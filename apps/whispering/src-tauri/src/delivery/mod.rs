@@ -0,0 +1,20 @@
+#[cfg(target_os = "linux")]
+pub mod atspi;
+pub mod clipboard;
+pub mod commands;
+pub mod injector;
+pub mod paste_key;
+pub mod profile;
+pub mod strategy;
+pub mod target;
+pub mod typing;
+#[cfg(target_os = "linux")]
+pub mod wayland;
+
+// Export everything from commands for easy access
+pub use commands::{get_text_input_backend, paste, write_text};
+
+// Export key types
+pub use clipboard::ClipboardPreservation;
+pub use strategy::{DeliveryOptions, TextDeliveryStrategy};
+pub use target::get_focused_app;
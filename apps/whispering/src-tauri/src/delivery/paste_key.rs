@@ -0,0 +1,398 @@
+// Layout-aware paste keystroke.
+//
+// `Key::Unicode('v')` leaves the lookup to enigo, which goes wrong on non-QWERTY
+// layouts: when no key types 'v' (Cyrillic, Greek...) enigo's macOS backend sends
+// keycode 0 - Cmd+A - and its X11 backend remaps a spare keycode that the target may
+// only read after the mapping was restored. Each platform matches Cmd/Ctrl+V
+// differently, so the V key is resolved per platform:
+// - macOS: the key typing 'v' in the ASCII-capable layout macOS uses for Cmd shortcuts
+// - Windows: the VK_V virtual key, which the active layout already places
+// - Linux (X11): the keycode whose keysym is 'v' in the active XKB group
+// falling back to the physical (ANSI) V key when the lookup fails.
+//
+// Delivery profiles can swap Cmd/Ctrl+V for the terminal shortcuts (`PasteKeys`).
+
+use enigo::{Direction, Enigo, Key, Keyboard};
+use serde::{Deserialize, Serialize};
+
+#[cfg(target_os = "macos")]
+const PASTE_MODIFIER: Key = Key::Meta;
+#[cfg(not(target_os = "macos"))]
+const PASTE_MODIFIER: Key = Key::Control;
+
+/// Key combination that pastes in the target app
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PasteKeys {
+    /// Cmd+V on macOS, Ctrl+V elsewhere
+    #[default]
+    Standard,
+    /// Linux terminals, where Ctrl+V inserts the next character literally
+    ControlShiftV,
+    /// X11 terminals and xterm-likes (not on macOS)
+    ShiftInsert,
+}
+
+/// Press `keys`
+pub fn send_paste(enigo: &mut Enigo, keys: PasteKeys) -> Result<(), String> {
+    let modifiers: &[Key] = match keys {
+        PasteKeys::Standard => &[PASTE_MODIFIER],
+        PasteKeys::ControlShiftV => &[Key::Control, Key::Shift],
+        PasteKeys::ShiftInsert => &[Key::Shift],
+    };
+
+    let mut pressed = 0;
+    let mut result = Ok(());
+    for modifier in modifiers {
+        result = enigo
+            .key(*modifier, Direction::Press)
+            .map_err(|e| format!("Failed to press modifier key: {}", e));
+        if result.is_err() {
+            break;
+        }
+        pressed += 1;
+    }
+    if result.is_ok() {
+        result = match keys {
+            PasteKeys::ShiftInsert => click_insert(enigo),
+            _ => click_v(enigo),
+        };
+    }
+
+    // Release the modifiers even when the key fails, or they stay stuck down
+    for modifier in modifiers[..pressed].iter().rev() {
+        enigo
+            .key(*modifier, Direction::Release)
+            .map_err(|e| format!("Failed to release modifier key: {}", e))?;
+    }
+    result
+}
+
+#[cfg(not(target_os = "macos"))]
+fn click_insert(enigo: &mut Enigo) -> Result<(), String> {
+    enigo
+        .key(Key::Insert, Direction::Click)
+        .map_err(|e| format!("Failed to click Insert key: {}", e))
+}
+
+#[cfg(target_os = "macos")]
+fn click_insert(_enigo: &mut Enigo) -> Result<(), String> {
+    Err("Mac keyboards have no Insert key, use another paste shortcut".to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn click_v(enigo: &mut Enigo) -> Result<(), String> {
+    let keycode = macos::v_keycode().unwrap_or_else(|| {
+        println!("[Paste] No key types 'v' in the shortcut layout, using the physical V key");
+        macos::PHYSICAL_V
+    });
+    enigo
+        .raw(keycode, Direction::Click)
+        .map_err(|e| format!("Failed to click 'v' key: {}", e))
+}
+
+#[cfg(target_os = "windows")]
+fn click_v(enigo: &mut Enigo) -> Result<(), String> {
+    enigo
+        .key(Key::V, Direction::Click)
+        .map_err(|e| format!("Failed to click 'v' key: {}", e))
+}
+
+#[cfg(target_os = "linux")]
+fn click_v(enigo: &mut Enigo) -> Result<(), String> {
+    let keycode = x11::v_keycode().unwrap_or_else(|e| {
+        println!("[Paste] {}, using the physical V key", e);
+        x11::PHYSICAL_V
+    });
+    enigo
+        .raw(keycode, Direction::Click)
+        .map_err(|e| format!("Failed to click 'v' key: {}", e))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn click_v(enigo: &mut Enigo) -> Result<(), String> {
+    enigo
+        .key(Key::Unicode('v'), Direction::Click)
+        .map_err(|e| format!("Failed to click 'v' key: {}", e))
+}
+
+// ============================================================================
+// MACOS
+// ============================================================================
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use core_foundation_sys::base::{CFRelease, OSStatus};
+    use core_foundation_sys::data::{CFDataGetBytePtr, CFDataRef};
+    use core_foundation_sys::string::{CFStringRef, UniChar};
+    use std::ffi::c_void;
+
+    /// kVK_ANSI_V
+    pub const PHYSICAL_V: u16 = 0x09;
+
+    const K_UC_KEY_ACTION_DOWN: u16 = 0;
+    const K_UC_KEY_TRANSLATE_NO_DEAD_KEYS_MASK: u32 = 1;
+
+    type TISInputSourceRef = *const c_void;
+
+    #[link(name = "Carbon", kind = "framework")]
+    extern "C" {
+        static kTISPropertyUnicodeKeyLayoutData: CFStringRef;
+
+        fn TISCopyCurrentASCIICapableKeyboardLayoutInputSource() -> TISInputSourceRef;
+        fn TISGetInputSourceProperty(source: TISInputSourceRef, key: CFStringRef) -> CFDataRef;
+        fn LMGetKbdType() -> u8;
+        fn UCKeyTranslate(
+            layout: *const u8,
+            virtual_key_code: u16,
+            key_action: u16,
+            modifier_key_state: u32,
+            keyboard_type: u32,
+            key_translate_options: u32,
+            dead_key_state: *mut u32,
+            max_string_length: usize,
+            actual_string_length: *mut usize,
+            unicode_string: *mut UniChar,
+        ) -> OSStatus;
+    }
+
+    /// Keycode typing 'v' in the layout used for Cmd shortcuts (the current layout, or
+    /// the last Latin one when a Cyrillic/Greek/... layout is active).
+    ///
+    /// Text Input Sources must be queried on the main thread, where sync Tauri commands run.
+    pub fn v_keycode() -> Option<u16> {
+        unsafe {
+            let source = TISCopyCurrentASCIICapableKeyboardLayoutInputSource();
+            if source.is_null() {
+                return None;
+            }
+
+            // Owned by `source`, valid until it is released
+            let data = TISGetInputSourceProperty(source, kTISPropertyUnicodeKeyLayoutData);
+            let keycode = if data.is_null() {
+                None
+            } else {
+                let layout = CFDataGetBytePtr(data);
+                let keyboard_type = u32::from(LMGetKbdType());
+                (0..128u16).find(|&keycode| {
+                    let mut dead_key_state = 0u32;
+                    let mut chars: [UniChar; 4] = [0; 4];
+                    let mut length = 0usize;
+                    let status = UCKeyTranslate(
+                        layout,
+                        keycode,
+                        K_UC_KEY_ACTION_DOWN,
+                        0,
+                        keyboard_type,
+                        K_UC_KEY_TRANSLATE_NO_DEAD_KEYS_MASK,
+                        &mut dead_key_state,
+                        chars.len(),
+                        &mut length,
+                        chars.as_mut_ptr(),
+                    );
+                    status == 0 && chars[..length] == [UniChar::from(b'v')]
+                })
+            };
+
+            CFRelease(source);
+            keycode
+        }
+    }
+}
+
+// ============================================================================
+// LINUX (X11)
+// ============================================================================
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use x11rb::connection::{Connection, RequestConnection};
+    use x11rb::protocol::xkb::{self, ConnectionExt as _};
+    use x11rb::protocol::xproto::ConnectionExt as _;
+
+    /// evdev KEY_V + 8, the key labelled V on a QWERTY keyboard
+    pub const PHYSICAL_V: u16 = 55;
+
+    /// XK_v
+    const KEYSYM_V: u32 = 0x0076;
+
+    /// Keycode typing 'v' in the active XKB group of the X server at `$DISPLAY`
+    /// (Xwayland included, which is what enigo talks to under Wayland).
+    pub fn v_keycode() -> Result<u16, String> {
+        let (conn, _) =
+            x11rb::connect(None).map_err(|e| format!("Failed to connect to X server: {}", e))?;
+        let setup = conn.setup();
+        let (min_keycode, max_keycode) = (setup.min_keycode, setup.max_keycode);
+
+        let mapping = conn
+            .get_keyboard_mapping(min_keycode, max_keycode - min_keycode + 1)
+            .map_err(|e| format!("Failed to request keyboard mapping: {}", e))?
+            .reply()
+            .map_err(|e| format!("Failed to read keyboard mapping: {}", e))?;
+        let group = active_group(&conn).unwrap_or(0);
+
+        find_keycode(
+            &mapping.keysyms,
+            mapping.keysyms_per_keycode,
+            min_keycode,
+            group,
+            KEYSYM_V,
+        )
+        .ok_or_else(|| "No key types 'v' in the X keymap".to_string())
+    }
+
+    /// Index of the layout in use, on servers with XKB
+    fn active_group(conn: &impl RequestConnection) -> Option<u8> {
+        let extension = conn.xkb_use_extension(1, 0).ok()?.reply().ok()?;
+        if !extension.supported {
+            return None;
+        }
+        let state = conn
+            .xkb_get_state(xkb::ID::USE_CORE_KBD.into())
+            .ok()?
+            .reply()
+            .ok()?;
+        Some(u8::from(state.group))
+    }
+
+    /// Find the keycode typing `keysym` without Shift in the core keymap (`per_keycode`
+    /// keysyms per keycode from `min_keycode`): in `group`, else in the first group.
+    /// The core keymap lists the first two groups as columns 0-1 and 2-3.
+    fn find_keycode(
+        keysyms: &[u32],
+        per_keycode: u8,
+        min_keycode: u8,
+        group: u8,
+        keysym: u32,
+    ) -> Option<u16> {
+        let per_keycode = usize::from(per_keycode);
+        if per_keycode == 0 {
+            return None;
+        }
+        let in_column = |column: usize| {
+            keysyms
+                .chunks(per_keycode)
+                .position(|syms| syms.get(column) == Some(&keysym))
+        };
+
+        let index = in_column(usize::from(group) * 2).or_else(|| in_column(0))?;
+        u16::try_from(index)
+            .ok()
+            .map(|index| u16::from(min_keycode) + index)
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use enigo::Settings;
+    use std::process::Command;
+    use std::time::{Duration, Instant};
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{
+        ConnectionExt as _, CreateWindowAux, EventMask, InputFocus, KeyButMask, KeyPressEvent,
+        WindowClass,
+    };
+    use x11rb::protocol::Event;
+    use x11rb::rust_connection::RustConnection;
+    use x11rb::{COPY_DEPTH_FROM_PARENT, CURRENT_TIME};
+
+    /// XK_v
+    const KEYSYM_V: u32 = 0x0076;
+
+    fn set_layout(args: &[&str]) {
+        let status = Command::new("setxkbmap")
+            .args(args)
+            .status()
+            .expect("setxkbmap is needed to switch layouts");
+        assert!(status.success(), "setxkbmap {:?} failed", args);
+    }
+
+    fn keysym(conn: &RustConnection, keycode: u8) -> u32 {
+        let mapping = conn
+            .get_keyboard_mapping(keycode, 1)
+            .unwrap()
+            .reply()
+            .unwrap();
+        mapping.keysyms[0]
+    }
+
+    /// A focused window receiving key presses
+    fn focused_window(conn: &RustConnection, screen: usize) {
+        let root = conn.setup().roots[screen].root;
+        let window = conn.generate_id().unwrap();
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            0,
+            0,
+            200,
+            200,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            0,
+            &CreateWindowAux::new().event_mask(EventMask::KEY_PRESS | EventMask::STRUCTURE_NOTIFY),
+        )
+        .unwrap();
+        conn.map_window(window).unwrap();
+        conn.flush().unwrap();
+        next_event(conn, |event| matches!(event, Event::MapNotify(_)));
+
+        conn.set_input_focus(InputFocus::PARENT, window, CURRENT_TIME)
+            .unwrap();
+        // Round trip, so the focus is set before any key arrives
+        conn.get_input_focus().unwrap().reply().unwrap();
+    }
+
+    fn next_event(conn: &RustConnection, wanted: impl Fn(&Event) -> bool) -> Event {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            match conn.poll_for_event().unwrap() {
+                Some(event) if wanted(&event) => return event,
+                Some(_) => {}
+                None => std::thread::sleep(Duration::from_millis(10)),
+            }
+        }
+        panic!("Timed out waiting for an X event");
+    }
+
+    fn paste_with_dvorak() {
+        let (conn, screen) = x11rb::connect(None).expect("no X server at $DISPLAY");
+
+        // Dvorak puts 'k' on the QWERTY V key and 'v' on the period key
+        let keycode = x11::v_keycode().unwrap();
+        assert_ne!(keycode, x11::PHYSICAL_V);
+        assert_eq!(keysym(&conn, keycode as u8), KEYSYM_V);
+        assert_ne!(keysym(&conn, x11::PHYSICAL_V as u8), KEYSYM_V);
+
+        focused_window(&conn, screen);
+        let mut enigo = Enigo::new(&Settings::default()).unwrap();
+        send_paste(&mut enigo, PasteKeys::Standard).unwrap();
+
+        // What the focused app matches as Ctrl+V: 'v' pressed with Control down
+        let control_press = |event: &Event| match event {
+            Event::KeyPress(press) => press.state.contains(KeyButMask::CONTROL),
+            _ => false,
+        };
+        let Event::KeyPress(KeyPressEvent { detail, state, .. }) = next_event(&conn, control_press)
+        else {
+            unreachable!()
+        };
+        assert_eq!(u16::from(detail), keycode);
+        assert_eq!(keysym(&conn, detail), KEYSYM_V);
+        assert!(!state.contains(KeyButMask::SHIFT));
+    }
+
+    /// Run with `xvfb-run cargo test -- --ignored paste_follows_the_layout`
+    #[test]
+    #[ignore = "needs an X server (e.g. xvfb-run) and setxkbmap"]
+    fn paste_follows_the_layout() {
+        set_layout(&["-layout", "us", "-variant", "dvorak"]);
+        let result = std::panic::catch_unwind(paste_with_dvorak);
+        set_layout(&["-layout", "us"]);
+        if let Err(panic) = result {
+            std::panic::resume_unwind(panic);
+        }
+    }
+}
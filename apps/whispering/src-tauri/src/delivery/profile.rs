@@ -0,0 +1,132 @@
+// Per-application delivery profiles.
+//
+// The frontend sends its profile list with every `write_text` call. The first profile
+// naming the focused app (see `target`) overrides the call's strategy and typing mode,
+// picks the paste shortcut and can press Return after the text.
+
+use crate::delivery::paste_key::PasteKeys;
+use crate::delivery::strategy::{DeliveryOptions, TextDeliveryStrategy, UnicodeHandling};
+use crate::delivery::target::FocusedApp;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveryProfile {
+    pub name: String,
+    /// Bundle identifiers, app names or window classes, compared case-insensitively
+    pub apps: Vec<String>,
+    #[serde(default)]
+    pub strategy: Option<TextDeliveryStrategy>,
+    #[serde(default)]
+    pub unicode: Option<UnicodeHandling>,
+    #[serde(default)]
+    pub paste_keys: PasteKeys,
+    /// Press Return after the text (sends the message in chat apps)
+    #[serde(default)]
+    pub trailing_newline: bool,
+}
+
+impl DeliveryProfile {
+    pub fn matches(&self, app: &FocusedApp) -> bool {
+        app.identifiers().any(|id| {
+            self.apps
+                .iter()
+                .any(|app| app.trim().eq_ignore_ascii_case(id))
+        })
+    }
+
+    /// Override the call's options with this profile's
+    pub fn apply(&self, options: &mut DeliveryOptions) {
+        if let Some(strategy) = self.strategy {
+            options.strategy = strategy;
+        }
+        if let Some(unicode) = self.unicode {
+            options.typing.unicode = unicode;
+        }
+    }
+}
+
+/// First profile naming `app`
+pub fn find_profile<'a>(
+    profiles: &'a [DeliveryProfile],
+    app: &FocusedApp,
+) -> Option<&'a DeliveryProfile> {
+    profiles.iter().find(|profile| profile.matches(app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::delivery::strategy::TextDeliveryStrategy::*;
+
+    fn app(app_id: &str, name: Option<&str>) -> FocusedApp {
+        FocusedApp {
+            app_id: app_id.to_string(),
+            name: name.map(str::to_string),
+            title: Some("Untitled".to_string()),
+        }
+    }
+
+    fn profiles() -> Vec<DeliveryProfile> {
+        serde_json::from_value(serde_json::json!([
+            { "name": "Terminals", "apps": [" gnome-terminal-server ", "Alacritty"],
+              "strategy": "typing", "pasteKeys": "controlShiftV" },
+            { "name": "Chat", "apps": ["com.tinyspeck.slackmacgap", "Slack"],
+              "unicode": "keystrokes", "trailingNewline": true },
+            { "name": "Anything Slack", "apps": ["slack"], "strategy": "directInsert" },
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn matches_app_id_or_name_ignoring_case() {
+        let profiles = profiles();
+        let terminals = &profiles[0];
+        assert!(terminals.matches(&app("Gnome-terminal-server", None)));
+        assert!(terminals.matches(&app("org.alacritty", Some("alacritty"))));
+        assert!(!terminals.matches(&app("firefox", Some("Firefox"))));
+
+        // The window title is not an identifier
+        let mut untitled = app("firefox", None);
+        untitled.title = Some("Alacritty".to_string());
+        assert!(!terminals.matches(&untitled));
+    }
+
+    #[test]
+    fn first_matching_profile_wins() {
+        let profiles = profiles();
+        let slack = app("COM.TINYSPECK.SLACKMACGAP", Some("Slack"));
+        assert_eq!(find_profile(&profiles, &slack).unwrap().name, "Chat");
+        assert_eq!(
+            find_profile(&profiles, &app("slack", None)).unwrap().name,
+            "Chat"
+        );
+        assert!(find_profile(&profiles, &app("firefox", Some("Firefox"))).is_none());
+        assert!(find_profile(&[], &slack).is_none());
+    }
+
+    #[test]
+    fn overrides_only_what_the_profile_sets() {
+        let profiles = profiles();
+        let defaults = DeliveryOptions {
+            strategy: ClipboardPaste,
+            fallback: false,
+            ..Default::default()
+        };
+
+        let mut options = defaults;
+        profiles[0].apply(&mut options);
+        assert_eq!(options.strategy, Typing);
+        assert_eq!(options.typing.unicode, UnicodeHandling::Unicode);
+        assert!(!options.fallback);
+        assert_eq!(profiles[0].paste_keys, PasteKeys::ControlShiftV);
+        assert!(!profiles[0].trailing_newline);
+
+        let mut options = defaults;
+        profiles[1].apply(&mut options);
+        assert_eq!(options.strategy, ClipboardPaste);
+        assert_eq!(options.typing.unicode, UnicodeHandling::Keystrokes);
+        assert_eq!(profiles[1].paste_keys, PasteKeys::Standard);
+        assert!(profiles[1].trailing_newline);
+    }
+}
@@ -0,0 +1,183 @@
+// How text gets into the focused application.
+//
+// Pasting is fast but needs the clipboard and an app that takes Ctrl/Cmd+V; terminals,
+// remote desktops and password fields often don't. Typing works nearly everywhere but
+// is slow, and direct insertion goes through the accessibility tree without any keys.
+// `write_text` tries the requested strategy first and falls back to the others.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Longest accepted delay between typed characters
+const MAX_CHAR_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TextDeliveryStrategy {
+    /// Put the text on the clipboard and press Cmd/Ctrl+V
+    ClipboardPaste,
+    /// Type the text key by key
+    Typing,
+    /// Insert into the focused text field through AT-SPI (Linux only)
+    DirectInsert,
+}
+
+/// Fallback order
+const ALL_STRATEGIES: [TextDeliveryStrategy; 3] = [
+    TextDeliveryStrategy::ClipboardPaste,
+    TextDeliveryStrategy::Typing,
+    TextDeliveryStrategy::DirectInsert,
+];
+
+/// How typed characters reach the target
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UnicodeHandling {
+    /// Any character, through the platform's Unicode input (fast, works in most apps)
+    #[default]
+    Unicode,
+    /// Real key presses for each character, for VMs and remote desktops that only
+    /// forward keys. Text with non-ASCII characters fails over to the next strategy.
+    Keystrokes,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TypingOptions {
+    pub char_delay_ms: u64, // Pause between characters, for apps that drop fast input
+    pub unicode: UnicodeHandling,
+}
+
+impl TypingOptions {
+    pub fn char_delay(&self) -> Duration {
+        Duration::from_millis(self.char_delay_ms)
+    }
+}
+
+/// Delivery settings of one `write_text` call
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeliveryOptions {
+    pub strategy: TextDeliveryStrategy,
+    pub fallback: bool, // Try the other strategies when `strategy` fails
+    pub typing: TypingOptions,
+}
+
+impl Default for DeliveryOptions {
+    fn default() -> Self {
+        Self {
+            strategy: TextDeliveryStrategy::ClipboardPaste,
+            fallback: true,
+            typing: TypingOptions::default(),
+        }
+    }
+}
+
+impl DeliveryOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.typing.char_delay() > MAX_CHAR_DELAY {
+            return Err(format!(
+                "Typing delay must be at most {} ms per character, got {}",
+                MAX_CHAR_DELAY.as_millis(),
+                self.typing.char_delay_ms
+            ));
+        }
+        Ok(())
+    }
+
+    /// Strategies to try, in order
+    pub fn chain(&self) -> Vec<TextDeliveryStrategy> {
+        let mut chain = vec![self.strategy];
+        if self.fallback {
+            chain.extend(ALL_STRATEGIES.iter().filter(|s| **s != self.strategy));
+        }
+        chain
+    }
+}
+
+/// A strategy that did not deliver the text
+#[derive(Debug)]
+pub struct DeliveryFailure {
+    pub message: String,
+    /// Some text may already be in the target; falling back would insert it twice
+    pub partial: bool,
+}
+
+impl DeliveryFailure {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            partial: false,
+        }
+    }
+
+    pub fn partial(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            partial: true,
+        }
+    }
+}
+
+impl From<String> for DeliveryFailure {
+    fn from(message: String) -> Self {
+        Self::new(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TextDeliveryStrategy::*;
+
+    fn options(strategy: TextDeliveryStrategy, fallback: bool) -> DeliveryOptions {
+        DeliveryOptions {
+            strategy,
+            fallback,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn falls_back_in_a_fixed_order() {
+        assert_eq!(
+            options(ClipboardPaste, true).chain(),
+            [ClipboardPaste, Typing, DirectInsert]
+        );
+        assert_eq!(
+            options(Typing, true).chain(),
+            [Typing, ClipboardPaste, DirectInsert]
+        );
+        assert_eq!(
+            options(DirectInsert, true).chain(),
+            [DirectInsert, ClipboardPaste, Typing]
+        );
+    }
+
+    #[test]
+    fn only_the_chosen_strategy_without_fallback() {
+        for strategy in ALL_STRATEGIES {
+            assert_eq!(options(strategy, false).chain(), [strategy]);
+        }
+    }
+
+    #[test]
+    fn defaults_from_partial_settings() {
+        let options: DeliveryOptions = serde_json::from_str(r#"{"strategy":"typing"}"#).unwrap();
+        assert_eq!(options.strategy, Typing);
+        assert!(options.fallback);
+        assert_eq!(options.typing.char_delay_ms, 0);
+        assert_eq!(options.typing.unicode, UnicodeHandling::Unicode);
+    }
+
+    #[test]
+    fn caps_the_typing_delay() {
+        let mut options = DeliveryOptions::default();
+        options.typing.char_delay_ms = MAX_CHAR_DELAY.as_millis() as u64;
+        assert!(options.validate().is_ok());
+
+        options.typing.char_delay_ms += 1;
+        let error = options.validate().unwrap_err();
+        assert!(error.contains("1000 ms"), "{}", error);
+    }
+}
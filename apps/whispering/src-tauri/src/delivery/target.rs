@@ -0,0 +1,185 @@
+// The application text is about to be delivered to.
+//
+// - macOS: NSWorkspace's frontmost application (bundle identifier and name)
+// - Linux (X11): WM_CLASS and title of the `_NET_ACTIVE_WINDOW`
+// Other platforms, and Wayland sessions without an X11 focus, report no app.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusedApp {
+    /// Bundle identifier on macOS, WM_CLASS class on Linux
+    pub app_id: String,
+    /// Localized app name on macOS, WM_CLASS instance on Linux
+    pub name: Option<String>,
+    pub title: Option<String>,
+}
+
+impl FocusedApp {
+    /// Identifiers a delivery profile can match
+    pub fn identifiers(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.app_id.as_str()).chain(self.name.as_deref())
+    }
+}
+
+/// Frontmost application, when the platform can tell
+#[tauri::command]
+pub fn get_focused_app() -> Result<Option<FocusedApp>, String> {
+    focused_app()
+}
+
+#[cfg(target_os = "macos")]
+pub fn focused_app() -> Result<Option<FocusedApp>, String> {
+    Ok(macos::focused_app())
+}
+
+#[cfg(target_os = "linux")]
+pub fn focused_app() -> Result<Option<FocusedApp>, String> {
+    x11::focused_app()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub fn focused_app() -> Result<Option<FocusedApp>, String> {
+    Ok(None)
+}
+
+// ============================================================================
+// MACOS
+// ============================================================================
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::FocusedApp;
+    use cocoa::base::{id, nil};
+    use objc::runtime::Class;
+    use objc::{msg_send, sel, sel_impl};
+    use std::ffi::{c_char, CStr};
+
+    pub fn focused_app() -> Option<FocusedApp> {
+        unsafe {
+            let workspace_class = Class::get("NSWorkspace")?;
+            let workspace: id = msg_send![workspace_class, sharedWorkspace];
+            let app: id = msg_send![workspace, frontmostApplication];
+            if app == nil {
+                return None;
+            }
+
+            let bundle_id: id = msg_send![app, bundleIdentifier];
+            let name: id = msg_send![app, localizedName];
+            let name = to_string(name);
+            // Unbundled executables only have a name
+            let app_id = to_string(bundle_id).or_else(|| name.clone())?;
+            Some(FocusedApp {
+                app_id,
+                name,
+                title: None,
+            })
+        }
+    }
+
+    unsafe fn to_string(string: id) -> Option<String> {
+        if string == nil {
+            return None;
+        }
+        let utf8: *const c_char = msg_send![string, UTF8String];
+        if utf8.is_null() {
+            return None;
+        }
+        Some(CStr::from_ptr(utf8).to_string_lossy().into_owned())
+    }
+}
+
+// ============================================================================
+// LINUX (X11)
+// ============================================================================
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use super::FocusedApp;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _, Window};
+
+    /// Longest property read, in 32-bit units
+    const MAX_PROPERTY_LENGTH: u32 = 1024;
+
+    pub fn focused_app() -> Result<Option<FocusedApp>, String> {
+        let (conn, screen) =
+            x11rb::connect(None).map_err(|e| format!("Failed to connect to X server: {}", e))?;
+        let root = conn.setup().roots[screen].root;
+
+        let active_window = intern(&conn, "_NET_ACTIVE_WINDOW")?;
+        let window = property(&conn, root, active_window, AtomEnum::WINDOW.into())?
+            .and_then(|value| {
+                value
+                    .get(..4)
+                    .map(|w| u32::from_ne_bytes([w[0], w[1], w[2], w[3]]))
+            })
+            .filter(|&window| window != 0);
+        let Some(window) = window else {
+            return Ok(None);
+        };
+
+        // "instance\0class\0"
+        let class = property(
+            &conn,
+            window,
+            AtomEnum::WM_CLASS.into(),
+            AtomEnum::STRING.into(),
+        )?
+        .unwrap_or_default();
+        let mut parts = class
+            .split(|&b| b == 0)
+            .filter(|part| !part.is_empty())
+            .map(|part| String::from_utf8_lossy(part).into_owned());
+        let instance = parts.next();
+        let Some(app_id) = parts.next().or_else(|| instance.clone()) else {
+            return Ok(None);
+        };
+
+        let net_wm_name = intern(&conn, "_NET_WM_NAME")?;
+        let utf8_string = intern(&conn, "UTF8_STRING")?;
+        let title = match property(&conn, window, net_wm_name, utf8_string)? {
+            Some(title) => Some(title),
+            None => property(
+                &conn,
+                window,
+                AtomEnum::WM_NAME.into(),
+                AtomEnum::STRING.into(),
+            )?,
+        }
+        .map(|title| String::from_utf8_lossy(&title).into_owned());
+
+        Ok(Some(FocusedApp {
+            app_id,
+            name: instance,
+            title,
+        }))
+    }
+
+    fn intern(conn: &impl Connection, name: &str) -> Result<u32, String> {
+        conn.intern_atom(false, name.as_bytes())
+            .map_err(|e| format!("Failed to intern {}: {}", name, e))?
+            .reply()
+            .map(|reply| reply.atom)
+            .map_err(|e| format!("Failed to intern {}: {}", name, e))
+    }
+
+    /// Raw value of `property`, None when unset or of another type
+    fn property(
+        conn: &impl Connection,
+        window: Window,
+        property: u32,
+        type_: u32,
+    ) -> Result<Option<Vec<u8>>, String> {
+        let reply = conn
+            .get_property(false, window, property, type_, 0, MAX_PROPERTY_LENGTH)
+            .map_err(|e| format!("Failed to read window property: {}", e))?
+            .reply()
+            .map_err(|e| format!("Failed to read window property: {}", e))?;
+        if reply.type_ != type_ || reply.value.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(reply.value))
+    }
+}
@@ -0,0 +1,128 @@
+// Simulated typing.
+//
+// Newlines and tabs are pressed as Return/Tab so terminals and forms see real keys.
+// Everything else is typed per `UnicodeHandling`: through enigo's Unicode text input,
+// or as the key producing each character in the active layout.
+
+use crate::delivery::strategy::{DeliveryFailure, TypingOptions, UnicodeHandling};
+use enigo::{Direction, Enigo, Key, Keyboard};
+use std::thread;
+
+/// Type `text` at the cursor
+pub fn type_text(
+    enigo: &mut Enigo,
+    text: &str,
+    options: &TypingOptions,
+) -> Result<(), DeliveryFailure> {
+    check_keystrokes(text, options)?;
+
+    let delay = options.char_delay();
+    let mut typed_any = false;
+    let fail = |typed_any: bool, message: String| {
+        if typed_any {
+            DeliveryFailure::partial(message)
+        } else {
+            DeliveryFailure::new(message)
+        }
+    };
+
+    // Without a delay, runs of plain characters go to enigo in one call
+    let chunks: Vec<&str> = if delay.is_zero() && options.unicode == UnicodeHandling::Unicode {
+        split_controls(text)
+    } else {
+        text.char_indices()
+            .map(|(i, c)| &text[i..i + c.len_utf8()])
+            .collect()
+    };
+
+    for chunk in chunks {
+        if typed_any && !delay.is_zero() {
+            thread::sleep(delay);
+        }
+        let result = match chunk {
+            // Typed as part of "\r\n"
+            "\r" => continue,
+            "\n" => enigo.key(Key::Return, Direction::Click),
+            "\t" => enigo.key(Key::Tab, Direction::Click),
+            _ => match options.unicode {
+                UnicodeHandling::Unicode => enigo.text(chunk),
+                UnicodeHandling::Keystrokes => chunk
+                    .chars()
+                    .try_for_each(|c| enigo.key(Key::Unicode(c), Direction::Click)),
+            },
+        };
+        result.map_err(|e| {
+            fail(
+                typed_any,
+                format!("Failed to type '{}': {}", chunk.escape_default(), e),
+            )
+        })?;
+        typed_any = true;
+    }
+    Ok(())
+}
+
+/// Keystroke typing covers ASCII only. Checked up front so the next strategy takes
+/// over before anything is typed.
+pub fn check_keystrokes(text: &str, options: &TypingOptions) -> Result<(), DeliveryFailure> {
+    if options.unicode == UnicodeHandling::Keystrokes {
+        if let Some(c) = text.chars().find(|c| !c.is_ascii()) {
+            return Err(DeliveryFailure::new(format!(
+                "'{}' has no key to type it with",
+                c
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Split `text` into runs of plain characters and single '\r', '\n', '\t'
+fn split_controls(text: &str) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        if matches!(c, '\r' | '\n' | '\t') {
+            if start < i {
+                chunks.push(&text[start..i]);
+            }
+            chunks.push(&text[i..i + 1]);
+            start = i + 1;
+        }
+    }
+    if start < text.len() {
+        chunks.push(&text[start..]);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_off_line_breaks_and_tabs() {
+        assert_eq!(
+            split_controls("one\r\ntwo\tthree\n"),
+            ["one", "\r", "\n", "two", "\t", "three", "\n"]
+        );
+        assert_eq!(split_controls("\n\n"), ["\n", "\n"]);
+        assert_eq!(split_controls("héllo wörld"), ["héllo wörld"]);
+        assert_eq!(split_controls("ü\tß"), ["ü", "\t", "ß"]);
+        assert!(split_controls("").is_empty());
+    }
+
+    #[test]
+    fn keystrokes_only_type_ascii() {
+        let keystrokes = TypingOptions {
+            unicode: UnicodeHandling::Keystrokes,
+            ..Default::default()
+        };
+        assert!(check_keystrokes("Hello, world!\r\n\t~", &keystrokes).is_ok());
+
+        let failure = check_keystrokes("naïve", &keystrokes).unwrap_err();
+        assert!(failure.message.contains('ï'), "{}", failure.message);
+        assert!(!failure.partial, "Nothing is typed before the check");
+
+        assert!(check_keystrokes("naïve 🎤", &TypingOptions::default()).is_ok());
+    }
+}
@@ -0,0 +1,417 @@
+// Key injection on Wayland.
+//
+// Wayland clients cannot send input to each other, and enigo's XTest events only reach
+// Xwayland windows. The first backend the system offers is used:
+// - wtype: virtual-keyboard protocol, layout independent (wlroots compositors, KDE);
+//   GNOME does not implement the protocol
+// - ydotool: uinput through the ydotoold daemon; US layout, ASCII only
+// - uinput: our own virtual keyboard, for shortcuts only (needs write access to
+//   /dev/uinput, like the Fn shortcut listener needs read access to /dev/input)
+// uinput backends press physical keys, read through the compositor's active layout.
+
+use crate::delivery::paste_key::PasteKeys;
+use crate::delivery::strategy::{DeliveryFailure, TypingOptions};
+use evdev::uinput::VirtualDevice;
+use evdev::{AttributeSet, KeyCode, KeyEvent};
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+/// Time for the compositor to pick up a new uinput device before it gets events
+const UINPUT_SETTLE: Duration = Duration::from_millis(200);
+
+/// Whether this is a Wayland session (Xwayland apps included)
+pub fn is_wayland_session() -> bool {
+    session_is_wayland(
+        env::var("XDG_SESSION_TYPE").ok().as_deref(),
+        env::var_os("WAYLAND_DISPLAY").is_some(),
+    )
+}
+
+/// XDG_SESSION_TYPE decides when set, otherwise a Wayland display does
+fn session_is_wayland(session_type: Option<&str>, wayland_display: bool) -> bool {
+    match session_type {
+        Some(session) if !session.is_empty() => session.eq_ignore_ascii_case("wayland"),
+        _ => wayland_display,
+    }
+}
+
+/// The key injection tools this system offers
+struct Tools {
+    gnome: bool,
+    wtype: Option<PathBuf>,
+    ydotool: Option<PathBuf>,
+    ydotoold_running: bool,
+}
+
+impl Tools {
+    fn probe() -> Self {
+        Self {
+            gnome: is_gnome(),
+            wtype: find_program("wtype"),
+            ydotool: find_program("ydotool"),
+            ydotoold_running: ydotool_running(),
+        }
+    }
+}
+
+pub enum WaylandBackend {
+    Wtype(PathBuf),
+    Ydotool(PathBuf),
+    Uinput(VirtualDevice),
+}
+
+impl WaylandBackend {
+    /// First available backend, or why there is none
+    pub fn detect() -> Result<Self, String> {
+        let mut missing = match Self::pick_tool(Tools::probe()) {
+            Ok(backend) => return Ok(backend),
+            Err(missing) => missing,
+        };
+
+        match uinput_keyboard() {
+            Ok(device) => return Ok(Self::Uinput(device)),
+            Err(e) => missing.push(format!("/dev/uinput is unavailable ({})", e)),
+        }
+
+        Err(format!(
+            "No way to send keys on Wayland: {}. Install wtype (Sway, Hyprland, KDE) or \
+             ydotool with ydotoold running, or allow your user to write /dev/uinput",
+            missing.join(", ")
+        ))
+    }
+
+    /// wtype, then ydotool, or why neither can be used
+    fn pick_tool(tools: Tools) -> Result<Self, Vec<String>> {
+        let mut missing = Vec::new();
+
+        if tools.gnome {
+            missing.push("wtype does not work on GNOME".to_string());
+        } else {
+            match tools.wtype {
+                Some(path) => return Ok(Self::Wtype(path)),
+                None => missing.push("wtype is not installed".to_string()),
+            }
+        }
+
+        match (tools.ydotool, tools.ydotoold_running) {
+            (Some(path), true) => return Ok(Self::Ydotool(path)),
+            (Some(_), false) => missing.push("ydotoold is not running".to_string()),
+            (None, _) => missing.push("ydotool is not installed".to_string()),
+        }
+
+        Err(missing)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Wtype(_) => "wtype",
+            Self::Ydotool(_) => "ydotool",
+            Self::Uinput(_) => "uinput",
+        }
+    }
+
+    pub fn paste(&mut self, keys: PasteKeys) -> Result<(), String> {
+        let (modifiers, key) = paste_chord(keys);
+        self.chord(modifiers, key)
+    }
+
+    pub fn press_return(&mut self) -> Result<(), String> {
+        self.chord(&[], KeyCode::KEY_ENTER)
+    }
+
+    /// Type `text`. Tool failures are taken as happening before any output, as both
+    /// tools fail on connecting rather than halfway through.
+    pub fn type_text(
+        &mut self,
+        text: &str,
+        options: &TypingOptions,
+    ) -> Result<(), DeliveryFailure> {
+        match self {
+            Self::Wtype(path) => {
+                run(path, wtype_type_args(text, options)).map_err(DeliveryFailure::new)
+            }
+            Self::Ydotool(path) => {
+                run(path, ydotool_type_args(text, options)?).map_err(DeliveryFailure::new)
+            }
+            Self::Uinput(_) => Err(DeliveryFailure::new(
+                "Typing on Wayland needs wtype or ydotool",
+            )),
+        }
+    }
+
+    fn chord(&mut self, modifiers: &[KeyCode], key: KeyCode) -> Result<(), String> {
+        match self {
+            Self::Wtype(path) => run(path, wtype_chord_args(modifiers, key)),
+            Self::Ydotool(path) => run(path, ydotool_chord_args(modifiers, key)),
+            Self::Uinput(device) => {
+                let mut emit = |key: KeyCode, value: i32| {
+                    device
+                        .emit(&[*KeyEvent::new(key, value)])
+                        .map_err(|e| format!("Failed to send key through uinput: {}", e))
+                };
+                for modifier in modifiers {
+                    emit(*modifier, 1)?;
+                }
+                let clicked = emit(key, 1).and_then(|_| emit(key, 0));
+                // Release the modifiers even when the key fails, or they stay stuck down
+                for modifier in modifiers.iter().rev() {
+                    emit(*modifier, 0)?;
+                }
+                clicked
+            }
+        }
+    }
+}
+
+/// Modifiers and key of a paste shortcut
+fn paste_chord(keys: PasteKeys) -> (&'static [KeyCode], KeyCode) {
+    match keys {
+        PasteKeys::Standard => (&[KeyCode::KEY_LEFTCTRL], KeyCode::KEY_V),
+        PasteKeys::ControlShiftV => (
+            &[KeyCode::KEY_LEFTCTRL, KeyCode::KEY_LEFTSHIFT],
+            KeyCode::KEY_V,
+        ),
+        PasteKeys::ShiftInsert => (&[KeyCode::KEY_LEFTSHIFT], KeyCode::KEY_INSERT),
+    }
+}
+
+/// wtype arguments: hold the modifiers, click the key, release in reverse
+fn wtype_chord_args(modifiers: &[KeyCode], key: KeyCode) -> Vec<&'static str> {
+    let mut args = Vec::new();
+    for modifier in modifiers {
+        args.extend(["-M", wtype_name(*modifier)]);
+    }
+    args.extend(["-k", wtype_name(key)]);
+    for modifier in modifiers.iter().rev() {
+        args.extend(["-m", wtype_name(*modifier)]);
+    }
+    args
+}
+
+/// ydotool arguments: `code:1` presses and `code:0` releases, in chord order
+fn ydotool_chord_args(modifiers: &[KeyCode], key: KeyCode) -> Vec<String> {
+    let mut args = vec!["key".to_string()];
+    args.extend(modifiers.iter().map(|m| format!("{}:1", m.code())));
+    args.push(format!("{}:1", key.code()));
+    args.push(format!("{}:0", key.code()));
+    args.extend(modifiers.iter().rev().map(|m| format!("{}:0", m.code())));
+    args
+}
+
+fn wtype_type_args(text: &str, options: &TypingOptions) -> Vec<String> {
+    vec![
+        "-d".to_string(),
+        options.char_delay_ms.to_string(),
+        "--".to_string(),
+        text.to_string(),
+    ]
+}
+
+/// ydotool only types ASCII (through a US layout)
+fn ydotool_type_args(text: &str, options: &TypingOptions) -> Result<Vec<String>, DeliveryFailure> {
+    if let Some(c) = text.chars().find(|c| !c.is_ascii()) {
+        return Err(DeliveryFailure::new(format!("ydotool cannot type '{}'", c)));
+    }
+    Ok(vec![
+        "type".to_string(),
+        format!("--key-delay={}", options.char_delay_ms),
+        "--".to_string(),
+        text.to_string(),
+    ])
+}
+
+/// Names wtype takes for the keys used in chords
+fn wtype_name(key: KeyCode) -> &'static str {
+    match key {
+        KeyCode::KEY_LEFTCTRL => "ctrl",
+        KeyCode::KEY_LEFTSHIFT => "shift",
+        KeyCode::KEY_V => "v",
+        KeyCode::KEY_INSERT => "Insert",
+        _ => "Return", // KEY_ENTER
+    }
+}
+
+fn run<I, S>(program: &PathBuf, args: I) -> Result<(), String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<std::ffi::OsStr>,
+{
+    let name = program
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", name, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+fn is_gnome() -> bool {
+    env::var("XDG_CURRENT_DESKTOP")
+        .map(|desktops| desktops.split(':').any(|d| d.eq_ignore_ascii_case("gnome")))
+        .unwrap_or(false)
+}
+
+fn find_program(name: &str) -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+    env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Whether ydotoold's socket exists where ydotool looks for it
+fn ydotool_running() -> bool {
+    let mut sockets: Vec<PathBuf> = Vec::new();
+    if let Some(socket) = env::var_os("YDOTOOL_SOCKET") {
+        sockets.push(socket.into());
+    }
+    if let Some(runtime_dir) = env::var_os("XDG_RUNTIME_DIR") {
+        sockets.push(PathBuf::from(runtime_dir).join(".ydotool_socket"));
+    }
+    sockets.push("/tmp/.ydotool_socket".into());
+    sockets.iter().any(|socket| socket.exists())
+}
+
+fn uinput_keyboard() -> std::io::Result<VirtualDevice> {
+    let mut keys = AttributeSet::<KeyCode>::new();
+    for key in [
+        KeyCode::KEY_LEFTCTRL,
+        KeyCode::KEY_LEFTSHIFT,
+        KeyCode::KEY_V,
+        KeyCode::KEY_INSERT,
+        KeyCode::KEY_ENTER,
+    ] {
+        keys.insert(key);
+    }
+    let device = VirtualDevice::builder()?
+        .name("NoteFlux virtual keyboard")
+        .with_keys(&keys)?
+        .build()?;
+    thread::sleep(UINPUT_SETTLE);
+    Ok(device)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::delivery::strategy::UnicodeHandling;
+
+    fn options(char_delay_ms: u64) -> TypingOptions {
+        TypingOptions {
+            char_delay_ms,
+            unicode: UnicodeHandling::Unicode,
+        }
+    }
+
+    fn tools(gnome: bool, wtype: bool, ydotool: bool, ydotoold_running: bool) -> Tools {
+        Tools {
+            gnome,
+            wtype: wtype.then(|| PathBuf::from("/usr/bin/wtype")),
+            ydotool: ydotool.then(|| PathBuf::from("/usr/bin/ydotool")),
+            ydotoold_running,
+        }
+    }
+
+    fn picked(tools: Tools) -> Result<&'static str, Vec<String>> {
+        WaylandBackend::pick_tool(tools).map(|backend| backend.name())
+    }
+
+    #[test]
+    fn session_type_decides_before_the_display() {
+        assert!(session_is_wayland(Some("wayland"), false));
+        assert!(session_is_wayland(Some("Wayland"), false));
+        assert!(!session_is_wayland(Some("x11"), true));
+        assert!(!session_is_wayland(Some("tty"), true));
+
+        // Unset or empty falls back to WAYLAND_DISPLAY
+        assert!(session_is_wayland(None, true));
+        assert!(session_is_wayland(Some(""), true));
+        assert!(!session_is_wayland(None, false));
+    }
+
+    #[test]
+    fn prefers_wtype_then_ydotool() {
+        assert_eq!(picked(tools(false, true, true, true)), Ok("wtype"));
+        assert_eq!(picked(tools(false, false, true, true)), Ok("ydotool"));
+        // GNOME lacks the virtual-keyboard protocol wtype needs
+        assert_eq!(picked(tools(true, true, true, true)), Ok("ydotool"));
+    }
+
+    #[test]
+    fn explains_why_no_tool_is_usable() {
+        assert_eq!(
+            picked(tools(false, false, true, false)),
+            Err(vec![
+                "wtype is not installed".to_string(),
+                "ydotoold is not running".to_string(),
+            ])
+        );
+        assert_eq!(
+            picked(tools(true, true, false, true)),
+            Err(vec![
+                "wtype does not work on GNOME".to_string(),
+                "ydotool is not installed".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn wtype_chords_release_in_reverse() {
+        let (modifiers, key) = paste_chord(PasteKeys::ControlShiftV);
+        assert_eq!(
+            wtype_chord_args(modifiers, key),
+            ["-M", "ctrl", "-M", "shift", "-k", "v", "-m", "shift", "-m", "ctrl"]
+        );
+
+        let (modifiers, key) = paste_chord(PasteKeys::ShiftInsert);
+        assert_eq!(
+            wtype_chord_args(modifiers, key),
+            ["-M", "shift", "-k", "Insert", "-m", "shift"]
+        );
+        assert_eq!(wtype_chord_args(&[], KeyCode::KEY_ENTER), ["-k", "Return"]);
+    }
+
+    #[test]
+    fn ydotool_chords_use_key_codes() {
+        let (modifiers, key) = paste_chord(PasteKeys::Standard);
+        // KEY_LEFTCTRL = 29, KEY_V = 47
+        assert_eq!(
+            ydotool_chord_args(modifiers, key),
+            ["key", "29:1", "47:1", "47:0", "29:0"]
+        );
+        assert_eq!(
+            ydotool_chord_args(&[], KeyCode::KEY_ENTER),
+            ["key", "28:1", "28:0"]
+        );
+    }
+
+    #[test]
+    fn typing_args_end_option_parsing() {
+        assert_eq!(
+            wtype_type_args("-n text", &options(5)),
+            ["-d", "5", "--", "-n text"]
+        );
+        assert_eq!(
+            ydotool_type_args("--help", &options(0)).unwrap(),
+            ["type", "--key-delay=0", "--", "--help"]
+        );
+    }
+
+    #[test]
+    fn ydotool_rejects_non_ascii() {
+        let failure = ydotool_type_args("café", &options(0)).unwrap_err();
+        assert_eq!(failure.message, "ydotool cannot type 'é'");
+        assert!(!failure.partial);
+    }
+}
@@ -0,0 +1,1082 @@
+// Platform-independent shortcut state machine.
+//
+// Backends translate native key events into `KeyEvent`s and feed them to a
+// `ShortcutMachine`, which turns them into taps, holds, double taps and combos.
+// Nothing here touches the OS, so the same logic runs on every platform.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+// ============================================================================
+// KEYS & EVENTS
+// ============================================================================
+
+/// Modifier names in the order they appear in accelerators
+pub const MODIFIER_ORDER: [&str; 6] = ["Fn", "Control", "Alt", "Shift", "Super", "CapsLock"];
+
+/// Keys that can act as a shortcut on their own.
+///
+/// The unsided variants are for platforms that only report modifier flags (macOS).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Modifier {
+    Fn,
+    Control,
+    LeftControl,
+    RightControl,
+    Alt,
+    LeftAlt,
+    RightAlt,
+    Shift,
+    LeftShift,
+    RightShift,
+    Super,
+    LeftSuper,
+    RightSuper,
+    CapsLock,
+}
+
+impl Modifier {
+    /// Name used in accelerators ("RightControl")
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Fn => "Fn",
+            Self::Control => "Control",
+            Self::LeftControl => "LeftControl",
+            Self::RightControl => "RightControl",
+            Self::Alt => "Alt",
+            Self::LeftAlt => "LeftAlt",
+            Self::RightAlt => "RightAlt",
+            Self::Shift => "Shift",
+            Self::LeftShift => "LeftShift",
+            Self::RightShift => "RightShift",
+            Self::Super => "Super",
+            Self::LeftSuper => "LeftSuper",
+            Self::RightSuper => "RightSuper",
+            Self::CapsLock => "CapsLock",
+        }
+    }
+
+    /// Name without the side ("Control") - combos do not distinguish sides
+    pub fn generic_name(self) -> &'static str {
+        generic_modifier(self.name())
+    }
+}
+
+/// A key as seen by the state machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    Modifier(Modifier),
+    /// Any other key, with its accelerator name when it can end a combo ("A", "Space")
+    Other(Option<&'static str>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyEvent {
+    Down(Key), // Auto-repeats may be passed through, they are ignored
+    Up(Key),
+}
+
+/// What the user did, in terms of bound triggers and accelerators
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShortcutAction {
+    /// A bound modifier went down with no other modifier held
+    Pressed { trigger: String },
+    /// ...and came back up. `was_tap` when it was quick and nothing else was pressed.
+    Released { trigger: String, was_tap: bool },
+    /// Quick press and release (deferred by the double-tap window when a double tap is bound)
+    Tap { trigger: String },
+    /// Two taps within the double-tap window
+    DoubleTap { trigger: String },
+    /// Held past the hold threshold without other keys
+    HoldStart { trigger: String },
+    /// Released after `HoldStart`
+    HoldEnd { trigger: String },
+    /// A bound accelerator: a key pressed while modifiers were held
+    Combo { accelerator: String },
+}
+
+/// Timing thresholds of the state machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShortcutConfig {
+    /// Longest press that still counts as a tap (at most `hold_min`)
+    pub tap_max: Duration,
+    /// How long a trigger must be held before `HoldStart`
+    pub hold_min: Duration,
+    /// How long to wait after a tap for a second one (only for triggers with a double tap bound)
+    pub double_tap_window: Duration,
+}
+
+impl Default for ShortcutConfig {
+    fn default() -> Self {
+        Self {
+            tap_max: Duration::from_millis(300),
+            hold_min: Duration::from_millis(300),
+            double_tap_window: Duration::from_millis(300),
+        }
+    }
+}
+
+/// Gesture timing from the frontend, in milliseconds (missing values keep the defaults)
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GestureTiming {
+    pub tap_max_ms: Option<u64>,
+    pub hold_min_ms: Option<u64>,
+    pub double_tap_window_ms: Option<u64>,
+}
+
+impl TryFrom<GestureTiming> for ShortcutConfig {
+    type Error = String;
+
+    fn try_from(timing: GestureTiming) -> Result<Self, String> {
+        let defaults = Self::default();
+        let window = |value: Option<u64>, default: Duration, name: &str| match value {
+            None => Ok(default),
+            Some(ms @ 1..=5000) => Ok(Duration::from_millis(ms)),
+            Some(ms) => Err(format!(
+                "{} must be between 1 and 5000 ms, got {}",
+                name, ms
+            )),
+        };
+
+        let config = Self {
+            tap_max: window(timing.tap_max_ms, defaults.tap_max, "tapMaxMs")?,
+            hold_min: window(timing.hold_min_ms, defaults.hold_min, "holdMinMs")?,
+            double_tap_window: window(
+                timing.double_tap_window_ms,
+                defaults.double_tap_window,
+                "doubleTapWindowMs",
+            )?,
+        };
+        // Otherwise one press could be both a hold and a tap
+        if config.tap_max > config.hold_min {
+            return Err(format!(
+                "tapMaxMs ({} ms) must not exceed holdMinMs ({} ms)",
+                config.tap_max.as_millis(),
+                config.hold_min.as_millis()
+            ));
+        }
+        Ok(config)
+    }
+}
+
+// ============================================================================
+// BINDINGS
+// ============================================================================
+
+/// Gesture a command can be bound to, on top of a trigger's plain press/release/tap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Gesture {
+    Tap,       // Emitted as fn-shortcut-triggered
+    DoubleTap, // Emitted as fn-shortcut-triggered
+    Hold,      // Emitted as fn-shortcut-pressed / fn-shortcut-released
+}
+
+/// Registered shortcuts: normalized accelerator -> command_id, plain or per gesture.
+///
+/// A plain binding receives every press, release and tap of its trigger (and combos).
+/// Gesture bindings only receive their gesture, so one key can serve several commands.
+#[derive(Debug, Default)]
+pub struct ShortcutBindings {
+    plain: HashMap<String, String>,
+    gestures: HashMap<(String, Gesture), String>,
+}
+
+impl ShortcutBindings {
+    /// Bind a normalized accelerator (see `normalize_accelerator`)
+    pub fn insert(
+        &mut self,
+        accelerator: String,
+        gesture: Option<Gesture>,
+        command_id: String,
+    ) -> Result<(), String> {
+        match gesture {
+            None => {
+                self.plain.insert(accelerator, command_id);
+            }
+            Some(_) if accelerator.contains('+') => {
+                return Err(format!(
+                    "Gestures need a single modifier key, not a combo ({})",
+                    accelerator
+                ));
+            }
+            Some(gesture) => {
+                self.gestures.insert((accelerator, gesture), command_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove one gesture binding, or every binding of the accelerator when `gesture` is `None`
+    pub fn remove(&mut self, accelerator: &str, gesture: Option<Gesture>) {
+        match gesture {
+            Some(gesture) => {
+                self.gestures.remove(&(accelerator.to_string(), gesture));
+            }
+            None => {
+                self.plain.remove(accelerator);
+                self.gestures.retain(|(bound, _), _| bound != accelerator);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.plain.len() + self.gestures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether anything is bound to the trigger or accelerator
+    pub fn is_bound(&self, accelerator: &str) -> bool {
+        self.plain.contains_key(accelerator)
+            || self.gestures.keys().any(|(bound, _)| bound == accelerator)
+    }
+
+    fn has_gesture(&self, trigger: &str, gesture: Gesture) -> bool {
+        self.gestures.contains_key(&(trigger.to_string(), gesture))
+    }
+
+    /// Frontend events for an action: (event name, command_id)
+    pub fn resolve(&self, action: &ShortcutAction) -> Vec<(&'static str, String)> {
+        let plain = |event: &'static str, accelerator: &str| {
+            self.plain
+                .get(accelerator)
+                .map(|command_id| (event, command_id.clone()))
+        };
+        let gesture = |event: &'static str, trigger: &str, gesture: Gesture| {
+            self.gestures
+                .get(&(trigger.to_string(), gesture))
+                .map(|command_id| (event, command_id.clone()))
+        };
+
+        let events = match action {
+            ShortcutAction::Pressed { trigger } => [plain("fn-shortcut-pressed", trigger), None],
+            ShortcutAction::Released { trigger, .. } => {
+                [plain("fn-shortcut-released", trigger), None]
+            }
+            ShortcutAction::Tap { trigger } => [
+                plain("fn-shortcut-triggered", trigger),
+                gesture("fn-shortcut-triggered", trigger, Gesture::Tap),
+            ],
+            ShortcutAction::DoubleTap { trigger } => [
+                gesture("fn-shortcut-triggered", trigger, Gesture::DoubleTap),
+                None,
+            ],
+            ShortcutAction::HoldStart { trigger } => {
+                [gesture("fn-shortcut-pressed", trigger, Gesture::Hold), None]
+            }
+            ShortcutAction::HoldEnd { trigger } => [
+                gesture("fn-shortcut-released", trigger, Gesture::Hold),
+                None,
+            ],
+            ShortcutAction::Combo { accelerator } => {
+                [plain("fn-shortcut-triggered", accelerator), None]
+            }
+        };
+        events.into_iter().flatten().collect()
+    }
+}
+
+// ============================================================================
+// STATE MACHINE
+// ============================================================================
+
+/// A bound modifier that went down on its own and is still held
+#[derive(Debug)]
+struct ActiveTrigger {
+    modifier: Modifier,
+    trigger: String,
+    pressed_at: Instant,
+    interrupted: bool, // Another key went down meanwhile
+    holding: bool,     // HoldStart was reported
+    second_tap: bool,  // Pressed within the double-tap window of a tap
+}
+
+/// A tap waiting to see whether a second one follows
+#[derive(Debug)]
+struct PendingTap {
+    trigger: String,
+    released_at: Instant,
+}
+
+/// Turns key events into shortcut actions.
+///
+/// `handle` and `tick` take the time explicitly; backends call `tick` at
+/// `next_deadline` so holds and deferred taps are reported without further input.
+#[derive(Debug, Default)]
+pub struct ShortcutMachine {
+    config: ShortcutConfig,
+    held: HashSet<Modifier>,
+    active: Option<ActiveTrigger>,
+    pending_tap: Option<PendingTap>,
+}
+
+impl ShortcutMachine {
+    pub fn new(config: ShortcutConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    pub fn config(&self) -> ShortcutConfig {
+        self.config
+    }
+
+    pub fn set_config(&mut self, config: ShortcutConfig) {
+        self.config = config;
+    }
+
+    /// Forget all key state (e.g. the listener was restarted and may have missed releases)
+    pub fn reset(&mut self) {
+        self.held.clear();
+        self.active = None;
+        self.pending_tap = None;
+    }
+
+    /// Apply one key event against the registered `bindings`
+    pub fn handle(
+        &mut self,
+        event: KeyEvent,
+        now: Instant,
+        bindings: &ShortcutBindings,
+    ) -> Vec<ShortcutAction> {
+        // Timers that expired before this event come first
+        let mut actions = self.tick(now);
+
+        match event {
+            KeyEvent::Down(Key::Modifier(modifier)) => {
+                if !self.held.insert(modifier) {
+                    return actions; // Auto-repeat, or the same key on a second keyboard
+                }
+
+                if let Some(active) = self.active.as_mut() {
+                    active.interrupted = true;
+                    return actions;
+                }
+                if self.held.len() > 1 {
+                    return actions;
+                }
+
+                let trigger = [modifier.name(), modifier.generic_name()]
+                    .into_iter()
+                    .find(|name| bindings.is_bound(name));
+                if let Some(trigger) = trigger {
+                    let second_tap = self
+                        .pending_tap
+                        .as_ref()
+                        .is_some_and(|pending| pending.trigger == trigger);
+                    if !second_tap {
+                        self.flush_pending_tap(&mut actions);
+                    }
+
+                    self.active = Some(ActiveTrigger {
+                        modifier,
+                        trigger: trigger.to_string(),
+                        pressed_at: now,
+                        interrupted: false,
+                        holding: false,
+                        second_tap,
+                    });
+                    actions.push(ShortcutAction::Pressed {
+                        trigger: trigger.to_string(),
+                    });
+                }
+            }
+
+            KeyEvent::Down(Key::Other(name)) => {
+                // Typing after a tap means no double tap is coming
+                self.flush_pending_tap(&mut actions);
+
+                if let Some(active) = self.active.as_mut() {
+                    active.interrupted = true;
+                }
+
+                if let Some(accelerator) = name.and_then(|name| self.combo_accelerator(name)) {
+                    if bindings.is_bound(&accelerator) {
+                        actions.push(ShortcutAction::Combo { accelerator });
+                    }
+                }
+            }
+
+            KeyEvent::Up(Key::Modifier(modifier)) => {
+                self.held.remove(&modifier);
+
+                let Some(active) = self.active.take_if(|active| active.modifier == modifier) else {
+                    return actions;
+                };
+
+                let was_tap = !active.interrupted
+                    && !active.holding
+                    && now.duration_since(active.pressed_at) < self.config.tap_max;
+                actions.push(ShortcutAction::Released {
+                    trigger: active.trigger.clone(),
+                    was_tap,
+                });
+
+                if active.holding {
+                    actions.push(ShortcutAction::HoldEnd {
+                        trigger: active.trigger.clone(),
+                    });
+                }
+
+                if active.second_tap {
+                    self.pending_tap = None;
+                    actions.push(if was_tap {
+                        ShortcutAction::DoubleTap {
+                            trigger: active.trigger,
+                        }
+                    } else {
+                        // The first tap stands on its own
+                        ShortcutAction::Tap {
+                            trigger: active.trigger,
+                        }
+                    });
+                } else if was_tap {
+                    if bindings.has_gesture(&active.trigger, Gesture::DoubleTap) {
+                        self.pending_tap = Some(PendingTap {
+                            trigger: active.trigger,
+                            released_at: now,
+                        });
+                    } else {
+                        actions.push(ShortcutAction::Tap {
+                            trigger: active.trigger,
+                        });
+                    }
+                }
+            }
+
+            KeyEvent::Up(Key::Other(_)) => {}
+        }
+
+        actions
+    }
+
+    /// Report holds and deferred taps whose time has come
+    pub fn tick(&mut self, now: Instant) -> Vec<ShortcutAction> {
+        let mut actions = Vec::new();
+
+        if let Some(active) = self.active.as_mut() {
+            if !active.holding
+                && !active.interrupted
+                && now.duration_since(active.pressed_at) >= self.config.hold_min
+            {
+                active.holding = true;
+                actions.push(ShortcutAction::HoldStart {
+                    trigger: active.trigger.clone(),
+                });
+            }
+        }
+
+        // A second press that is still down keeps the first tap waiting
+        let second_press_down = self.active.as_ref().is_some_and(|active| active.second_tap);
+        if !second_press_down && self.pending_tap_deadline().is_some_and(|at| now >= at) {
+            self.flush_pending_tap(&mut actions);
+        }
+
+        actions
+    }
+
+    /// When `tick` next has something to report, if ever
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let hold = self
+            .active
+            .as_ref()
+            .filter(|active| !active.holding && !active.interrupted)
+            .map(|active| active.pressed_at + self.config.hold_min);
+        let tap = self
+            .pending_tap_deadline()
+            .filter(|_| !self.active.as_ref().is_some_and(|active| active.second_tap));
+
+        match (hold, tap) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn pending_tap_deadline(&self) -> Option<Instant> {
+        let pending = self.pending_tap.as_ref()?;
+        Some(pending.released_at + self.config.double_tap_window)
+    }
+
+    fn flush_pending_tap(&mut self, actions: &mut Vec<ShortcutAction>) {
+        if let Some(pending) = self.pending_tap.take() {
+            actions.push(ShortcutAction::Tap {
+                trigger: pending.trigger,
+            });
+        }
+    }
+
+    /// Accelerator for `key` pressed with the modifiers currently held
+    fn combo_accelerator(&self, key: &str) -> Option<String> {
+        if self.held.is_empty() {
+            return None;
+        }
+        let held: HashSet<&str> = self.held.iter().map(|m| m.generic_name()).collect();
+
+        let mut parts: Vec<&str> = MODIFIER_ORDER
+            .iter()
+            .copied()
+            .filter(|modifier| held.contains(modifier))
+            .collect();
+        parts.push(key);
+        Some(parts.join("+"))
+    }
+}
+
+// ============================================================================
+// ACCELERATORS
+// ============================================================================
+
+/// Names of the keys that can end a combo
+pub const COMBO_KEY_NAMES: [&str; 64] = [
+    "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q", "R", "S",
+    "T", "U", "V", "W", "X", "Y", "Z", "0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "-", "=",
+    "[", "]", ";", "'", "`", "\\", ",", ".", "/", "Return", "Tab", "Space", "Delete", "Escape",
+    "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12",
+];
+
+/// Canonical form of an accelerator: a single modifier ("RightControl", "Fn"), or
+/// modifiers in `MODIFIER_ORDER` followed by one key ("Fn+Control+A")
+pub fn normalize_accelerator(accelerator: &str) -> Result<String, String> {
+    let tokens: Vec<&str> = accelerator.split('+').map(str::trim).collect();
+
+    if let [single] = tokens.as_slice() {
+        return canonical_modifier(single)
+            .map(str::to_string)
+            .ok_or_else(|| format!("'{}' is not a modifier key", single));
+    }
+
+    let (key, modifiers) = tokens
+        .split_last()
+        .ok_or_else(|| "Empty shortcut".to_string())?;
+    let mut generic: Vec<&str> = modifiers
+        .iter()
+        .map(|token| {
+            canonical_modifier(token)
+                .map(generic_modifier)
+                .ok_or_else(|| format!("'{}' is not a modifier key", token))
+        })
+        .collect::<Result<_, _>>()?;
+    generic.sort_by_key(|modifier| MODIFIER_ORDER.iter().position(|m| m == modifier));
+    generic.dedup();
+
+    let key = COMBO_KEY_NAMES
+        .iter()
+        .find(|name| name.eq_ignore_ascii_case(key))
+        .ok_or_else(|| format!("Unsupported key '{}' in {}", key, accelerator))?;
+    generic.push(key);
+
+    Ok(generic.join("+"))
+}
+
+/// Canonical form of a global-shortcut plugin accelerator ("CommandOrControl+Shift+KeyR"),
+/// comparable with `normalize_accelerator`. Keys outside `COMBO_KEY_NAMES` are kept as given.
+pub fn normalize_global_accelerator(accelerator: &str) -> Result<String, String> {
+    let tokens: Vec<&str> = accelerator.split('+').map(str::trim).collect();
+    let (key, modifiers) = tokens
+        .split_last()
+        .filter(|(key, _)| !key.is_empty())
+        .ok_or_else(|| "Empty shortcut".to_string())?;
+
+    let mut generic: Vec<&str> = modifiers
+        .iter()
+        .map(|token| match token.to_ascii_lowercase().as_str() {
+            "commandorcontrol" | "cmdorctrl" | "commandorctrl" | "cmdorcontrol" => {
+                Ok(if cfg!(target_os = "macos") {
+                    "Super"
+                } else {
+                    "Control"
+                })
+            }
+            _ => canonical_modifier(token)
+                .map(generic_modifier)
+                .ok_or_else(|| format!("'{}' is not a modifier key", token)),
+        })
+        .collect::<Result<_, _>>()?;
+    generic.sort_by_key(|modifier| MODIFIER_ORDER.iter().position(|m| m == modifier));
+    generic.dedup();
+
+    // The plugin also takes DOM key codes ("KeyA", "Digit1") and a few aliases
+    let key = key
+        .strip_prefix("Key")
+        .or_else(|| key.strip_prefix("Digit"))
+        .filter(|rest| rest.len() == 1)
+        .unwrap_or(key);
+    let key = match key.to_ascii_lowercase().as_str() {
+        "enter" => "Return",
+        "esc" => "Escape",
+        "backspace" => "Delete",
+        _ => COMBO_KEY_NAMES
+            .iter()
+            .find(|name| name.eq_ignore_ascii_case(key))
+            .copied()
+            .unwrap_or(key),
+    };
+    generic.push(key);
+
+    Ok(generic.join("+"))
+}
+
+/// Canonical modifier name for any of the names the frontend may use
+fn canonical_modifier(token: &str) -> Option<&'static str> {
+    let name = match token.to_ascii_lowercase().as_str() {
+        "fn" => "Fn",
+        "control" | "ctrl" => "Control",
+        "leftcontrol" | "leftctrl" => "LeftControl",
+        "rightcontrol" | "rightctrl" => "RightControl",
+        "alt" | "option" => "Alt",
+        "leftalt" => "LeftAlt",
+        "rightalt" | "altgr" => "RightAlt",
+        "shift" => "Shift",
+        "leftshift" => "LeftShift",
+        "rightshift" => "RightShift",
+        "super" | "meta" | "command" | "cmd" | "win" => "Super",
+        "leftsuper" | "leftmeta" => "LeftSuper",
+        "rightsuper" | "rightmeta" => "RightSuper",
+        "capslock" => "CapsLock",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// "RightControl" -> "Control"
+fn generic_modifier(name: &'static str) -> &'static str {
+    name.trim_start_matches("Left").trim_start_matches("Right")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ShortcutAction::*;
+
+    const RIGHT_CONTROL: Key = Key::Modifier(Modifier::RightControl);
+    const LEFT_CONTROL: Key = Key::Modifier(Modifier::LeftControl);
+    const RIGHT_SHIFT: Key = Key::Modifier(Modifier::RightShift);
+    const A: Key = Key::Other(Some("A"));
+
+    /// Thresholds that differ from each other, so each edge is tested on its own
+    const CONFIG: ShortcutConfig = ShortcutConfig {
+        tap_max: Duration::from_millis(200),
+        hold_min: Duration::from_millis(400),
+        double_tap_window: Duration::from_millis(250),
+    };
+
+    /// A machine driven by a fake clock, ticked at its deadlines like the backends do
+    struct Harness {
+        machine: ShortcutMachine,
+        bindings: ShortcutBindings,
+        now: Instant,
+    }
+
+    impl Harness {
+        fn new(bound: &[(&str, Option<Gesture>)]) -> Self {
+            let mut bindings = ShortcutBindings::default();
+            for &(accelerator, gesture) in bound {
+                let accelerator = normalize_accelerator(accelerator).unwrap();
+                bindings
+                    .insert(accelerator, gesture, "command".to_string())
+                    .unwrap();
+            }
+            Self {
+                machine: ShortcutMachine::new(CONFIG),
+                bindings,
+                now: Instant::now(),
+            }
+        }
+
+        /// Let `ms` pass, collecting what the timers report
+        fn wait(&mut self, ms: u64) -> Vec<ShortcutAction> {
+            let until = self.now + Duration::from_millis(ms);
+            let mut actions = Vec::new();
+            while let Some(deadline) = self.machine.next_deadline().filter(|at| *at <= until) {
+                actions.extend(self.machine.tick(deadline));
+            }
+            self.now = until;
+            actions
+        }
+
+        fn down(&mut self, key: Key) -> Vec<ShortcutAction> {
+            self.machine
+                .handle(KeyEvent::Down(key), self.now, &self.bindings)
+        }
+
+        fn up(&mut self, key: Key) -> Vec<ShortcutAction> {
+            self.machine
+                .handle(KeyEvent::Up(key), self.now, &self.bindings)
+        }
+
+        /// Press and release `key`, held for `ms`
+        fn press(&mut self, key: Key, ms: u64) -> Vec<ShortcutAction> {
+            let mut actions = self.down(key);
+            actions.extend(self.wait(ms));
+            actions.extend(self.up(key));
+            actions
+        }
+    }
+
+    fn trigger(name: &str) -> String {
+        name.to_string()
+    }
+
+    #[test]
+    fn quick_press_is_a_tap() {
+        let mut keys = Harness::new(&[("RightControl", None)]);
+        assert_eq!(
+            keys.press(RIGHT_CONTROL, 100),
+            [
+                Pressed {
+                    trigger: trigger("RightControl")
+                },
+                Released {
+                    trigger: trigger("RightControl"),
+                    was_tap: true
+                },
+                Tap {
+                    trigger: trigger("RightControl")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn tap_ends_just_below_tap_max() {
+        let mut keys = Harness::new(&[("RightControl", None)]);
+        let actions = keys.press(RIGHT_CONTROL, 199);
+        assert!(actions.contains(&Tap {
+            trigger: trigger("RightControl")
+        }));
+
+        keys.wait(1000);
+        let actions = keys.press(RIGHT_CONTROL, 200);
+        assert_eq!(
+            actions,
+            [
+                Pressed {
+                    trigger: trigger("RightControl")
+                },
+                Released {
+                    trigger: trigger("RightControl"),
+                    was_tap: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn hold_starts_at_hold_min_and_ends_on_release() {
+        let mut keys = Harness::new(&[("RightControl", Some(Gesture::Hold))]);
+        keys.down(RIGHT_CONTROL);
+        assert_eq!(
+            keys.machine.next_deadline(),
+            Some(keys.now + CONFIG.hold_min)
+        );
+        assert!(keys.wait(399).is_empty());
+        assert_eq!(
+            keys.wait(1),
+            [HoldStart {
+                trigger: trigger("RightControl")
+            }]
+        );
+
+        // Reported once, however long the key stays down
+        assert!(keys.wait(2000).is_empty());
+        assert_eq!(keys.machine.next_deadline(), None);
+
+        assert_eq!(
+            keys.up(RIGHT_CONTROL),
+            [
+                Released {
+                    trigger: trigger("RightControl"),
+                    was_tap: false
+                },
+                HoldEnd {
+                    trigger: trigger("RightControl")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn other_keys_cancel_the_hold() {
+        let mut keys = Harness::new(&[("RightControl", Some(Gesture::Hold))]);
+        keys.down(RIGHT_CONTROL);
+        keys.wait(100);
+        keys.press(A, 50);
+        assert!(keys.wait(1000).is_empty());
+        assert_eq!(
+            keys.up(RIGHT_CONTROL),
+            [Released {
+                trigger: trigger("RightControl"),
+                was_tap: false
+            }]
+        );
+    }
+
+    #[test]
+    fn two_quick_taps_are_a_double_tap() {
+        let mut keys = Harness::new(&[("RightControl", Some(Gesture::DoubleTap))]);
+        let mut actions = keys.press(RIGHT_CONTROL, 80);
+        actions.extend(keys.wait(249));
+        actions.extend(keys.press(RIGHT_CONTROL, 80));
+        actions.extend(keys.wait(1000));
+
+        assert_eq!(
+            actions,
+            [
+                Pressed {
+                    trigger: trigger("RightControl")
+                },
+                Released {
+                    trigger: trigger("RightControl"),
+                    was_tap: true
+                },
+                Pressed {
+                    trigger: trigger("RightControl")
+                },
+                Released {
+                    trigger: trigger("RightControl"),
+                    was_tap: true
+                },
+                DoubleTap {
+                    trigger: trigger("RightControl")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn tap_waits_out_the_double_tap_window() {
+        let mut keys = Harness::new(&[("RightControl", Some(Gesture::DoubleTap))]);
+        let actions = keys.press(RIGHT_CONTROL, 80);
+        assert!(!actions.iter().any(|action| matches!(action, Tap { .. })));
+
+        assert!(keys.wait(249).is_empty());
+        assert_eq!(
+            keys.wait(1),
+            [Tap {
+                trigger: trigger("RightControl")
+            }]
+        );
+
+        // A second press at the end of the window starts over
+        let actions = keys.press(RIGHT_CONTROL, 80);
+        assert!(!actions
+            .iter()
+            .any(|action| matches!(action, DoubleTap { .. })));
+    }
+
+    #[test]
+    fn slow_second_press_leaves_the_first_tap() {
+        let mut keys = Harness::new(&[("RightControl", Some(Gesture::DoubleTap))]);
+        keys.press(RIGHT_CONTROL, 80);
+        keys.wait(100);
+        let actions = keys.press(RIGHT_CONTROL, 300);
+        assert_eq!(
+            actions.last(),
+            Some(&Tap {
+                trigger: trigger("RightControl")
+            })
+        );
+        assert!(!actions
+            .iter()
+            .any(|action| matches!(action, DoubleTap { .. })));
+    }
+
+    #[test]
+    fn typing_after_a_tap_reports_it_at_once() {
+        let mut keys = Harness::new(&[("RightControl", Some(Gesture::DoubleTap))]);
+        keys.press(RIGHT_CONTROL, 80);
+        assert_eq!(
+            keys.down(A),
+            [Tap {
+                trigger: trigger("RightControl")
+            }]
+        );
+        assert_eq!(keys.machine.next_deadline(), None);
+    }
+
+    #[test]
+    fn combos_ignore_the_modifier_side() {
+        let mut keys = Harness::new(&[("Control+Shift+A", None)]);
+        keys.down(LEFT_CONTROL);
+        keys.down(RIGHT_SHIFT);
+        assert_eq!(
+            keys.down(A),
+            [Combo {
+                accelerator: "Control+Shift+A".to_string()
+            }]
+        );
+
+        // Not bound with one modifier less
+        keys.up(A);
+        keys.up(RIGHT_SHIFT);
+        assert!(keys.down(A).is_empty());
+    }
+
+    #[test]
+    fn combo_is_not_a_tap_of_its_modifier() {
+        let mut keys = Harness::new(&[("Control", None), ("Control+A", None)]);
+        let mut actions = keys.down(LEFT_CONTROL);
+        actions.extend(keys.press(A, 20));
+        actions.extend(keys.up(LEFT_CONTROL));
+        assert_eq!(
+            actions,
+            [
+                Pressed {
+                    trigger: trigger("Control")
+                },
+                Combo {
+                    accelerator: "Control+A".to_string()
+                },
+                Released {
+                    trigger: trigger("Control"),
+                    was_tap: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn auto_repeat_and_second_modifiers_are_ignored() {
+        let mut keys = Harness::new(&[("RightControl", None)]);
+        keys.down(RIGHT_CONTROL);
+        assert!(keys.down(RIGHT_CONTROL).is_empty());
+
+        // Held with another modifier: no trigger
+        keys.up(RIGHT_CONTROL);
+        keys.wait(1000);
+        keys.down(RIGHT_SHIFT);
+        assert!(keys.down(RIGHT_CONTROL).is_empty());
+    }
+
+    #[test]
+    fn resolve_routes_gestures_to_their_commands() {
+        let mut bindings = ShortcutBindings::default();
+        bindings
+            .insert(
+                "RightControl".to_string(),
+                Some(Gesture::Hold),
+                "push-to-talk".to_string(),
+            )
+            .unwrap();
+        bindings
+            .insert(
+                "RightControl".to_string(),
+                Some(Gesture::DoubleTap),
+                "toggle".to_string(),
+            )
+            .unwrap();
+
+        let hold = HoldStart {
+            trigger: trigger("RightControl"),
+        };
+        assert_eq!(
+            bindings.resolve(&hold),
+            [("fn-shortcut-pressed", "push-to-talk".to_string())]
+        );
+        let double_tap = DoubleTap {
+            trigger: trigger("RightControl"),
+        };
+        assert_eq!(
+            bindings.resolve(&double_tap),
+            [("fn-shortcut-triggered", "toggle".to_string())]
+        );
+        assert!(bindings
+            .resolve(&Tap {
+                trigger: trigger("RightControl")
+            })
+            .is_empty());
+
+        assert!(bindings
+            .insert("Control+A".to_string(), Some(Gesture::Tap), "x".to_string())
+            .is_err());
+    }
+
+    #[test]
+    fn accelerators_are_normalized() {
+        let cases = [
+            ("rightctrl", "RightControl"),
+            ("AltGr", "RightAlt"),
+            ("cmd", "Super"),
+            ("capslock", "CapsLock"),
+            ("shift+ctrl+a", "Control+Shift+A"),
+            ("RightControl + Fn + space", "Fn+Control+Space"),
+            ("LeftAlt+RightAlt+F5", "Alt+F5"),
+        ];
+        for (accelerator, normalized) in cases {
+            assert_eq!(normalize_accelerator(accelerator).unwrap(), normalized);
+        }
+
+        for invalid in ["", "A", "Ctrl+", "Ctrl+Hyper+A", "Ctrl+PageUp"] {
+            assert!(normalize_accelerator(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn gesture_timing_is_bounded() {
+        let timing = |tap_max_ms| GestureTiming {
+            tap_max_ms,
+            ..GestureTiming::default()
+        };
+        assert_eq!(
+            ShortcutConfig::try_from(timing(None)).unwrap(),
+            ShortcutConfig::default()
+        );
+        assert_eq!(
+            ShortcutConfig::try_from(timing(Some(1))).unwrap().tap_max,
+            Duration::from_millis(1)
+        );
+        assert!(ShortcutConfig::try_from(GestureTiming {
+            tap_max_ms: Some(5000),
+            hold_min_ms: Some(5000),
+            ..GestureTiming::default()
+        })
+        .is_ok());
+        assert!(ShortcutConfig::try_from(timing(Some(0))).is_err());
+        assert!(ShortcutConfig::try_from(timing(Some(5001))).is_err());
+    }
+
+    #[test]
+    fn taps_cannot_outlast_holds() {
+        let timing = GestureTiming {
+            tap_max_ms: Some(1000),
+            hold_min_ms: Some(300),
+            ..GestureTiming::default()
+        };
+        assert!(ShortcutConfig::try_from(timing).is_err());
+
+        // Set directly, a press that became a hold is still not a tap
+        let mut keys = Harness::new(&[
+            ("RightControl", Some(Gesture::Hold)),
+            ("RightControl", Some(Gesture::Tap)),
+        ]);
+        keys.machine.set_config(ShortcutConfig {
+            tap_max: Duration::from_millis(1000),
+            hold_min: Duration::from_millis(300),
+            ..CONFIG
+        });
+        assert_eq!(
+            keys.press(RIGHT_CONTROL, 500),
+            [
+                Pressed {
+                    trigger: trigger("RightControl")
+                },
+                HoldStart {
+                    trigger: trigger("RightControl")
+                },
+                Released {
+                    trigger: trigger("RightControl"),
+                    was_tap: false
+                },
+                HoldEnd {
+                    trigger: trigger("RightControl")
+                },
+            ]
+        );
+    }
+}
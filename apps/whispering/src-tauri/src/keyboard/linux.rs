@@ -1,25 +1,674 @@
-use tauri::AppHandle;
+use super::core::{
+    normalize_accelerator, Gesture, Key, KeyEvent, Modifier, ShortcutBindings, ShortcutConfig,
+    ShortcutMachine,
+};
+use crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender};
+use evdev::{Device, EventSummary, InputEvent, KeyCode};
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Instant, SystemTime};
+use tauri::{AppHandle, Emitter};
 
-pub struct LinuxFnShortcutManager;
+// ============================================================================
+// TYPES & STATE
+// ============================================================================
+
+/// Shared state accessed by the manager and the processing thread
+struct SharedState {
+    bindings: ShortcutBindings,
+    machine: ShortcutMachine,
+    recording_mode: bool, // When true, emit Fn presses for UI recording
+    last_event_at: Option<SystemTime>,
+}
+
+/// eventfd that wakes the readers of one generation so they can be joined
+struct Wakeup(OwnedFd);
+
+impl Wakeup {
+    fn new() -> io::Result<Self> {
+        let fd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self(unsafe { OwnedFd::from_raw_fd(fd) }))
+    }
+
+    /// Make the fd readable for good (it is never read)
+    fn wake(&self) {
+        let one = 1u64;
+        unsafe {
+            libc::write(
+                self.0.as_raw_fd(),
+                &one as *const u64 as *const libc::c_void,
+                std::mem::size_of::<u64>(),
+            );
+        }
+    }
+}
+
+// ============================================================================
+// MAIN MANAGER
+// ============================================================================
+
+/// Modifier-only shortcuts (taps and holds of e.g. Right Ctrl, Super, CapsLock) read
+/// straight from the keyboards in `/dev/input`.
+///
+/// Works on X11 and Wayland alike, but needs read access to the event devices (usually
+/// membership in the `input` group). Keys are observed, not grabbed, so the focused
+/// application still receives them (a CapsLock tap also toggles Caps Lock).
+pub struct LinuxFnShortcutManager {
+    app: AppHandle,
+    state: Arc<Mutex<SharedState>>,
+    wakeup: Option<Arc<Wakeup>>, // Of the running readers
+    input_nodes: Vec<PathBuf>,   // In /dev/input when the readers started
+    reader_threads: Vec<thread::JoinHandle<()>>,
+    processing_thread: Option<thread::JoinHandle<()>>,
+}
 
 impl LinuxFnShortcutManager {
-    pub fn new(_app: AppHandle) -> Result<Self, String> {
-        Err("Fn key shortcuts are not supported on Linux".to_string())
+    pub fn new(app: AppHandle) -> Result<Self, String> {
+        println!("[FnShortcut] Initializing Linux evdev shortcut manager");
+
+        let state = Arc::new(Mutex::new(SharedState {
+            bindings: ShortcutBindings::default(),
+            machine: ShortcutMachine::new(ShortcutConfig::default()),
+            recording_mode: false,
+            last_event_at: None,
+        }));
+
+        let mut manager = Self {
+            app,
+            state,
+            wakeup: None,
+            input_nodes: Vec::new(),
+            reader_threads: Vec::new(),
+            processing_thread: None,
+        };
+        manager.spawn_threads()?;
+
+        println!("[FnShortcut] Manager initialized successfully");
+        Ok(manager)
+    }
+
+    /// Re-open the keyboards (e.g. after one was unplugged), keeping the registrations
+    pub fn restart(&mut self) -> Result<(), String> {
+        println!("[FnShortcut] Restarting Linux evdev shortcut manager");
+        self.spawn_threads()
     }
 
-    pub fn register(&self, _shortcut: &str, _command_id: &str) -> Result<(), String> {
-        Err("Fn key shortcuts are not supported on Linux".to_string())
+    /// `Err(reason)` when a thread stopped or keyboards came or went, so the supervisor
+    /// restarts the manager with the current keyboards
+    pub fn check(&self) -> Result<(), String> {
+        if self
+            .processing_thread
+            .as_ref()
+            .is_none_or(|thread| thread.is_finished())
+        {
+            return Err("Shortcut processing thread stopped".to_string());
+        }
+        if self
+            .reader_threads
+            .iter()
+            .any(|thread| thread.is_finished())
+        {
+            return Err(
+                "A keyboard stopped reporting (unplugged, or access was revoked)".to_string(),
+            );
+        }
+        if input_nodes() != self.input_nodes {
+            return Err("Input devices were added or removed".to_string());
+        }
+        Ok(())
     }
 
-    pub fn unregister(&self, _shortcut: &str) -> Result<(), String> {
-        Err("Fn key shortcuts are not supported on Linux".to_string())
+    pub fn last_event(&self) -> Option<SystemTime> {
+        self.state.lock().ok().and_then(|state| state.last_event_at)
+    }
+
+    /// Start readers and a processing thread, retiring the previous ones
+    fn spawn_threads(&mut self) -> Result<(), String> {
+        // Taken first, so a device that appears while the keyboards are opened is picked
+        // up by the next restart
+        self.input_nodes = input_nodes();
+        let keyboards: Vec<(PathBuf, Device)> = evdev::enumerate()
+            .filter(|(_, device)| is_keyboard(device))
+            .collect();
+        if keyboards.is_empty() {
+            return Err(
+                "No readable keyboards in /dev/input (add your user to the 'input' group)"
+                    .to_string(),
+            );
+        }
+        let wakeup =
+            Arc::new(Wakeup::new().map_err(|e| format!("Failed to create eventfd: {}", e))?);
+
+        self.stop_threads();
+        {
+            let mut state = self.state.lock().map_err(|_| "Failed to lock state")?;
+            // Releases may have been missed while the old threads were down
+            state.machine.reset();
+        }
+        let (event_sender, event_receiver) = unbounded::<KeyEvent>();
+
+        // One reader per keyboard, all feeding the same processing thread
+        self.reader_threads = keyboards
+            .into_iter()
+            .map(|(path, device)| {
+                println!(
+                    "[FnShortcut] Reading keys from {:?} ({})",
+                    path,
+                    device.name().unwrap_or("unnamed")
+                );
+                let sender = event_sender.clone();
+                let wakeup = wakeup.clone();
+                thread::spawn(move || read_device(path, device, sender, wakeup))
+            })
+            .collect();
+        self.wakeup = Some(wakeup);
+
+        let app = self.app.clone();
+        let state = self.state.clone();
+        self.processing_thread = Some(thread::spawn(move || {
+            println!("[FnShortcut] Processing thread started");
+            process_events(app, state, event_receiver);
+        }));
+
+        Ok(())
+    }
+
+    /// Wake the readers and wait for every thread, which closes the devices
+    fn stop_threads(&mut self) {
+        if let Some(wakeup) = self.wakeup.take() {
+            wakeup.wake();
+        }
+        for thread in self.reader_threads.drain(..) {
+            let _ = thread.join();
+        }
+        // Ends once the last reader dropped its sender
+        if let Some(thread) = self.processing_thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    pub fn register(
+        &self,
+        shortcut: &str,
+        command_id: &str,
+        gesture: Option<Gesture>,
+    ) -> Result<(), String> {
+        let accelerator = normalize_accelerator(shortcut)?;
+        let mut state = self.state.lock().map_err(|_| "Failed to lock state")?;
+
+        println!(
+            "[FnShortcut] Registering: {} ({}, {:?}) -> {}",
+            shortcut, accelerator, gesture, command_id
+        );
+        state
+            .bindings
+            .insert(accelerator, gesture, command_id.to_string())
+    }
+
+    pub fn unregister(&self, shortcut: &str, gesture: Option<Gesture>) -> Result<(), String> {
+        let accelerator = normalize_accelerator(shortcut).unwrap_or_else(|_| shortcut.to_string());
+        let mut state = self.state.lock().map_err(|_| "Failed to lock state")?;
+
+        println!("[FnShortcut] Unregistering: {} ({:?})", shortcut, gesture);
+        state.bindings.remove(&accelerator, gesture);
+
+        Ok(())
+    }
+
+    pub fn set_gesture_timing(&self, config: ShortcutConfig) -> Result<(), String> {
+        let mut state = self.state.lock().map_err(|_| "Failed to lock state")?;
+        println!("[FnShortcut] Gesture timing: {:?}", config);
+        state.machine.set_config(config);
+        Ok(())
     }
 
     pub fn enable_recording_mode(&self) -> Result<(), String> {
-        Err("Fn key shortcuts are not supported on Linux".to_string())
+        let mut state = self.state.lock().map_err(|_| "Failed to lock state")?;
+        state.recording_mode = true;
+        println!("[FnShortcut] Recording mode ENABLED");
+        Ok(())
     }
 
     pub fn disable_recording_mode(&self) -> Result<(), String> {
-        Err("Fn key shortcuts are not supported on Linux".to_string())
+        let mut state = self.state.lock().map_err(|_| "Failed to lock state")?;
+        state.recording_mode = false;
+        println!("[FnShortcut] Recording mode DISABLED");
+        Ok(())
+    }
+}
+
+impl Drop for LinuxFnShortcutManager {
+    fn drop(&mut self) {
+        self.stop_threads();
+    }
+}
+
+// ============================================================================
+// DEVICE READERS
+// ============================================================================
+
+/// Anything with letter keys or modifiers counts (media remotes and power buttons do not)
+fn is_keyboard(device: &Device) -> bool {
+    device.supported_keys().is_some_and(|keys| {
+        keys.contains(KeyCode::KEY_A)
+            || keys.contains(KeyCode::KEY_LEFTCTRL)
+            || keys.contains(KeyCode::KEY_FN)
+    })
+}
+
+/// Event device nodes in `/dev/input`, sorted
+fn input_nodes() -> Vec<PathBuf> {
+    let mut nodes: Vec<PathBuf> = std::fs::read_dir("/dev/input")
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| name.starts_with("event"))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    nodes.sort();
+    nodes
+}
+
+/// Forward the keys of one device until it goes away or `wakeup` fires
+fn read_device(path: PathBuf, mut device: Device, sender: Sender<KeyEvent>, wakeup: Arc<Wakeup>) {
+    if let Err(e) = device.set_nonblocking(true) {
+        eprintln!(
+            "[FnShortcut] Cannot read {:?} without blocking: {}",
+            path, e
+        );
+        return;
+    }
+
+    let mut fds = [device.as_raw_fd(), wakeup.0.as_raw_fd()].map(|fd| libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    });
+    loop {
+        if unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, -1) } < 0 {
+            let e = io::Error::last_os_error();
+            if e.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            eprintln!("[FnShortcut] Stopped reading {:?}: {}", path, e);
+            return;
+        }
+
+        // Retired by a restart or drop (the device closes with this thread)
+        if fds[1].revents != 0 {
+            return;
+        }
+
+        let events = match device.fetch_events() {
+            Ok(events) => events,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => continue,
+            Err(e) => {
+                // Typically the keyboard was unplugged
+                eprintln!("[FnShortcut] Stopped reading {:?}: {}", path, e);
+                return;
+            }
+        };
+
+        for event in events.filter_map(translate) {
+            if sender.send(event).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+// ============================================================================
+// PROCESSING THREAD
+// ============================================================================
+
+fn process_events(app: AppHandle, state: Arc<Mutex<SharedState>>, receiver: Receiver<KeyEvent>) {
+    loop {
+        // Wake up for holds and deferred taps even when no key arrives
+        let deadline = match state.lock() {
+            Ok(state) => state.machine.next_deadline(),
+            Err(_) => None,
+        };
+        let received = match deadline {
+            Some(deadline) => receiver.recv_deadline(deadline),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        let now = Instant::now();
+
+        let mut state = match state.lock() {
+            Ok(state) => state,
+            Err(_) => continue,
+        };
+        let SharedState {
+            bindings,
+            machine,
+            recording_mode,
+            last_event_at,
+            ..
+        } = &mut *state;
+        let actions = match received {
+            Ok(event) => {
+                *last_event_at = Some(SystemTime::now());
+                if *recording_mode && event == KeyEvent::Down(Key::Modifier(Modifier::Fn)) {
+                    let _ = app.emit("fn-key-pressed-for-recording", ());
+                }
+                machine.handle(event, now, bindings)
+            }
+            Err(RecvTimeoutError::Timeout) => machine.tick(now),
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        for (event, command_id) in actions.iter().flat_map(|action| bindings.resolve(action)) {
+            println!("[FnShortcut] Emitting {}: {}", event, command_id);
+            let _ = app.emit(event, command_id);
+        }
+    }
+
+    println!("[FnShortcut] Processing thread stopped");
+}
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+/// Key presses and releases for the state machine; auto-repeats (value 2), scan codes
+/// and sync reports are dropped
+fn translate(event: InputEvent) -> Option<KeyEvent> {
+    match event.destructure() {
+        EventSummary::Key(_, key, 1) => Some(KeyEvent::Down(core_key(key))),
+        EventSummary::Key(_, key, 0) => Some(KeyEvent::Up(core_key(key))),
+        _ => None,
+    }
+}
+
+/// Translate an evdev key for the state machine
+fn core_key(key: KeyCode) -> Key {
+    let modifier = match key {
+        KeyCode::KEY_FN => Modifier::Fn,
+        KeyCode::KEY_LEFTCTRL => Modifier::LeftControl,
+        KeyCode::KEY_RIGHTCTRL => Modifier::RightControl,
+        KeyCode::KEY_LEFTALT => Modifier::LeftAlt,
+        KeyCode::KEY_RIGHTALT => Modifier::RightAlt,
+        KeyCode::KEY_LEFTSHIFT => Modifier::LeftShift,
+        KeyCode::KEY_RIGHTSHIFT => Modifier::RightShift,
+        KeyCode::KEY_LEFTMETA => Modifier::LeftSuper,
+        KeyCode::KEY_RIGHTMETA => Modifier::RightSuper,
+        KeyCode::KEY_CAPSLOCK => Modifier::CapsLock,
+        _ => return Key::Other(key_name(key)),
+    };
+    Key::Modifier(modifier)
+}
+
+/// Names of the keys that can end a combo (see `core::COMBO_KEY_NAMES`)
+const KEY_NAMES: [(KeyCode, &str); 64] = [
+    (KeyCode::KEY_A, "A"),
+    (KeyCode::KEY_B, "B"),
+    (KeyCode::KEY_C, "C"),
+    (KeyCode::KEY_D, "D"),
+    (KeyCode::KEY_E, "E"),
+    (KeyCode::KEY_F, "F"),
+    (KeyCode::KEY_G, "G"),
+    (KeyCode::KEY_H, "H"),
+    (KeyCode::KEY_I, "I"),
+    (KeyCode::KEY_J, "J"),
+    (KeyCode::KEY_K, "K"),
+    (KeyCode::KEY_L, "L"),
+    (KeyCode::KEY_M, "M"),
+    (KeyCode::KEY_N, "N"),
+    (KeyCode::KEY_O, "O"),
+    (KeyCode::KEY_P, "P"),
+    (KeyCode::KEY_Q, "Q"),
+    (KeyCode::KEY_R, "R"),
+    (KeyCode::KEY_S, "S"),
+    (KeyCode::KEY_T, "T"),
+    (KeyCode::KEY_U, "U"),
+    (KeyCode::KEY_V, "V"),
+    (KeyCode::KEY_W, "W"),
+    (KeyCode::KEY_X, "X"),
+    (KeyCode::KEY_Y, "Y"),
+    (KeyCode::KEY_Z, "Z"),
+    (KeyCode::KEY_0, "0"),
+    (KeyCode::KEY_1, "1"),
+    (KeyCode::KEY_2, "2"),
+    (KeyCode::KEY_3, "3"),
+    (KeyCode::KEY_4, "4"),
+    (KeyCode::KEY_5, "5"),
+    (KeyCode::KEY_6, "6"),
+    (KeyCode::KEY_7, "7"),
+    (KeyCode::KEY_8, "8"),
+    (KeyCode::KEY_9, "9"),
+    (KeyCode::KEY_MINUS, "-"),
+    (KeyCode::KEY_EQUAL, "="),
+    (KeyCode::KEY_LEFTBRACE, "["),
+    (KeyCode::KEY_RIGHTBRACE, "]"),
+    (KeyCode::KEY_SEMICOLON, ";"),
+    (KeyCode::KEY_APOSTROPHE, "'"),
+    (KeyCode::KEY_GRAVE, "`"),
+    (KeyCode::KEY_BACKSLASH, "\\"),
+    (KeyCode::KEY_COMMA, ","),
+    (KeyCode::KEY_DOT, "."),
+    (KeyCode::KEY_SLASH, "/"),
+    (KeyCode::KEY_ENTER, "Return"),
+    (KeyCode::KEY_TAB, "Tab"),
+    (KeyCode::KEY_SPACE, "Space"),
+    (KeyCode::KEY_BACKSPACE, "Delete"),
+    (KeyCode::KEY_ESC, "Escape"),
+    (KeyCode::KEY_F1, "F1"),
+    (KeyCode::KEY_F2, "F2"),
+    (KeyCode::KEY_F3, "F3"),
+    (KeyCode::KEY_F4, "F4"),
+    (KeyCode::KEY_F5, "F5"),
+    (KeyCode::KEY_F6, "F6"),
+    (KeyCode::KEY_F7, "F7"),
+    (KeyCode::KEY_F8, "F8"),
+    (KeyCode::KEY_F9, "F9"),
+    (KeyCode::KEY_F10, "F10"),
+    (KeyCode::KEY_F11, "F11"),
+    (KeyCode::KEY_F12, "F12"),
+];
+
+fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|(code, _)| *code == key)
+        .map(|(_, name)| *name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keyboard::core::ShortcutAction;
+    use evdev::EventType;
+    use std::time::Duration;
+
+    const KEY: u16 = EventType::KEY.0;
+    const MSC: u16 = EventType::MISC.0;
+    const SYN: u16 = EventType::SYNCHRONIZATION.0;
+    const MSC_SCAN: u16 = 4;
+
+    /// Right Ctrl tapped on a USB keyboard, as `evtest` shows it: (ms, type, code, value)
+    const RIGHT_CTRL_TAP: [(u64, u16, u16, i32); 6] = [
+        (0, MSC, MSC_SCAN, 0x700e4),
+        (0, KEY, 97, 1),
+        (0, SYN, 0, 0),
+        (120, MSC, MSC_SCAN, 0x700e4),
+        (120, KEY, 97, 0),
+        (120, SYN, 0, 0),
+    ];
+
+    /// Right Ctrl held long enough for the keyboard to auto-repeat
+    const RIGHT_CTRL_HOLD: [(u64, u16, u16, i32); 10] = [
+        (0, MSC, MSC_SCAN, 0x700e4),
+        (0, KEY, 97, 1),
+        (0, SYN, 0, 0),
+        (250, KEY, 97, 2),
+        (250, SYN, 0, 1),
+        (283, KEY, 97, 2),
+        (283, SYN, 0, 1),
+        (700, MSC, MSC_SCAN, 0x700e4),
+        (700, KEY, 97, 0),
+        (700, SYN, 0, 0),
+    ];
+
+    /// Left Ctrl + Left Shift + A
+    const CTRL_SHIFT_A: [(u64, u16, u16, i32); 12] = [
+        (0, KEY, 29, 1),
+        (0, SYN, 0, 0),
+        (40, KEY, 42, 1),
+        (40, SYN, 0, 0),
+        (90, KEY, 30, 1),
+        (90, SYN, 0, 0),
+        (150, KEY, 30, 0),
+        (150, SYN, 0, 0),
+        (180, KEY, 42, 0),
+        (180, SYN, 0, 0),
+        (200, KEY, 29, 0),
+        (200, SYN, 0, 0),
+    ];
+
+    fn translated(recording: &[(u64, u16, u16, i32)]) -> Vec<KeyEvent> {
+        recording
+            .iter()
+            .filter_map(|&(_, kind, code, value)| translate(InputEvent::new(kind, code, value)))
+            .collect()
+    }
+
+    /// Feed a recording to a machine the way the processing thread does
+    fn replay(
+        recording: &[(u64, u16, u16, i32)],
+        bindings: &ShortcutBindings,
+        tick_at: &[u64],
+    ) -> Vec<ShortcutAction> {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut machine = ShortcutMachine::new(ShortcutConfig::default());
+        let mut ticks = tick_at.iter().peekable();
+        let mut actions = Vec::new();
+
+        for &(ms, kind, code, value) in recording {
+            while let Some(&&tick) = ticks.peek().filter(|&&&tick| tick <= ms) {
+                actions.extend(machine.tick(at(tick)));
+                ticks.next();
+            }
+            if let Some(event) = translate(InputEvent::new(kind, code, value)) {
+                actions.extend(machine.handle(event, at(ms), bindings));
+            }
+        }
+        actions
+    }
+
+    fn bindings(accelerator: &str, gesture: Option<Gesture>) -> ShortcutBindings {
+        let mut bindings = ShortcutBindings::default();
+        bindings
+            .insert(
+                normalize_accelerator(accelerator).unwrap(),
+                gesture,
+                "command".to_string(),
+            )
+            .unwrap();
+        bindings
+    }
+
+    #[test]
+    fn scan_codes_sync_reports_and_repeats_are_dropped() {
+        let right_ctrl = Key::Modifier(Modifier::RightControl);
+        assert_eq!(
+            translated(&RIGHT_CTRL_TAP),
+            [KeyEvent::Down(right_ctrl), KeyEvent::Up(right_ctrl)]
+        );
+        assert_eq!(
+            translated(&RIGHT_CTRL_HOLD),
+            [KeyEvent::Down(right_ctrl), KeyEvent::Up(right_ctrl)]
+        );
+    }
+
+    #[test]
+    fn keys_are_named_for_combos() {
+        assert_eq!(
+            translated(&CTRL_SHIFT_A),
+            [
+                KeyEvent::Down(Key::Modifier(Modifier::LeftControl)),
+                KeyEvent::Down(Key::Modifier(Modifier::LeftShift)),
+                KeyEvent::Down(Key::Other(Some("A"))),
+                KeyEvent::Up(Key::Other(Some("A"))),
+                KeyEvent::Up(Key::Modifier(Modifier::LeftShift)),
+                KeyEvent::Up(Key::Modifier(Modifier::LeftControl)),
+            ]
+        );
+
+        // Volume up has no accelerator name
+        assert_eq!(
+            translated(&[(0, KEY, 115, 1)]),
+            [KeyEvent::Down(Key::Other(None))]
+        );
+
+        // Every named key can be bound
+        for (_, name) in KEY_NAMES {
+            normalize_accelerator(&format!("Control+{}", name)).unwrap();
+        }
+    }
+
+    #[test]
+    fn recorded_tap_triggers_the_shortcut() {
+        let trigger = "RightControl".to_string();
+        assert_eq!(
+            replay(&RIGHT_CTRL_TAP, &bindings("RightControl", None), &[]),
+            [
+                ShortcutAction::Pressed {
+                    trigger: trigger.clone()
+                },
+                ShortcutAction::Released {
+                    trigger: trigger.clone(),
+                    was_tap: true
+                },
+                ShortcutAction::Tap { trigger },
+            ]
+        );
+    }
+
+    #[test]
+    fn recorded_hold_starts_and_ends_a_hold() {
+        let trigger = "RightControl".to_string();
+        let actions = replay(
+            &RIGHT_CTRL_HOLD,
+            &bindings("RightControl", Some(Gesture::Hold)),
+            &[300],
+        );
+        assert_eq!(
+            actions,
+            [
+                ShortcutAction::Pressed {
+                    trigger: trigger.clone()
+                },
+                ShortcutAction::HoldStart {
+                    trigger: trigger.clone()
+                },
+                ShortcutAction::Released {
+                    trigger: trigger.clone(),
+                    was_tap: false
+                },
+                ShortcutAction::HoldEnd { trigger },
+            ]
+        );
+    }
+
+    #[test]
+    fn recorded_combo_matches_the_accelerator() {
+        assert_eq!(
+            replay(&CTRL_SHIFT_A, &bindings("Ctrl+Shift+a", None), &[]),
+            [ShortcutAction::Combo {
+                accelerator: "Control+Shift+A".to_string()
+            }]
+        );
     }
 }
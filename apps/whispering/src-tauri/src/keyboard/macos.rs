@@ -1,55 +1,49 @@
+use super::core::{
+    normalize_accelerator, Gesture, Key, KeyEvent, Modifier, ShortcutAction, ShortcutBindings,
+    ShortcutConfig, ShortcutMachine,
+};
+use core_foundation::base::TCFType;
 use core_foundation::runloop::CFRunLoop;
+use core_foundation_sys::mach_port::CFMachPortInvalidate;
 use core_graphics::event::{
     CGEvent, CGEventFlags, CGEventTap, CGEventTapLocation, CGEventTapOptions,
     CGEventTapPlacement, CGEventType, EventField,
 };
-use crossbeam_channel::{unbounded, Receiver, Sender};
-use std::collections::HashMap;
+use crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Instant;
+use std::time::{Instant, SystemTime};
 use tauri::{AppHandle, Emitter};
 
 // ============================================================================
 // TYPES & STATE
 // ============================================================================
 
+/// Modifier flags tracked as keys (Caps Lock is a lock state, not a held key)
+const MODIFIER_FLAGS: [(CGEventFlags, Modifier); 5] = [
+    (CGEventFlags::CGEventFlagSecondaryFn, Modifier::Fn),
+    (CGEventFlags::CGEventFlagControl, Modifier::Control),
+    (CGEventFlags::CGEventFlagAlternate, Modifier::Alt),
+    (CGEventFlags::CGEventFlagShift, Modifier::Shift),
+    (CGEventFlags::CGEventFlagCommand, Modifier::Super),
+];
+
 /// Events sent from callback to processing thread
 #[derive(Debug, Clone)]
 enum ShortcutEvent {
-    FnAloneTriggered,
-    FnPressed, // Fn key pressed down
-    FnReleased, // Fn key released up
-    FnComboTriggered { accelerator: String },
-    TapDisabled,
+    Actions(Vec<ShortcutAction>), // Produced by the state machine in the callback
     FnKeyPressedForRecording, // Sent when Fn is pressed during recording mode
 }
 
-/// State tracking for Fn alone detection (shared between callback and manager)
-#[derive(Debug)]
-struct FnState {
-    is_pressed: bool,
-    was_pressed_last_frame: bool,
-    press_time: Option<Instant>,
-    other_keys_pressed: bool,
-}
-
-impl Default for FnState {
-    fn default() -> Self {
-        Self {
-            is_pressed: false,
-            was_pressed_last_frame: false,
-            press_time: None,
-            other_keys_pressed: false,
-        }
-    }
-}
-
 /// Shared state accessed by callback (must be thread-safe)
 struct SharedState {
-    registered_shortcuts: HashMap<String, String>, // accelerator -> command_id
-    fn_state: FnState,
-    recording_mode: bool, // When true, emit all Fn keypresses for UI recording
+    bindings: ShortcutBindings,
+    machine: ShortcutMachine,
+    modifier_flags: CGEventFlags, // Flags of the last FlagsChanged, to find what changed
+    recording_mode: bool,         // When true, emit all Fn keypresses for UI recording
+    failure: Option<String>,      // Why the event tap stopped working (for the supervisor)
+    run_loop: Option<CFRunLoop>,  // Of the monitoring thread, stopped on restart
+    last_event_at: Option<SystemTime>,
 }
 
 // ============================================================================
@@ -57,90 +51,177 @@ struct SharedState {
 // ============================================================================
 
 pub struct MacOSFnShortcutManager {
+    app: AppHandle,
     state: Arc<Mutex<SharedState>>,
-    _monitoring_thread: thread::JoinHandle<()>,
-    _processing_thread: thread::JoinHandle<()>,
+    monitoring_thread: Option<thread::JoinHandle<()>>,
+    processing_thread: Option<thread::JoinHandle<()>>,
 }
 
 impl MacOSFnShortcutManager {
     pub fn new(app: AppHandle) -> Result<Self, String> {
         println!("[FnShortcut] Initializing macOS Fn shortcut manager");
 
-        let (event_sender, event_receiver) = unbounded::<ShortcutEvent>();
-        println!("[FnShortcut] Created event channel");
-
         let state = Arc::new(Mutex::new(SharedState {
-            registered_shortcuts: HashMap::new(),
-            fn_state: FnState::default(),
+            bindings: ShortcutBindings::default(),
+            machine: ShortcutMachine::new(ShortcutConfig::default()),
+            modifier_flags: CGEventFlags::CGEventFlagNull,
             recording_mode: false,
+            failure: None,
+            run_loop: None,
+            last_event_at: None,
         }));
         println!("[FnShortcut] Created shared state");
 
-        // Spawn processing thread (handles AppHandle.emit_all)
-        let app_clone = app.clone();
-        let state_clone = state.clone();
-        let processing_thread = thread::spawn(move || {
+        let mut manager = Self {
+            app,
+            state,
+            monitoring_thread: None,
+            processing_thread: None,
+        };
+        manager.spawn_threads()?;
+
+        println!("[FnShortcut] Manager initialized successfully");
+
+        Ok(manager)
+    }
+
+    /// Replace the event tap (e.g. after macOS disabled it), keeping the registrations
+    pub fn restart(&mut self) -> Result<(), String> {
+        println!("[FnShortcut] Restarting event tap");
+
+        let old_loop = self
+            .state
+            .lock()
+            .map_err(|_| "Failed to lock state")?
+            .run_loop
+            .take();
+        if let Some(run_loop) = old_loop {
+            run_loop.stop();
+        }
+
+        self.spawn_threads()
+    }
+
+    /// `Err(reason)` when the event tap is disabled or its threads are gone
+    pub fn check(&self) -> Result<(), String> {
+        if let Some(failure) = self
+            .state
+            .lock()
+            .map_err(|_| "Failed to lock state")?
+            .failure
+            .clone()
+        {
+            return Err(failure);
+        }
+        if self
+            .monitoring_thread
+            .as_ref()
+            .is_none_or(|thread| thread.is_finished())
+        {
+            return Err("Event tap thread stopped".to_string());
+        }
+        if self
+            .processing_thread
+            .as_ref()
+            .is_none_or(|thread| thread.is_finished())
+        {
+            return Err("Shortcut processing thread stopped".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn last_event(&self) -> Option<SystemTime> {
+        self.state.lock().ok().and_then(|state| state.last_event_at)
+    }
+
+    fn spawn_threads(&mut self) -> Result<(), String> {
+        {
+            let mut state = self.state.lock().map_err(|_| "Failed to lock state")?;
+            state.failure = None;
+            // Releases may have been missed while the tap was down
+            state.machine.reset();
+            state.modifier_flags = CGEventFlags::CGEventFlagNull;
+        }
+
+        let (event_sender, event_receiver) = unbounded::<ShortcutEvent>();
+        println!("[FnShortcut] Created event channel");
+
+        // Spawn processing thread (handles AppHandle.emit_all), exits with the old tap's sender
+        let app_clone = self.app.clone();
+        let state_clone = self.state.clone();
+        self.processing_thread = Some(thread::spawn(move || {
             println!("[FnShortcut] Processing thread started");
             process_events(app_clone, state_clone, event_receiver);
-        });
+        }));
 
         // Spawn monitoring thread (runs CFRunLoop with CGEventTap)
-        let state_clone = state.clone();
-        let monitoring_thread = thread::spawn(move || {
+        let state_clone = self.state.clone();
+        self.monitoring_thread = Some(thread::spawn(move || {
             println!("[FnShortcut] Monitoring thread started");
-            if let Err(e) = start_event_tap(state_clone, event_sender) {
+            if let Err(e) = start_event_tap(state_clone.clone(), event_sender) {
                 eprintln!("[FnShortcut] ERROR: Failed to start event tap: {}", e);
+                if let Ok(mut state) = state_clone.lock() {
+                    state.failure = Some(e);
+                }
             }
-        });
-
-        println!("[FnShortcut] Manager initialized successfully");
+        }));
 
-        Ok(Self {
-            state,
-            _monitoring_thread: monitoring_thread,
-            _processing_thread: processing_thread,
-        })
+        Ok(())
     }
 
-    pub fn register(&self, shortcut: &str, command_id: &str) -> Result<(), String> {
-        if !shortcut.contains("Fn") {
+    pub fn register(
+        &self,
+        shortcut: &str,
+        command_id: &str,
+        gesture: Option<Gesture>,
+    ) -> Result<(), String> {
+        let accelerator = normalize_accelerator(shortcut)?;
+        if accelerator != "Fn" && !accelerator.starts_with("Fn+") {
             return Err("Shortcut must contain Fn modifier".to_string());
         }
 
         let mut state = self.state.lock().map_err(|_| "Failed to lock state")?;
 
         println!(
-            "[FnShortcut] Registering: {} -> {}",
-            shortcut, command_id
+            "[FnShortcut] Registering: {} ({}, {:?}) -> {}",
+            shortcut, accelerator, gesture, command_id
         );
 
         state
-            .registered_shortcuts
-            .insert(shortcut.to_string(), command_id.to_string());
+            .bindings
+            .insert(accelerator, gesture, command_id.to_string())?;
 
         println!(
             "[FnShortcut] Total registered shortcuts: {}",
-            state.registered_shortcuts.len()
+            state.bindings.len()
         );
 
         Ok(())
     }
 
-    pub fn unregister(&self, shortcut: &str) -> Result<(), String> {
+    pub fn unregister(&self, shortcut: &str, gesture: Option<Gesture>) -> Result<(), String> {
+        let accelerator = normalize_accelerator(shortcut).unwrap_or_else(|_| shortcut.to_string());
         let mut state = self.state.lock().map_err(|_| "Failed to lock state")?;
 
-        println!("[FnShortcut] Unregistering: {}", shortcut);
+        println!("[FnShortcut] Unregistering: {} ({:?})", shortcut, gesture);
 
-        state.registered_shortcuts.remove(shortcut);
+        state.bindings.remove(&accelerator, gesture);
 
         println!(
             "[FnShortcut] Total registered shortcuts: {}",
-            state.registered_shortcuts.len()
+            state.bindings.len()
         );
 
         Ok(())
     }
 
+    pub fn set_gesture_timing(&self, config: ShortcutConfig) -> Result<(), String> {
+        let mut state = self.state.lock().map_err(|_| "Failed to lock state")?;
+        println!("[FnShortcut] Gesture timing: {:?}", config);
+        state.machine.set_config(config);
+        Ok(())
+    }
+
     pub fn enable_recording_mode(&self) -> Result<(), String> {
         let mut state = self.state.lock().map_err(|_| "Failed to lock state")?;
         state.recording_mode = true;
@@ -156,6 +237,20 @@ impl MacOSFnShortcutManager {
     }
 }
 
+impl Drop for MacOSFnShortcutManager {
+    fn drop(&mut self) {
+        // Ends the monitoring thread, and with it the processing thread
+        let run_loop = self
+            .state
+            .lock()
+            .ok()
+            .and_then(|mut state| state.run_loop.take());
+        if let Some(run_loop) = run_loop {
+            run_loop.stop();
+        }
+    }
+}
+
 // ============================================================================
 // PROCESSING THREAD (Heavy lifting, off callback thread)
 // ============================================================================
@@ -165,78 +260,32 @@ fn process_events(
     state: Arc<Mutex<SharedState>>,
     receiver: Receiver<ShortcutEvent>,
 ) {
-    while let Ok(event) = receiver.recv() {
-        println!("[FnShortcut] Processing event: {:?}", event);
-
-        match event {
-            ShortcutEvent::FnAloneTriggered => {
-                // Get the command_id for "Fn" shortcut
-                let command_id = {
-                    let state = state.lock().unwrap();
-                    state.registered_shortcuts.get("Fn").cloned()
-                };
-
-                if let Some(cmd_id) = command_id {
-                    println!("[FnShortcut] Emitting Fn alone trigger: {}", cmd_id);
-                    let _ = app.emit("fn-shortcut-triggered", &cmd_id);
-                } else {
-                    println!("[FnShortcut] WARN: Fn alone triggered but no 'Fn' shortcut registered");
-                }
-            }
-            ShortcutEvent::FnPressed => {
-                // Emit press event for push-to-talk support
-                let command_id = {
-                    let state = state.lock().unwrap();
-                    state.registered_shortcuts.get("Fn").cloned()
-                };
-
-                if let Some(cmd_id) = command_id {
-                    println!("[FnShortcut] Emitting Fn PRESSED: {}", cmd_id);
-                    let _ = app.emit("fn-shortcut-pressed", &cmd_id);
-                } else {
-                    println!("[FnShortcut] WARN: Fn pressed but no 'Fn' shortcut registered");
-                }
-            }
-            ShortcutEvent::FnReleased => {
-                // Emit release event for push-to-talk support
-                let command_id = {
-                    let state = state.lock().unwrap();
-                    state.registered_shortcuts.get("Fn").cloned()
-                };
-
-                if let Some(cmd_id) = command_id {
-                    println!("[FnShortcut] Emitting Fn RELEASED: {}", cmd_id);
-                    let _ = app.emit("fn-shortcut-released", &cmd_id);
-                } else {
-                    println!("[FnShortcut] WARN: Fn released but no 'Fn' shortcut registered");
-                }
-            }
-            ShortcutEvent::FnComboTriggered { accelerator } => {
-                let command_id = {
-                    let state = state.lock().unwrap();
-                    state.registered_shortcuts.get(&accelerator).cloned()
-                };
-
-                if let Some(cmd_id) = command_id {
-                    println!(
-                        "[FnShortcut] Emitting Fn combo trigger: {} -> {}",
-                        accelerator, cmd_id
-                    );
-                    let _ = app.emit("fn-shortcut-triggered", &cmd_id);
-                } else {
-                    println!(
-                        "[FnShortcut] WARN: Combo triggered but no '{}' shortcut registered",
-                        accelerator
-                    );
-                }
-            }
-            ShortcutEvent::TapDisabled => {
-                eprintln!("[FnShortcut] ERROR: Event tap disabled, need to restart");
-                // TODO: Implement restart logic
-            }
-            ShortcutEvent::FnKeyPressedForRecording => {
+    loop {
+        // Wake up for holds and deferred taps even when no key arrives
+        let deadline = state.lock().unwrap().machine.next_deadline();
+        let received = match deadline {
+            Some(deadline) => receiver.recv_deadline(deadline),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+
+        let actions = match received {
+            Ok(ShortcutEvent::Actions(actions)) => actions,
+            Ok(ShortcutEvent::FnKeyPressedForRecording) => {
                 println!("[FnShortcut] Emitting Fn key press for UI recording");
                 let _ = app.emit("fn-key-pressed-for-recording", ());
+                continue;
+            }
+            Err(RecvTimeoutError::Timeout) => state.lock().unwrap().machine.tick(Instant::now()),
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        for action in actions {
+            println!("[FnShortcut] Processing action: {:?}", action);
+
+            let events = state.lock().unwrap().bindings.resolve(&action);
+            for (event, cmd_id) in events {
+                println!("[FnShortcut] Emitting {}: {}", event, cmd_id);
+                let _ = app.emit(event, &cmd_id);
             }
         }
     }
@@ -283,7 +332,7 @@ fn start_event_tap(
     println!("[FnShortcut] Event tap enabled");
 
     // Create a CFRunLoopSource from the event tap and add it to the current run loop
-    use core_foundation::runloop::{kCFRunLoopCommonModes, CFRunLoop};
+    use core_foundation::runloop::kCFRunLoopCommonModes;
 
     let loop_source = tap
         .mach_port
@@ -294,13 +343,16 @@ fn start_event_tap(
 
     let current_loop = CFRunLoop::get_current();
     current_loop.add_source(&loop_source, unsafe { kCFRunLoopCommonModes });
+    state.lock().unwrap().run_loop = Some(current_loop.clone());
 
     println!("[FnShortcut] Added source to run loop, entering run loop...");
 
-    // This blocks the thread indefinitely (required for event tap)
+    // This blocks the thread until `restart` stops the loop (required for event tap)
     CFRunLoop::run_current();
 
-    println!("[FnShortcut] RunLoop exited (unexpected)");
+    // Remove the old tap so it does not linger next to its replacement
+    unsafe { CFMachPortInvalidate(tap.mach_port.as_concrete_TypeRef()) };
+    println!("[FnShortcut] RunLoop exited");
 
     Ok(())
 }
@@ -316,9 +368,11 @@ fn handle_event_tap(
     sender: &Sender<ShortcutEvent>,
 ) -> Option<CGEvent> {
     match event_type {
-        CGEventType::TapDisabledByTimeout => {
-            eprintln!("[FnShortcut] CALLBACK: Tap disabled by timeout!");
-            let _ = sender.send(ShortcutEvent::TapDisabled);
+        CGEventType::TapDisabledByTimeout | CGEventType::TapDisabledByUserInput => {
+            eprintln!("[FnShortcut] CALLBACK: Tap disabled ({:?})!", event_type);
+            // The supervisor notices and replaces the tap
+            state.lock().unwrap().failure =
+                Some(format!("Event tap disabled ({:?})", event_type));
             Some(event.clone())
         }
 
@@ -336,104 +390,62 @@ fn handle_flags_changed(
     sender: &Sender<ShortcutEvent>,
 ) -> Option<CGEvent> {
     let flags = event.get_flags();
-    let fn_now = flags.contains(CGEventFlags::CGEventFlagSecondaryFn);
+    let now = Instant::now();
 
     // Lock state for minimal time
     let mut state_guard = state.lock().unwrap();
+    state_guard.last_event_at = Some(SystemTime::now());
+    let previous = std::mem::replace(&mut state_guard.modifier_flags, flags);
+
+    // Turn the flag difference into key events, releases first
+    let released = MODIFIER_FLAGS
+        .iter()
+        .filter(|(flag, _)| previous.contains(*flag) && !flags.contains(*flag))
+        .map(|(_, modifier)| KeyEvent::Up(Key::Modifier(*modifier)));
+    let pressed = MODIFIER_FLAGS
+        .iter()
+        .filter(|(flag, _)| !previous.contains(*flag) && flags.contains(*flag))
+        .map(|(_, modifier)| KeyEvent::Down(Key::Modifier(*modifier)));
+    let key_events: Vec<KeyEvent> = released.chain(pressed).collect();
+
+    let fn_down = key_events.contains(&KeyEvent::Down(Key::Modifier(Modifier::Fn)));
+    let is_recording = state_guard.recording_mode;
+
+    let SharedState {
+        machine,
+        bindings,
+        ..
+    } = &mut *state_guard;
+    let actions: Vec<ShortcutAction> = key_events
+        .into_iter()
+        .flat_map(|key_event| {
+            machine.handle(key_event, now, bindings)
+        })
+        .collect();
+    drop(state_guard);
 
-    // Check other modifiers FIRST
-    let other_mods = flags.contains(CGEventFlags::CGEventFlagCommand)
-        || flags.contains(CGEventFlags::CGEventFlagControl)
-        || flags.contains(CGEventFlags::CGEventFlagAlternate)
-        || flags.contains(CGEventFlags::CGEventFlagShift);
-
-    if other_mods {
-        // Other modifiers held, invalidate Fn alone
-        if state_guard.fn_state.is_pressed {
-            println!("[FnShortcut] CALLBACK: Other modifiers detected, invalidating Fn alone");
-        }
-        state_guard.fn_state.is_pressed = false;
-        state_guard.fn_state.was_pressed_last_frame = fn_now;
-        drop(state_guard);
-        return Some(event.clone());
+    if fn_down && is_recording {
+        println!("[FnShortcut] CALLBACK: Recording mode - sending Fn press to UI");
+        let _ = sender.send(ShortcutEvent::FnKeyPressedForRecording);
     }
 
-    // Detect Fn DOWN (transition false -> true)
-    if fn_now && !state_guard.fn_state.was_pressed_last_frame {
-        println!("[FnShortcut] CALLBACK: Fn DOWN detected");
-        state_guard.fn_state.is_pressed = true;
-        state_guard.fn_state.press_time = Some(Instant::now());
-        state_guard.fn_state.other_keys_pressed = false;
-        state_guard.fn_state.was_pressed_last_frame = true;
-
-        // Check if Fn is registered (for push-to-talk support)
-        let is_registered = state_guard.registered_shortcuts.contains_key("Fn");
-        let is_recording = state_guard.recording_mode;
-        drop(state_guard);
-
-        if is_recording {
-            println!("[FnShortcut] CALLBACK: Recording mode - sending Fn press to UI");
-            let _ = sender.send(ShortcutEvent::FnKeyPressedForRecording);
-        }
-
-        // Emit press event for push-to-talk
-        if is_registered {
-            println!("[FnShortcut] CALLBACK: Sending FnPressed event");
-            let _ = sender.send(ShortcutEvent::FnPressed);
-        }
+    // A tap of a registered Fn must not reach the system
+    let swallow = actions.iter().any(|action| {
+        matches!(action, ShortcutAction::Released { trigger, was_tap: true } if trigger == "Fn")
+    });
 
-        // PASS THROUGH - system needs to see Fn for hardware keys
-        return Some(event.clone());
+    if !actions.is_empty() {
+        println!("[FnShortcut] CALLBACK: Sending actions {:?}", actions);
+        let _ = sender.send(ShortcutEvent::Actions(actions));
     }
 
-    // Detect Fn UP (transition true -> false)
-    if !fn_now && state_guard.fn_state.was_pressed_last_frame {
-        println!("[FnShortcut] CALLBACK: Fn UP detected");
-        state_guard.fn_state.is_pressed = false;
-        state_guard.fn_state.was_pressed_last_frame = false;
-
-        // Check if this should trigger "Fn alone" (quick tap)
-        let should_trigger_tap = if let Some(start) = state_guard.fn_state.press_time {
-            let duration_ms = start.elapsed().as_millis();
-            let is_quick = duration_ms < 300;
-            let no_interference = !state_guard.fn_state.other_keys_pressed;
-            let is_registered = state_guard.registered_shortcuts.contains_key("Fn");
-
-            println!(
-                "[FnShortcut] CALLBACK: Fn tap check - duration: {}ms, quick: {}, no_interference: {}, registered: {}",
-                duration_ms, is_quick, no_interference, is_registered
-            );
-
-            is_quick && no_interference && is_registered
-        } else {
-            false
-        };
-
-        // Always emit release event for push-to-talk support
-        let is_registered = state_guard.registered_shortcuts.contains_key("Fn");
-        drop(state_guard); // Release lock before sending
-
-        if is_registered {
-            println!("[FnShortcut] CALLBACK: Sending FnReleased event");
-            let _ = sender.send(ShortcutEvent::FnReleased);
-        }
-
-        if should_trigger_tap {
-            println!("[FnShortcut] CALLBACK: Fn tap triggered! Swallowing event.");
-            // Send tap event to processing thread
-            let _ = sender.send(ShortcutEvent::FnAloneTriggered);
-
-            // CRITICAL: SWALLOW the UP event to prevent emoji picker
-            return None;
-        } else {
-            println!("[FnShortcut] CALLBACK: Fn released, passing through");
-        }
-    } else {
-        state_guard.fn_state.was_pressed_last_frame = fn_now;
-        drop(state_guard);
+    if swallow {
+        println!("[FnShortcut] CALLBACK: Fn tap triggered! Swallowing event.");
+        // CRITICAL: SWALLOW the UP event to prevent emoji picker
+        return None;
     }
 
-    // Pass through
+    // Pass through - system needs to see Fn for hardware keys
     Some(event.clone())
 }
 
@@ -442,55 +454,33 @@ fn handle_key_down(
     state: &Arc<Mutex<SharedState>>,
     sender: &Sender<ShortcutEvent>,
 ) -> Option<CGEvent> {
-    let flags = event.get_flags();
-    let has_fn = flags.contains(CGEventFlags::CGEventFlagSecondaryFn);
-
-    // Mark that other keys were pressed
-    {
-        let mut state_guard = state.lock().unwrap();
-        if state_guard.fn_state.is_pressed {
-            println!("[FnShortcut] CALLBACK: Other key pressed while Fn held, marking interference");
-            state_guard.fn_state.other_keys_pressed = true;
-        }
-    }
-
-    // Only process if Fn is held
-    if !has_fn {
-        return Some(event.clone());
-    }
-
-    // Get keycode and build accelerator
     let keycode = event.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE);
-    let accelerator = build_accelerator_string(flags, keycode);
+    let key_event = KeyEvent::Down(Key::Other(keycode_to_name(keycode)));
 
-    println!(
-        "[FnShortcut] CALLBACK: Key down with Fn - accelerator: {}",
-        accelerator
-    );
-
-    // Check if registered
-    let is_registered = {
-        let state_guard = state.lock().unwrap();
-        state_guard.registered_shortcuts.contains_key(&accelerator)
+    // Marks interference with a held trigger, and completes registered combos
+    let actions = {
+        let mut state_guard = state.lock().unwrap();
+        state_guard.last_event_at = Some(SystemTime::now());
+        let SharedState {
+            machine,
+            bindings,
+            ..
+        } = &mut *state_guard;
+        machine.handle(key_event, Instant::now(), bindings)
     };
 
-    if is_registered {
-        println!(
-            "[FnShortcut] CALLBACK: Registered combo triggered: {}",
-            accelerator
-        );
-        // Send to processing thread
-        let _ = sender.send(ShortcutEvent::FnComboTriggered {
-            accelerator: accelerator.clone(),
-        });
+    let is_combo = actions
+        .iter()
+        .any(|action| matches!(action, ShortcutAction::Combo { .. }));
 
+    if !actions.is_empty() {
+        println!("[FnShortcut] CALLBACK: Sending actions {:?}", actions);
+        let _ = sender.send(ShortcutEvent::Actions(actions));
+    }
+
+    if is_combo {
         // Consume the event
         return None;
-    } else {
-        println!(
-            "[FnShortcut] CALLBACK: Combo not registered: {}, passing through",
-            accelerator
-        );
     }
 
     // Pass through
@@ -501,31 +491,9 @@ fn handle_key_down(
 // HELPERS
 // ============================================================================
 
-fn build_accelerator_string(flags: CGEventFlags, keycode: i64) -> String {
-    let mut parts = Vec::new();
-
-    parts.push("Fn".to_string());
-
-    if flags.contains(CGEventFlags::CGEventFlagControl) {
-        parts.push("Control".to_string());
-    }
-    if flags.contains(CGEventFlags::CGEventFlagAlternate) {
-        parts.push("Option".to_string());
-    }
-    if flags.contains(CGEventFlags::CGEventFlagShift) {
-        parts.push("Shift".to_string());
-    }
-    if flags.contains(CGEventFlags::CGEventFlagCommand) {
-        parts.push("Command".to_string());
-    }
-
-    parts.push(keycode_to_string(keycode));
-
-    parts.join("+")
-}
-
-fn keycode_to_string(keycode: i64) -> String {
-    match keycode {
+/// Accelerator name of a key (see `core::COMBO_KEY_NAMES`)
+fn keycode_to_name(keycode: i64) -> Option<&'static str> {
+    let name = match keycode {
         0 => "A",
         1 => "S",
         2 => "D",
@@ -590,7 +558,7 @@ fn keycode_to_string(keycode: i64) -> String {
         118 => "F4",
         120 => "F2",
         122 => "F1",
-        _ => "Unknown",
-    }
-    .to_string()
+        _ => return None,
+    };
+    Some(name)
 }
@@ -1,5 +1,14 @@
 // Platform-specific keyboard handling modules
 
+// Shared shortcut state machine, fed by the platform modules
+pub mod core;
+
+// Restarts dead shortcut listeners and tracks their health
+pub mod supervisor;
+
+// Every registered shortcut, Fn and global, checked for conflicts
+pub mod registry;
+
 #[cfg(target_os = "macos")]
 pub mod macos;
 
@@ -0,0 +1,403 @@
+// Registry of every shortcut the app holds, Fn and global.
+//
+// The Fn managers and the global-shortcut plugin keep separate bindings, and both
+// quietly replace or shadow what the other holds. Registrations go through here
+// first, so a collision comes back as a conflict instead of a hotkey that does nothing.
+
+use super::core::{normalize_accelerator, normalize_global_accelerator, Gesture};
+use serde::Serialize;
+use std::fmt;
+
+/// Combos the operating system handles before any app sees them
+#[cfg(target_os = "macos")]
+const RESERVED: &[(&str, &str)] = &[
+    ("Super+Tab", "the app switcher"),
+    ("Super+Space", "Spotlight"),
+    ("Control+Super+Q", "Lock Screen"),
+    ("Alt+Super+Escape", "Force Quit"),
+    ("Shift+Super+3", "screenshots"),
+    ("Shift+Super+4", "screenshots"),
+    ("Shift+Super+5", "screenshots"),
+];
+
+#[cfg(target_os = "windows")]
+const RESERVED: &[(&str, &str)] = &[
+    ("Alt+Tab", "the app switcher"),
+    ("Alt+F4", "closing windows"),
+    ("Control+Alt+Delete", "the security screen"),
+    ("Control+Shift+Escape", "Task Manager"),
+    ("Super+L", "locking the screen"),
+    ("Super+D", "showing the desktop"),
+    ("Super+Tab", "Task View"),
+];
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const RESERVED: &[(&str, &str)] = &[
+    ("Alt+Tab", "the app switcher"),
+    ("Alt+F4", "closing windows"),
+    ("Control+Alt+Delete", "the session menu"),
+];
+
+/// Which listener handles a shortcut
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ShortcutSource {
+    Fn,     // Platform keyboard module (Fn and modifier-only shortcuts)
+    Global, // tauri_plugin_global_shortcut
+}
+
+/// One registered shortcut - returned by `list_shortcuts`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutEntry {
+    pub accelerator: String, // As registered
+    pub normalized: String,  // Compared between sources
+    pub source: ShortcutSource,
+    pub command_id: String,
+    pub gesture: Option<Gesture>,
+}
+
+impl ShortcutEntry {
+    pub fn new(
+        source: ShortcutSource,
+        accelerator: &str,
+        command_id: &str,
+        gesture: Option<Gesture>,
+    ) -> Result<Self, String> {
+        let normalized = match source {
+            ShortcutSource::Fn => normalize_accelerator(accelerator)?,
+            ShortcutSource::Global => normalize_global_accelerator(accelerator)?,
+        };
+        Ok(Self {
+            accelerator: accelerator.to_string(),
+            normalized,
+            source,
+            command_id: command_id.to_string(),
+            gesture,
+        })
+    }
+
+    /// Same binding slot: registering `other` would replace this entry
+    fn same_slot(&self, other: &ShortcutEntry) -> bool {
+        self.source == other.source
+            && self.normalized == other.normalized
+            && self.gesture == other.gesture
+    }
+}
+
+/// Why a shortcut was rejected
+#[derive(Debug, Clone, Serialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ShortcutConflict {
+    /// Bound to another command, or held by the other listener
+    AlreadyBound {
+        accelerator: String,
+        existing: ShortcutEntry,
+    },
+    /// Taken by the operating system
+    Reserved {
+        accelerator: String,
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for ShortcutConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShortcutConflict::AlreadyBound {
+                accelerator,
+                existing,
+            } => write!(
+                f,
+                "'{}' is already bound to '{}' ({:?} shortcut '{}')",
+                accelerator, existing.command_id, existing.source, existing.accelerator
+            ),
+            ShortcutConflict::Reserved {
+                accelerator,
+                reason,
+            } => write!(
+                f,
+                "'{}' is reserved by the system for {}",
+                accelerator, reason
+            ),
+        }
+    }
+}
+
+/// Error of the shortcut commands: a conflict object, or a message like other commands return
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum ShortcutError {
+    Conflict(ShortcutConflict),
+    Message(String),
+}
+
+impl From<ShortcutConflict> for ShortcutError {
+    fn from(conflict: ShortcutConflict) -> Self {
+        ShortcutError::Conflict(conflict)
+    }
+}
+
+impl From<String> for ShortcutError {
+    fn from(message: String) -> Self {
+        ShortcutError::Message(message)
+    }
+}
+
+impl From<&str> for ShortcutError {
+    fn from(message: &str) -> Self {
+        ShortcutError::Message(message.to_string())
+    }
+}
+
+/// Every shortcut held by the Fn managers and the global-shortcut plugin
+#[derive(Debug, Default)]
+pub struct ShortcutRegistry {
+    entries: Vec<ShortcutEntry>,
+}
+
+impl ShortcutRegistry {
+    /// Whether `entry` can be registered.
+    ///
+    /// Re-registering a binding for the same command is fine. The same key bound to
+    /// another command in the same slot, or to anything in the other source (both
+    /// listeners would fire, or one would swallow the key), is a conflict.
+    pub fn check(&self, entry: &ShortcutEntry) -> Result<(), ShortcutConflict> {
+        if let Some((_, reason)) = RESERVED
+            .iter()
+            .find(|(combo, _)| *combo == entry.normalized)
+        {
+            return Err(ShortcutConflict::Reserved {
+                accelerator: entry.accelerator.clone(),
+                reason,
+            });
+        }
+
+        let existing = self.entries.iter().find(|existing| {
+            existing.normalized == entry.normalized
+                && (existing.source != entry.source
+                    || (existing.gesture == entry.gesture
+                        && existing.command_id != entry.command_id))
+        });
+        match existing {
+            Some(existing) => Err(ShortcutConflict::AlreadyBound {
+                accelerator: entry.accelerator.clone(),
+                existing: existing.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Record `entry` once its listener accepted it
+    pub fn insert(&mut self, entry: ShortcutEntry) -> Result<(), ShortcutConflict> {
+        self.check(&entry)?;
+        self.entries.retain(|existing| !existing.same_slot(&entry));
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Without a gesture, removes every binding of the accelerator in `source`
+    pub fn remove(&mut self, source: ShortcutSource, accelerator: &str, gesture: Option<Gesture>) {
+        let Ok(probe) = ShortcutEntry::new(source, accelerator, "", gesture) else {
+            return;
+        };
+        self.entries.retain(|existing| {
+            !(existing.source == source
+                && existing.normalized == probe.normalized
+                && (gesture.is_none() || existing.gesture == gesture))
+        });
+    }
+
+    /// Remove every binding of `source`
+    pub fn clear(&mut self, source: ShortcutSource) {
+        self.entries.retain(|existing| existing.source != source);
+    }
+
+    /// Entries sorted by key
+    pub fn list(&self) -> Vec<ShortcutEntry> {
+        let mut entries = self.entries.clone();
+        entries.sort_by(|a, b| (&a.normalized, &a.command_id).cmp(&(&b.normalized, &b.command_id)));
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        source: ShortcutSource,
+        accelerator: &str,
+        command_id: &str,
+        gesture: Option<Gesture>,
+    ) -> ShortcutEntry {
+        ShortcutEntry::new(source, accelerator, command_id, gesture).unwrap()
+    }
+
+    fn bound_to(result: Result<(), ShortcutConflict>) -> String {
+        match result {
+            Err(ShortcutConflict::AlreadyBound { existing, .. }) => existing.command_id,
+            other => panic!("expected AlreadyBound, got {:?}", other),
+        }
+    }
+
+    fn commands(registry: &ShortcutRegistry) -> Vec<(String, Option<Gesture>)> {
+        registry
+            .list()
+            .into_iter()
+            .map(|entry| (entry.command_id, entry.gesture))
+            .collect()
+    }
+
+    #[test]
+    fn sources_cannot_share_a_key() {
+        let mut registry = ShortcutRegistry::default();
+        registry
+            .insert(entry(
+                ShortcutSource::Global,
+                "Control+Shift+KeyR",
+                "record",
+                None,
+            ))
+            .unwrap();
+
+        // Same combo spelled the Fn manager's way, even for the same command
+        let fn_entry = entry(ShortcutSource::Fn, "Shift+Control+R", "record", None);
+        assert_eq!(bound_to(registry.insert(fn_entry)), "record");
+
+        // and the other way round
+        registry
+            .insert(entry(ShortcutSource::Fn, "Fn+A", "cancel", None))
+            .unwrap();
+        let global = entry(ShortcutSource::Global, "Fn+KeyA", "cancel", None);
+        assert_eq!(bound_to(registry.check(&global)), "cancel");
+    }
+
+    #[test]
+    fn a_slot_holds_one_command() {
+        let mut registry = ShortcutRegistry::default();
+        registry
+            .insert(entry(
+                ShortcutSource::Fn,
+                "RightControl",
+                "pushToTalk",
+                Some(Gesture::Hold),
+            ))
+            .unwrap();
+
+        let other = entry(
+            ShortcutSource::Fn,
+            "RightControl",
+            "toggle",
+            Some(Gesture::Hold),
+        );
+        assert_eq!(bound_to(registry.insert(other)), "pushToTalk");
+
+        // Another gesture of the same key is another slot
+        registry
+            .insert(entry(
+                ShortcutSource::Fn,
+                "RightControl",
+                "toggle",
+                Some(Gesture::DoubleTap),
+            ))
+            .unwrap();
+        assert_eq!(
+            commands(&registry),
+            [
+                ("pushToTalk".to_string(), Some(Gesture::Hold)),
+                ("toggle".to_string(), Some(Gesture::DoubleTap)),
+            ]
+        );
+    }
+
+    #[test]
+    fn re_registering_a_command_replaces_its_entry() {
+        let mut registry = ShortcutRegistry::default();
+        registry
+            .insert(entry(
+                ShortcutSource::Global,
+                "Control+Shift+R",
+                "record",
+                None,
+            ))
+            .unwrap();
+        registry
+            .insert(entry(
+                ShortcutSource::Global,
+                "Shift+Control+KeyR",
+                "record",
+                None,
+            ))
+            .unwrap();
+
+        let entries = registry.list();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].accelerator, "Shift+Control+KeyR");
+    }
+
+    #[test]
+    fn reserved_combos_are_rejected() {
+        let (combo, reason) = RESERVED[0];
+        let mut registry = ShortcutRegistry::default();
+
+        match registry.insert(entry(ShortcutSource::Global, combo, "record", None)) {
+            Err(ShortcutConflict::Reserved {
+                accelerator,
+                reason: rejected,
+            }) => assert_eq!((accelerator.as_str(), rejected), (combo, reason)),
+            other => panic!("expected Reserved, got {:?}", other),
+        }
+        assert!(registry.list().is_empty());
+    }
+
+    #[test]
+    fn remove_by_gesture_or_all_gestures() {
+        let mut registry = ShortcutRegistry::default();
+        for (command_id, gesture) in [
+            ("pushToTalk", Some(Gesture::Hold)),
+            ("toggle", Some(Gesture::DoubleTap)),
+            ("cancel", Some(Gesture::Tap)),
+        ] {
+            registry
+                .insert(entry(
+                    ShortcutSource::Fn,
+                    "RightControl",
+                    command_id,
+                    gesture,
+                ))
+                .unwrap();
+        }
+        registry
+            .insert(entry(
+                ShortcutSource::Global,
+                "Control+Shift+R",
+                "record",
+                None,
+            ))
+            .unwrap();
+
+        // Spelled differently, still the same key
+        registry.remove(ShortcutSource::Fn, "rightctrl", Some(Gesture::DoubleTap));
+        assert_eq!(
+            commands(&registry),
+            [
+                ("record".to_string(), None),
+                ("cancel".to_string(), Some(Gesture::Tap)),
+                ("pushToTalk".to_string(), Some(Gesture::Hold)),
+            ]
+        );
+
+        // Without a gesture, every binding of the key in that source goes
+        registry.remove(ShortcutSource::Fn, "RightControl", None);
+        assert_eq!(commands(&registry), [("record".to_string(), None)]);
+
+        // Another source's binding of the key is left alone
+        registry.remove(ShortcutSource::Fn, "Control+Shift+R", None);
+        assert_eq!(registry.list().len(), 1);
+    }
+}
@@ -0,0 +1,473 @@
+// Supervision of shortcut backends.
+//
+// Listeners die quietly: macOS disables event taps that take too long, keyboards get
+// unplugged, permissions get revoked. The supervisor probes every backend, restarts
+// dead ones with exponential backoff and keeps a health record for the frontend.
+
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How often backends are probed
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A shortcut listener the supervisor can probe and restart
+pub trait ShortcutBackend: Send {
+    /// Name in health reports ("fn", "global")
+    fn name(&self) -> &'static str;
+
+    /// (Re)start the listener, keeping its registered shortcuts
+    fn start(&mut self) -> Result<(), String>;
+
+    /// `Err(reason)` when the listener died or was disabled
+    fn check(&self) -> Result<(), String>;
+
+    /// When the listener last saw a key event
+    fn last_event(&self) -> Option<SystemTime>;
+}
+
+/// Restart delays: `initial`, doubling up to `max`, back to `initial` once a
+/// backend has stayed up for `reset_after`
+#[derive(Debug, Clone, Copy)]
+pub struct BackoffPolicy {
+    pub initial: Duration,
+    pub max: Duration,
+    pub reset_after: Duration,
+}
+
+impl Default for BackoffPolicy {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(60),
+            reset_after: Duration::from_secs(60),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BackendStatus {
+    Running,
+    Restarting, // Down, waiting for the next restart attempt
+}
+
+/// Health of one backend - emitted as `shortcut-health` and returned by `get_shortcut_health`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutHealth {
+    pub backend: String,
+    pub status: BackendStatus,
+    pub last_event_at: Option<u64>, // Unix time in milliseconds
+    pub restart_count: u32,
+    pub failure_reason: Option<String>, // Most recent failure, kept after recovery
+}
+
+struct Supervised {
+    backend: Box<dyn ShortcutBackend>,
+    status: BackendStatus,
+    restart_count: u32,
+    failure_reason: Option<String>,
+    delay: Duration,           // Wait before the next restart attempt
+    retry_at: Option<Instant>, // Set while Restarting
+    running_since: Option<Instant>,
+}
+
+/// Watches backends and restarts them. `poll` does one round of checks; the thread
+/// started by `spawn_supervisor` calls it every `CHECK_INTERVAL`.
+pub struct ShortcutSupervisor {
+    policy: BackoffPolicy,
+    backends: Vec<Supervised>,
+}
+
+impl ShortcutSupervisor {
+    pub fn new(policy: BackoffPolicy) -> Self {
+        Self {
+            policy,
+            backends: Vec::new(),
+        }
+    }
+
+    /// Watch an already started backend. One that is down gets restarted on the next poll.
+    pub fn add(&mut self, backend: Box<dyn ShortcutBackend>, now: Instant) {
+        let failure_reason = backend.check().err();
+        let status = if failure_reason.is_some() {
+            BackendStatus::Restarting
+        } else {
+            BackendStatus::Running
+        };
+        println!(
+            "[ShortcutSupervisor] Watching '{}' ({:?})",
+            backend.name(),
+            status
+        );
+
+        self.backends.push(Supervised {
+            backend,
+            status,
+            restart_count: 0,
+            retry_at: failure_reason.as_ref().map(|_| now),
+            running_since: failure_reason.is_none().then_some(now),
+            failure_reason,
+            delay: self.policy.initial,
+        });
+    }
+
+    /// Probe every backend and restart the ones that are due. Returns the health of
+    /// backends whose status changed.
+    pub fn poll(&mut self, now: Instant) -> Vec<ShortcutHealth> {
+        let policy = self.policy;
+        let mut changed = Vec::new();
+
+        for entry in &mut self.backends {
+            match entry.status {
+                BackendStatus::Running => match entry.backend.check() {
+                    Ok(()) => {
+                        if entry
+                            .running_since
+                            .is_some_and(|since| now.duration_since(since) >= policy.reset_after)
+                        {
+                            entry.delay = policy.initial;
+                        }
+                    }
+                    Err(reason) => {
+                        eprintln!(
+                            "[ShortcutSupervisor] '{}' is down: {}",
+                            entry.backend.name(),
+                            reason
+                        );
+                        entry.fail(reason, now, &policy);
+                        changed.push(entry.health());
+                    }
+                },
+                BackendStatus::Restarting => {
+                    if entry.retry_at.is_some_and(|at| now < at) {
+                        continue;
+                    }
+
+                    println!(
+                        "[ShortcutSupervisor] Restarting '{}' (attempt {})",
+                        entry.backend.name(),
+                        entry.restart_count + 1
+                    );
+                    match entry.backend.start().and_then(|()| entry.backend.check()) {
+                        Ok(()) => {
+                            entry.status = BackendStatus::Running;
+                            entry.restart_count += 1;
+                            entry.retry_at = None;
+                            entry.running_since = Some(now);
+                            println!("[ShortcutSupervisor] '{}' is back up", entry.backend.name());
+                        }
+                        Err(reason) => {
+                            eprintln!(
+                                "[ShortcutSupervisor] Restart of '{}' failed: {}",
+                                entry.backend.name(),
+                                reason
+                            );
+                            entry.fail(reason, now, &policy);
+                        }
+                    }
+                    changed.push(entry.health());
+                }
+            }
+        }
+
+        changed
+    }
+
+    pub fn health(&self) -> Vec<ShortcutHealth> {
+        self.backends.iter().map(Supervised::health).collect()
+    }
+}
+
+impl Supervised {
+    /// Mark down and schedule the next attempt, doubling the delay
+    fn fail(&mut self, reason: String, now: Instant, policy: &BackoffPolicy) {
+        self.status = BackendStatus::Restarting;
+        self.failure_reason = Some(reason);
+        self.running_since = None;
+        self.retry_at = Some(now + self.delay);
+        self.delay = (self.delay * 2).min(policy.max);
+    }
+
+    fn health(&self) -> ShortcutHealth {
+        ShortcutHealth {
+            backend: self.backend.name().to_string(),
+            status: self.status,
+            last_event_at: self
+                .backend
+                .last_event()
+                .and_then(|at| at.duration_since(UNIX_EPOCH).ok())
+                .map(|since_epoch| since_epoch.as_millis() as u64),
+            restart_count: self.restart_count,
+            failure_reason: self.failure_reason.clone(),
+        }
+    }
+}
+
+/// Poll `supervisor` every `CHECK_INTERVAL`, reporting status changes to `on_change`
+pub fn spawn_supervisor(
+    supervisor: Arc<Mutex<ShortcutSupervisor>>,
+    on_change: impl Fn(&ShortcutHealth) + Send + 'static,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || loop {
+        thread::sleep(CHECK_INTERVAL);
+
+        let changed = match supervisor.lock() {
+            Ok(mut supervisor) => supervisor.poll(Instant::now()),
+            Err(_) => break,
+        };
+        for health in &changed {
+            on_change(health);
+        }
+    })
+}
+
+// ============================================================================
+// GLOBAL SHORTCUTS
+// ============================================================================
+
+/// The global-shortcut plugin listens inside the app's event loop, so there is
+/// nothing to restart; it is supervised for its activity record only.
+#[derive(Clone, Default)]
+pub struct GlobalShortcutBackend {
+    last_event: Arc<Mutex<Option<SystemTime>>>,
+}
+
+impl GlobalShortcutBackend {
+    /// Record a shortcut event (called from the plugin's handler)
+    pub fn touch(&self) {
+        if let Ok(mut last_event) = self.last_event.lock() {
+            *last_event = Some(SystemTime::now());
+        }
+    }
+}
+
+impl ShortcutBackend for GlobalShortcutBackend {
+    fn name(&self) -> &'static str {
+        "global"
+    }
+
+    fn start(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn check(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn last_event(&self) -> Option<SystemTime> {
+        self.last_event
+            .lock()
+            .ok()
+            .and_then(|last_event| *last_event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+    const MS: Duration = Duration::from_millis(1);
+
+    const POLICY: BackoffPolicy = BackoffPolicy {
+        initial: SECOND,
+        max: Duration::from_secs(8),
+        reset_after: Duration::from_secs(30),
+    };
+
+    /// What the fake listener does, shared with the test
+    #[derive(Default)]
+    struct Fake {
+        down: Option<String>,        // Reason `check` fails with
+        start_error: Option<String>, // Reason `start` fails with
+        starts: u32,
+    }
+
+    struct FakeBackend(Arc<Mutex<Fake>>);
+
+    impl ShortcutBackend for FakeBackend {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+
+        fn start(&mut self) -> Result<(), String> {
+            let mut fake = self.0.lock().unwrap();
+            fake.starts += 1;
+            match fake.start_error.clone() {
+                Some(reason) => Err(reason),
+                None => {
+                    fake.down = None;
+                    Ok(())
+                }
+            }
+        }
+
+        fn check(&self) -> Result<(), String> {
+            self.0.lock().unwrap().down.clone().map_or(Ok(()), Err)
+        }
+
+        fn last_event(&self) -> Option<SystemTime> {
+            Some(UNIX_EPOCH + Duration::from_millis(1234))
+        }
+    }
+
+    fn supervised(now: Instant) -> (ShortcutSupervisor, Arc<Mutex<Fake>>) {
+        let fake = Arc::new(Mutex::new(Fake::default()));
+        let mut supervisor = ShortcutSupervisor::new(POLICY);
+        supervisor.add(Box::new(FakeBackend(fake.clone())), now);
+        (supervisor, fake)
+    }
+
+    /// Take the backend down, failing every restart until `recover`
+    fn kill(fake: &Mutex<Fake>, reason: &str) {
+        let mut fake = fake.lock().unwrap();
+        fake.down = Some(reason.to_string());
+        fake.start_error = Some("Still down".to_string());
+    }
+
+    fn recover(fake: &Mutex<Fake>) {
+        fake.lock().unwrap().start_error = None;
+    }
+
+    fn starts(fake: &Mutex<Fake>) -> u32 {
+        fake.lock().unwrap().starts
+    }
+
+    /// Poll just before and at `delay` after `now`, expecting one restart attempt at the end
+    fn expect_attempt_after(
+        supervisor: &mut ShortcutSupervisor,
+        fake: &Mutex<Fake>,
+        now: Instant,
+        delay: Duration,
+    ) -> Instant {
+        let before = starts(fake);
+        assert!(supervisor.poll(now + delay - MS).is_empty());
+        assert_eq!(starts(fake), before, "attempted before {:?}", delay);
+        assert_eq!(supervisor.poll(now + delay).len(), 1);
+        assert_eq!(starts(fake), before + 1, "no attempt after {:?}", delay);
+        now + delay
+    }
+
+    #[test]
+    fn running_backends_are_left_alone() {
+        let now = Instant::now();
+        let (mut supervisor, fake) = supervised(now);
+        assert!(supervisor.poll(now + SECOND).is_empty());
+        assert_eq!(starts(&fake), 0);
+
+        let health = &supervisor.health()[0];
+        assert_eq!(health.backend, "fake");
+        assert_eq!(health.status, BackendStatus::Running);
+        assert_eq!(health.last_event_at, Some(1234));
+        assert_eq!(health.restart_count, 0);
+        assert_eq!(health.failure_reason, None);
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let mut now = Instant::now();
+        let (mut supervisor, fake) = supervised(now);
+        kill(&fake, "Keyboard unplugged");
+
+        let changed = supervisor.poll(now);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].status, BackendStatus::Restarting);
+
+        for seconds in [1, 2, 4, 8, 8, 8] {
+            now = expect_attempt_after(&mut supervisor, &fake, now, seconds * SECOND);
+            assert_eq!(supervisor.health()[0].status, BackendStatus::Restarting);
+        }
+        assert_eq!(supervisor.health()[0].restart_count, 0);
+    }
+
+    #[test]
+    fn recovery_counts_restarts_and_keeps_the_reason() {
+        let now = Instant::now();
+        let (mut supervisor, fake) = supervised(now);
+        kill(&fake, "Event tap disabled by timeout");
+        supervisor.poll(now);
+        recover(&fake);
+
+        let changed = supervisor.poll(now + SECOND);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].status, BackendStatus::Running);
+        assert_eq!(changed[0].restart_count, 1);
+        assert_eq!(
+            changed[0].failure_reason.as_deref(),
+            Some("Event tap disabled by timeout")
+        );
+
+        // Only changes are reported
+        assert!(supervisor.poll(now + 2 * SECOND).is_empty());
+
+        // A failed restart replaces the reason, a successful one keeps counting
+        kill(&fake, "Permission revoked");
+        supervisor.poll(now + 3 * SECOND);
+        let now = expect_attempt_after(&mut supervisor, &fake, now + 3 * SECOND, 2 * SECOND);
+        assert_eq!(
+            supervisor.health()[0].failure_reason.as_deref(),
+            Some("Still down")
+        );
+        recover(&fake);
+        supervisor.poll(now + 4 * SECOND);
+        assert_eq!(supervisor.health()[0].restart_count, 2);
+    }
+
+    #[test]
+    fn delay_keeps_growing_while_the_backend_flaps() {
+        let now = Instant::now();
+        let (mut supervisor, fake) = supervised(now);
+        kill(&fake, "Down");
+        supervisor.poll(now);
+        let now = expect_attempt_after(&mut supervisor, &fake, now, SECOND);
+        recover(&fake);
+        let now = expect_attempt_after(&mut supervisor, &fake, now, 2 * SECOND);
+        assert_eq!(supervisor.health()[0].status, BackendStatus::Running);
+
+        // Down again before `reset_after`: the next delay carries on from 4 s
+        kill(&fake, "Down");
+        let now = now + 10 * SECOND;
+        supervisor.poll(now);
+        expect_attempt_after(&mut supervisor, &fake, now, 4 * SECOND);
+    }
+
+    #[test]
+    fn delay_resets_after_staying_up() {
+        let now = Instant::now();
+        let (mut supervisor, fake) = supervised(now);
+        kill(&fake, "Down");
+        supervisor.poll(now);
+        let now = expect_attempt_after(&mut supervisor, &fake, now, SECOND);
+        recover(&fake);
+        let now = expect_attempt_after(&mut supervisor, &fake, now, 2 * SECOND);
+
+        // Up for `reset_after`
+        assert!(supervisor.poll(now + POLICY.reset_after).is_empty());
+
+        kill(&fake, "Down");
+        let now = now + POLICY.reset_after + SECOND;
+        supervisor.poll(now);
+        expect_attempt_after(&mut supervisor, &fake, now, SECOND);
+    }
+
+    #[test]
+    fn dead_backends_are_restarted_on_the_first_poll() {
+        let now = Instant::now();
+        let fake = Arc::new(Mutex::new(Fake {
+            down: Some("No keyboards".to_string()),
+            ..Fake::default()
+        }));
+        let mut supervisor = ShortcutSupervisor::new(POLICY);
+        supervisor.add(Box::new(FakeBackend(fake.clone())), now);
+        assert_eq!(supervisor.health()[0].status, BackendStatus::Restarting);
+
+        let changed = supervisor.poll(now);
+        assert_eq!(starts(&fake), 1);
+        assert_eq!(changed[0].status, BackendStatus::Running);
+        assert_eq!(changed[0].restart_count, 1);
+        assert_eq!(changed[0].failure_reason.as_deref(), Some("No keyboards"));
+    }
+}
@@ -1,3 +1,4 @@
+use super::core::{Gesture, ShortcutConfig};
 use tauri::AppHandle;
 
 pub struct WindowsFnShortcutManager;
@@ -7,11 +8,20 @@ impl WindowsFnShortcutManager {
         Err("Fn key shortcuts are not supported on Windows".to_string())
     }
 
-    pub fn register(&self, _shortcut: &str, _command_id: &str) -> Result<(), String> {
+    pub fn register(
+        &self,
+        _shortcut: &str,
+        _command_id: &str,
+        _gesture: Option<Gesture>,
+    ) -> Result<(), String> {
         Err("Fn key shortcuts are not supported on Windows".to_string())
     }
 
-    pub fn unregister(&self, _shortcut: &str) -> Result<(), String> {
+    pub fn unregister(&self, _shortcut: &str, _gesture: Option<Gesture>) -> Result<(), String> {
+        Err("Fn key shortcuts are not supported on Windows".to_string())
+    }
+
+    pub fn set_gesture_timing(&self, _config: ShortcutConfig) -> Result<(), String> {
         Err("Fn key shortcuts are not supported on Windows".to_string())
     }
 
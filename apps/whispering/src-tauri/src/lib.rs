@@ -5,8 +5,8 @@ mod accessibility;
 #[cfg(target_os = "macos")]
 mod microphone;
 
-// Keyboard handling module (Fn key support)
-mod keyboard;
+// Keyboard handling module (Fn key support, shared shortcut state machine)
+pub mod keyboard;
 
 // Re-export platform-specific functions
 #[cfg(target_os = "macos")]
@@ -15,7 +15,7 @@ use accessibility::{is_macos_accessibility_enabled, open_apple_accessibility};
 #[cfg(target_os = "macos")]
 use microphone::{is_macos_microphone_enabled, request_macos_microphone_permission};
 
-use tauri::{Manager, WebviewUrl, WebviewWindowBuilder, menu::{Menu, MenuItem}, tray::{TrayIconBuilder, TrayIconEvent}};
+use tauri::{Emitter, Manager, WebviewUrl, WebviewWindowBuilder, menu::{Menu, MenuItem}, tray::{TrayIconBuilder, TrayIconEvent}};
 use tauri_plugin_aptabase::EventTracker;
 use tauri_plugin_clipboard_manager;
 
@@ -26,11 +26,23 @@ use cocoa::base::nil;
 #[cfg(target_os = "macos")]
 use objc::{msg_send, sel, sel_impl};
 
+pub mod delivery;
+use delivery::{get_focused_app, get_text_input_backend, paste, write_text, ClipboardPreservation};
+
 pub mod recorder;
 use recorder::commands::{
     cancel_recording, close_recording_session, enumerate_recording_devices,
-    get_current_recording_id, hide_recording_overlay, init_recording_session, 
-    set_overlay_processing, show_recording_overlay, start_recording, stop_recording, AppData,
+    get_current_recording_id, get_input_level, get_recorder_status, get_recovered_recordings,
+    hide_recording_overlay, init_recording_session, pause_recording, resume_recording,
+    set_overlay_processing, show_recording_overlay, spawn_startup_recovery, start_recording,
+    stop_recording, AppData,
+};
+
+pub mod transcription;
+use transcription::{
+    get_local_models_dir, is_local_transcription_available, list_local_models,
+    start_streaming_transcription, stop_streaming_transcription, transcribe_recording_locally,
+    unload_local_model, verify_local_model, TranscriptionState,
 };
 
 #[cfg(target_os = "macos")]
@@ -240,7 +252,13 @@ fn create_recording_overlay_at_startup(app: &tauri::App) -> Result<(), String> {
 // FN SHORTCUT MANAGER COMMANDS
 // ============================================================================
 
-use std::sync::Mutex as StdMutex;
+use keyboard::core::{Gesture, GestureTiming, ShortcutConfig};
+use keyboard::registry::{ShortcutEntry, ShortcutError, ShortcutRegistry, ShortcutSource};
+use keyboard::supervisor::{
+    spawn_supervisor, BackoffPolicy, GlobalShortcutBackend, ShortcutHealth, ShortcutSupervisor,
+};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Instant;
 
 #[cfg(target_os = "macos")]
 type FnShortcutManager = StdMutex<Option<keyboard::macos::MacOSFnShortcutManager>>;
@@ -249,40 +267,122 @@ type FnShortcutManager = StdMutex<Option<keyboard::macos::MacOSFnShortcutManager
 type FnShortcutManager = StdMutex<keyboard::windows::WindowsFnShortcutManager>;
 
 #[cfg(target_os = "linux")]
-type FnShortcutManager = StdMutex<keyboard::linux::LinuxFnShortcutManager>;
+type FnShortcutManager = StdMutex<Option<keyboard::linux::LinuxFnShortcutManager>>;
+
+type ShortcutRegistryState = StdMutex<ShortcutRegistry>;
 
 #[tauri::command]
 fn register_fn_shortcut(
     manager: tauri::State<FnShortcutManager>,
+    registry: tauri::State<ShortcutRegistryState>,
     accelerator: String,
     command_id: String,
-) -> Result<(), String> {
+    gesture: Option<Gesture>,
+) -> Result<(), ShortcutError> {
     println!(
-        "[FnShortcut] Command: register_fn_shortcut({}, {})",
-        accelerator, command_id
+        "[FnShortcut] Command: register_fn_shortcut({}, {}, {:?})",
+        accelerator, command_id, gesture
     );
 
+    let entry = ShortcutEntry::new(ShortcutSource::Fn, &accelerator, &command_id, gesture)?;
+    let mut registry = registry.lock().map_err(|_| "Failed to lock shortcut registry")?;
+    if let Err(conflict) = registry.check(&entry) {
+        eprintln!("[FnShortcut] Rejected: {}", conflict);
+        return Err(conflict.into());
+    }
+
     let mgr_guard = manager.lock().map_err(|_| "Failed to lock manager")?;
     match mgr_guard.as_ref() {
-        Some(mgr) => mgr.register(&accelerator, &command_id),
-        None => Err("Fn shortcut manager not initialized (check Accessibility permissions)".to_string()),
+        Some(mgr) => mgr.register(&accelerator, &command_id, gesture)?,
+        None => return Err("Fn shortcut manager not initialized (check Accessibility permissions)".into()),
     }
+    registry.insert(entry)?;
+    Ok(())
 }
 
 #[tauri::command]
 fn unregister_fn_shortcut(
     manager: tauri::State<FnShortcutManager>,
+    registry: tauri::State<ShortcutRegistryState>,
     accelerator: String,
+    gesture: Option<Gesture>,
+) -> Result<(), String> {
+    println!(
+        "[FnShortcut] Command: unregister_fn_shortcut({}, {:?})",
+        accelerator, gesture
+    );
+
+    registry
+        .lock()
+        .map_err(|_| "Failed to lock shortcut registry")?
+        .remove(ShortcutSource::Fn, &accelerator, gesture);
+
+    let mgr_guard = manager.lock().map_err(|_| "Failed to lock manager")?;
+    match mgr_guard.as_ref() {
+        Some(mgr) => mgr.unregister(&accelerator, gesture),
+        None => Err("Fn shortcut manager not initialized (check Accessibility permissions)".to_string()),
+    }
+}
+
+#[tauri::command]
+fn set_fn_gesture_timing(
+    manager: tauri::State<FnShortcutManager>,
+    timing: GestureTiming,
 ) -> Result<(), String> {
-    println!("[FnShortcut] Command: unregister_fn_shortcut({})", accelerator);
+    println!("[FnShortcut] Command: set_fn_gesture_timing({:?})", timing);
 
+    let config = ShortcutConfig::try_from(timing)?;
     let mgr_guard = manager.lock().map_err(|_| "Failed to lock manager")?;
     match mgr_guard.as_ref() {
-        Some(mgr) => mgr.unregister(&accelerator),
+        Some(mgr) => mgr.set_gesture_timing(config),
         None => Err("Fn shortcut manager not initialized (check Accessibility permissions)".to_string()),
     }
 }
 
+/// Record a global shortcut before the frontend hands it to the global-shortcut plugin
+#[tauri::command]
+fn claim_global_shortcut(
+    registry: tauri::State<ShortcutRegistryState>,
+    accelerator: String,
+    command_id: String,
+) -> Result<(), ShortcutError> {
+    println!(
+        "[GlobalShortcut] Command: claim_global_shortcut({}, {})",
+        accelerator, command_id
+    );
+
+    let entry = ShortcutEntry::new(ShortcutSource::Global, &accelerator, &command_id, None)?;
+    let mut registry = registry.lock().map_err(|_| "Failed to lock shortcut registry")?;
+    registry.insert(entry).map_err(|conflict| {
+        eprintln!("[GlobalShortcut] Rejected: {}", conflict);
+        conflict.into()
+    })
+}
+
+/// Without an accelerator, releases every global shortcut
+#[tauri::command]
+fn release_global_shortcut(
+    registry: tauri::State<ShortcutRegistryState>,
+    accelerator: Option<String>,
+) -> Result<(), String> {
+    println!("[GlobalShortcut] Command: release_global_shortcut({:?})", accelerator);
+
+    let mut registry = registry.lock().map_err(|_| "Failed to lock shortcut registry")?;
+    match accelerator {
+        Some(accelerator) => registry.remove(ShortcutSource::Global, &accelerator, None),
+        None => registry.clear(ShortcutSource::Global),
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn list_shortcuts(
+    registry: tauri::State<ShortcutRegistryState>,
+) -> Result<Vec<ShortcutEntry>, String> {
+    let registry = registry.lock().map_err(|_| "Failed to lock shortcut registry")?;
+    Ok(registry.list())
+}
+
 #[tauri::command]
 fn enable_fn_recording_mode(manager: tauri::State<FnShortcutManager>) -> Result<(), String> {
     println!("[FnShortcut] Command: enable_fn_recording_mode");
@@ -339,10 +439,83 @@ fn reinitialize_fn_manager(app: tauri::AppHandle) -> Result<(), String> {
         }
     }
 
-    #[cfg(not(target_os = "macos"))]
+    #[cfg(target_os = "linux")]
+    {
+        // E.g. after the user was added to the 'input' group
+        let new_manager = keyboard::linux::LinuxFnShortcutManager::new(app.clone())
+            .map_err(|e| format!("Failed to reinitialize Fn manager: {}", e))?;
+        let manager_state: tauri::State<FnShortcutManager> = app.state();
+        let mut mgr_guard = manager_state.lock().map_err(|_| "Failed to lock manager")?;
+        *mgr_guard = Some(new_manager);
+        println!("[FnShortcut] ✓ Linux manager reinitialized");
+        Ok(())
+    }
+
+    #[cfg(target_os = "windows")]
     Ok(())
 }
 
+// ============================================================================
+// SHORTCUT SUPERVISION
+// ============================================================================
+
+type ShortcutSupervisorState = Arc<StdMutex<ShortcutSupervisor>>;
+
+/// The managed Fn shortcut manager, as seen by the supervisor. Looks the manager up on
+/// every call so a `reinitialize_fn_manager` replacement is picked up.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+struct FnShortcutBackend {
+    app: tauri::AppHandle,
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+impl keyboard::supervisor::ShortcutBackend for FnShortcutBackend {
+    fn name(&self) -> &'static str {
+        "fn"
+    }
+
+    fn start(&mut self) -> Result<(), String> {
+        let manager_state: tauri::State<FnShortcutManager> = self.app.state();
+        let mut mgr_guard = manager_state.lock().map_err(|_| "Failed to lock manager")?;
+        if let Some(mgr) = mgr_guard.as_mut() {
+            return mgr.restart();
+        }
+
+        // Never came up (e.g. missing permissions) - try from scratch
+        #[cfg(target_os = "macos")]
+        let manager = keyboard::macos::MacOSFnShortcutManager::new(self.app.clone())?;
+        #[cfg(target_os = "linux")]
+        let manager = keyboard::linux::LinuxFnShortcutManager::new(self.app.clone())?;
+        *mgr_guard = Some(manager);
+        Ok(())
+    }
+
+    fn check(&self) -> Result<(), String> {
+        let manager_state: tauri::State<FnShortcutManager> = self.app.state();
+        let mgr_guard = manager_state.lock().map_err(|_| "Failed to lock manager")?;
+        match mgr_guard.as_ref() {
+            Some(mgr) => mgr.check(),
+            None => Err("Fn shortcut manager not initialized".to_string()),
+        }
+    }
+
+    fn last_event(&self) -> Option<std::time::SystemTime> {
+        let manager_state: tauri::State<FnShortcutManager> = self.app.state();
+        let mgr_guard = manager_state.lock().ok()?;
+        mgr_guard.as_ref().and_then(|mgr| mgr.last_event())
+    }
+}
+
+#[tauri::command]
+fn get_shortcut_health(
+    supervisor: tauri::State<ShortcutSupervisorState>,
+) -> Result<Vec<ShortcutHealth>, String> {
+    let supervisor = supervisor
+        .lock()
+        .map_err(|_| "Failed to lock shortcut supervisor")?;
+    Ok(supervisor.health())
+}
+
 // ============================================================================
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -350,6 +523,9 @@ fn reinitialize_fn_manager(app: tauri::AppHandle) -> Result<(), String> {
 pub async fn run() {
     // Load .env file if it exists
     dotenvy::dotenv().ok();
+
+    // Records global shortcut activity for the supervisor
+    let global_shortcuts = GlobalShortcutBackend::default();
     
     let mut builder = tauri::Builder::default()
         .plugin(tauri_plugin_aptabase::Builder::new(
@@ -358,7 +534,14 @@ pub async fn run() {
         .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
-        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler({
+                    let global_shortcuts = global_shortcuts.clone();
+                    move |_app, _shortcut, _event| global_shortcuts.touch()
+                })
+                .build(),
+        )
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_os::init())
@@ -366,7 +549,10 @@ pub async fn run() {
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_deep_link::init())
-        .manage(AppData::new());
+        .manage(AppData::new())
+        .manage(TranscriptionState::default())
+        .manage(ClipboardPreservation::default())
+        .manage(ShortcutRegistryState::default());
 
     #[cfg(desktop)]
     {
@@ -389,18 +575,35 @@ pub async fn run() {
     let builder = builder.invoke_handler(tauri::generate_handler![
         write_text,
         paste,
+        get_focused_app,
+        get_text_input_backend,
         open_apple_accessibility,
         is_macos_accessibility_enabled,
         is_macos_microphone_enabled,
         request_macos_microphone_permission,
         // Audio recorder commands
         get_current_recording_id,
+        get_recorder_status,
+        get_input_level,
+        get_recovered_recordings,
         enumerate_recording_devices,
         init_recording_session,
         close_recording_session,
         start_recording,
+        pause_recording,
+        resume_recording,
         stop_recording,
         cancel_recording,
+        // Streaming transcription commands
+        start_streaming_transcription,
+        stop_streaming_transcription,
+        // Local transcription commands
+        is_local_transcription_available,
+        get_local_models_dir,
+        list_local_models,
+        verify_local_model,
+        transcribe_recording_locally,
+        unload_local_model,
         // UI commands
         show_recording_overlay,
         hide_recording_overlay,
@@ -408,23 +611,45 @@ pub async fn run() {
         // Fn shortcut commands
         register_fn_shortcut,
         unregister_fn_shortcut,
+        set_fn_gesture_timing,
         enable_fn_recording_mode,
         disable_fn_recording_mode,
         reinitialize_fn_manager,
+        get_shortcut_health,
+        claim_global_shortcut,
+        release_global_shortcut,
+        list_shortcuts,
     ]);
 
     #[cfg(not(target_os = "macos"))]
     let builder = builder.invoke_handler(tauri::generate_handler![
         write_text,
         paste,
+        get_focused_app,
+        get_text_input_backend,
         // Audio recorder commands
         get_current_recording_id,
+        get_recorder_status,
+        get_input_level,
+        get_recovered_recordings,
         enumerate_recording_devices,
         init_recording_session,
         close_recording_session,
         start_recording,
+        pause_recording,
+        resume_recording,
         stop_recording,
         cancel_recording,
+        // Streaming transcription commands
+        start_streaming_transcription,
+        stop_streaming_transcription,
+        // Local transcription commands
+        is_local_transcription_available,
+        get_local_models_dir,
+        list_local_models,
+        verify_local_model,
+        transcribe_recording_locally,
+        unload_local_model,
         // UI commands
         show_recording_overlay,
         hide_recording_overlay,
@@ -432,13 +657,21 @@ pub async fn run() {
         // Fn shortcut commands
         register_fn_shortcut,
         unregister_fn_shortcut,
+        set_fn_gesture_timing,
         enable_fn_recording_mode,
         disable_fn_recording_mode,
         reinitialize_fn_manager,
+        get_shortcut_health,
+        claim_global_shortcut,
+        release_global_shortcut,
+        list_shortcuts,
     ]);
 
     let mut app = builder
-        .setup(|app| {
+        .setup(move |app| {
+            // Repair recordings left behind by a crash or forced quit
+            spawn_startup_recovery(app.handle().clone());
+
             println!("[FnShortcut] Initializing Fn shortcut manager...");
 
             // Initialize Fn shortcut manager based on platform
@@ -468,11 +701,38 @@ pub async fn run() {
 
             #[cfg(target_os = "linux")]
             {
-                // Linux stub - will return error when trying to use
-                println!("[FnShortcut] Linux platform detected (Fn shortcuts not supported)");
-                let manager = keyboard::linux::LinuxFnShortcutManager;
-                app.manage(StdMutex::new(manager));
+                match keyboard::linux::LinuxFnShortcutManager::new(app.handle().clone()) {
+                    Ok(manager) => {
+                        println!("[FnShortcut] Linux manager initialized successfully");
+                        app.manage(StdMutex::new(Some(manager)));
+                    }
+                    Err(e) => {
+                        eprintln!("[FnShortcut] ERROR: Failed to initialize Linux manager: {}", e);
+                        // Store None so the state exists, will be initialized later
+                        app.manage(StdMutex::new(None::<keyboard::linux::LinuxFnShortcutManager>));
+                    }
+                }
+            }
+
+            // Restart shortcut listeners that die, and report their health
+            let supervisor: ShortcutSupervisorState =
+                Arc::new(StdMutex::new(ShortcutSupervisor::new(BackoffPolicy::default())));
+            {
+                let mut supervisor = supervisor.lock().unwrap();
+                supervisor.add(Box::new(global_shortcuts), Instant::now());
+                #[cfg(any(target_os = "macos", target_os = "linux"))]
+                supervisor.add(
+                    Box::new(FnShortcutBackend {
+                        app: app.handle().clone(),
+                    }),
+                    Instant::now(),
+                );
             }
+            let app_handle = app.handle().clone();
+            spawn_supervisor(supervisor.clone(), move |health| {
+                let _ = app_handle.emit("shortcut-health", health);
+            });
+            app.manage(supervisor);
 
             Ok(())
         })
@@ -572,115 +832,3 @@ pub async fn run() {
         _ => {}
     });
 }
-
-use enigo::{Direction, Enigo, Key, Keyboard, Settings};
-
-/// Types text character-by-character at the cursor position using Enigo.
-///
-/// This simulates keyboard input by typing each character sequentially, which works
-/// across all applications but is slower than pasting. Best used as a fallback when
-/// paste operations fail or for applications that don't support paste.
-///
-/// **Note**: This method may have issues with non-ASCII characters in some applications
-/// and can appear slow for large text blocks.
-#[tauri::command]
-fn write_text(text: String, app_handle: tauri::AppHandle, keep_window_visible: Option<bool>, refocus_window: Option<bool>) -> Result<(), String> {
-    // Hide our main window if it's visible to avoid interfering with focus
-    // Unless keep_window_visible is true (used during onboarding when pasting into the app itself)
-    let should_hide = !keep_window_visible.unwrap_or(false);
-    let should_refocus = refocus_window.unwrap_or(false);
-
-    if should_hide {
-        if let Some(main_window) = app_handle.get_webview_window("main") {
-            if let Ok(is_visible) = main_window.is_visible() {
-                if is_visible {
-                    let _ = main_window.hide();
-                }
-            }
-        }
-    }
-    
-    // Try to create Enigo instance with retry for permission issues
-    let mut enigo = None;
-    let mut last_error = String::new();
-    
-    for attempt in 1..=2 {
-        match Enigo::new(&Settings::default()) {
-            Ok(e) => {
-                enigo = Some(e);
-                break;
-            },
-            Err(e) => {
-                last_error = format!("Failed to create Enigo instance: {}", e);
-                
-                if last_error.contains("permission") && attempt == 1 {
-                    std::thread::sleep(std::time::Duration::from_millis(500));
-                }
-            }
-        }
-    }
-    
-    let mut enigo = enigo.ok_or(last_error)?;
-    
-    // Removed delay for maximum speed
-
-    // Use clipboard + paste approach instead of character simulation
-    // This works better with terminal applications and avoids duplication issues
-
-    // First, copy text to clipboard using Tauri's clipboard manager (handles UTF-8 properly)
-    use tauri_plugin_clipboard_manager::ClipboardExt;
-    app_handle.clipboard().write_text(text.clone())
-        .map_err(|e| format!("Failed to write to clipboard: {}", e))?;
-    
-    // When pasting into the app itself (onboarding), refocus the window
-    if should_refocus {
-        if let Some(main_window) = app_handle.get_webview_window("main") {
-            let _ = main_window.set_focus();
-            // Give focus time to settle
-            std::thread::sleep(std::time::Duration::from_millis(200));
-        }
-    }
-
-    // Then simulate paste (Cmd+V on macOS, Ctrl+V elsewhere)
-    #[cfg(target_os = "macos")]
-    let modifier = Key::Meta;
-    #[cfg(not(target_os = "macos"))]
-    let modifier = Key::Control;
-
-    enigo.key(modifier, Direction::Press).map_err(|e| format!("Failed to press modifier key: {}", e))?;
-    enigo.key(Key::Unicode('v'), Direction::Click).map_err(|e| format!("Failed to click 'v' key: {}", e))?;
-    enigo.key(modifier, Direction::Release).map_err(|e| format!("Failed to release modifier key: {}", e))?;
-    
-    Ok(())
-}
-
-/// Simulates a paste operation (Cmd+V on macOS, Ctrl+V elsewhere).
-///
-/// **Important**: This assumes text is already in the system clipboard. Call your
-/// clipboard service to copy text before using this function.
-///
-/// **Known Issue**: Uses `Key::Unicode('v')` which assumes QWERTY keyboard layout.
-/// This may fail on alternative layouts like Dvorak or Colemak.
-#[tauri::command]
-fn paste() -> Result<(), String> {
-    let mut enigo = Enigo::new(&Settings::default()).map_err(|e| {
-        format!("Failed to create Enigo instance for paste: {}", e)
-    })?;
-
-    #[cfg(target_os = "macos")]
-    let modifier = Key::Meta;
-    #[cfg(not(target_os = "macos"))]
-    let modifier = Key::Control;
-
-    enigo
-        .key(modifier, Direction::Press)
-        .map_err(|e| format!("Failed to press modifier key: {}", e))?;
-    enigo
-        .key(Key::Unicode('v'), Direction::Click)
-        .map_err(|e| format!("Failed to click 'v' key: {}", e))?;
-    enigo
-        .key(modifier, Direction::Release)
-        .map_err(|e| format!("Failed to release modifier key: {}", e))?;
-
-    Ok(())
-}
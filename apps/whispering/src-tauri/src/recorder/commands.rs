@@ -1,24 +1,125 @@
-use crate::recorder::recorder::{AudioRecording, RecorderState, Result};
-use std::path::PathBuf;
+use crate::recorder::devices::RecordingDevice;
+use crate::recorder::encoder::RecordingFormat;
+use crate::recorder::levels::AudioLevel;
+use crate::recorder::limits::RecordingLimits;
+use crate::recorder::recorder::{AudioRecording, RecorderState, Result, SessionConfig};
+use crate::recorder::recovery::{recover_interrupted_recordings, RecoveredRecording};
+use crate::recorder::status::RecorderStatusInfo;
+use crate::recorder::streaming::{StreamingConfig, StreamingSession};
+use crate::recorder::vad::VadConfig;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use tauri::{Manager, State};
-use tracing::{debug, info};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{debug, info, warn};
 
 /// Application state containing the recorder
 pub struct AppData {
     pub recorder: Mutex<RecorderState>,
+    pub recovered: Mutex<Vec<RecoveredRecording>>, // Interrupted recordings repaired so far
 }
 
 impl AppData {
     pub fn new() -> Self {
         Self {
             recorder: Mutex::new(RecorderState::new()),
+            recovered: Mutex::new(Vec::new()),
         }
     }
 }
 
+/// Resolve the recordings folder - the user's choice or `<app data>/recordings`
+fn resolve_recordings_dir(
+    app_handle: &AppHandle,
+    output_folder: Option<String>,
+) -> Result<PathBuf> {
+    if let Some(folder) = output_folder {
+        // Use user-specified folder
+        let path = PathBuf::from(folder);
+        // Validate the path exists and is a directory
+        if !path.exists() {
+            return Err(format!("Output folder does not exist: {:?}", path));
+        }
+        if !path.is_dir() {
+            return Err(format!("Output path is not a directory: {:?}", path));
+        }
+        Ok(path)
+    } else {
+        // Use default app data directory
+        let app_data_dir = app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+        let default_dir = app_data_dir.join("recordings");
+        std::fs::create_dir_all(&default_dir)
+            .map_err(|e| format!("Failed to create recordings dir: {}", e))?;
+        Ok(default_dir)
+    }
+}
+
+/// Repair recordings in `dir` left behind by a crash and remember them for the frontend
+fn recover_into_state(app_handle: &AppHandle, dir: &Path) -> Result<Vec<RecoveredRecording>> {
+    let state = app_handle.state::<AppData>();
+
+    // Hold the recorder so no session can create a fresh (header-only) file mid-scan,
+    // and never touch the file of the session that is currently open
+    let recorder = state
+        .recorder
+        .lock()
+        .map_err(|e| format!("Failed to lock recorder: {}", e))?;
+    let found = recover_interrupted_recordings(dir, recorder.get_file_path().as_deref());
+    drop(recorder);
+
+    let mut recovered = state
+        .recovered
+        .lock()
+        .map_err(|e| format!("Failed to lock recovered recordings: {}", e))?;
+    recovered.extend(found.iter().cloned());
+    Ok(found)
+}
+
+/// Startup recovery of the default recordings folder, run off the main thread. The
+/// frontend asks for the results with `get_recovered_recordings` once it has loaded.
+pub fn spawn_startup_recovery(app_handle: AppHandle) {
+    std::thread::spawn(move || {
+        let found = resolve_recordings_dir(&app_handle, None)
+            .and_then(|dir| recover_into_state(&app_handle, &dir));
+        if let Err(e) = found {
+            warn!("Startup recording recovery failed: {}", e);
+        }
+    });
+}
+
+/// Finalize a recording ended by a limit or the silence timeout. Runs on its own thread:
+/// the processing thread cannot wait for the recorder, which joins it when the session
+/// closes.
+pub fn spawn_auto_finalize(app_handle: AppHandle) {
+    std::thread::spawn(move || {
+        let state = app_handle.state::<AppData>();
+        let result = state
+            .recorder
+            .lock()
+            .map_err(|e| format!("Failed to lock recorder: {}", e))
+            .and_then(|mut recorder| recorder.finalize_auto_stopped());
+        match result {
+            Ok(Some(recording)) => {
+                let event = if recording.truncated {
+                    "recording-truncated"
+                } else {
+                    "recording-silence-finalized"
+                };
+                let _ = app_handle.emit(event, recording);
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to finalize auto-stopped recording: {}", e),
+        }
+    });
+}
+
 #[tauri::command]
-pub async fn enumerate_recording_devices(state: State<'_, AppData>) -> Result<Vec<String>> {
+pub async fn enumerate_recording_devices(
+    state: State<'_, AppData>,
+) -> Result<Vec<RecordingDevice>> {
     debug!("Enumerating recording devices");
     let recorder = state
         .recorder
@@ -27,55 +128,62 @@ pub async fn enumerate_recording_devices(state: State<'_, AppData>) -> Result<Ve
     recorder.enumerate_devices()
 }
 
+/// `device_identifier` is a `RecordingDevice` id, a device name or "default"
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn init_recording_session(
     device_identifier: String,
     recording_id: String,
     output_folder: Option<String>,
     sample_rate: Option<u32>,
+    vad: Option<VadConfig>,
+    pre_roll_ms: Option<u32>,
+    canonical_output: Option<bool>,
+    format: Option<RecordingFormat>,
+    streaming: Option<StreamingConfig>,
+    failover_to_default: Option<bool>,
+    limits: Option<RecordingLimits>,
     state: State<'_, AppData>,
     app_handle: tauri::AppHandle,
+    webview: tauri::Webview,
 ) -> Result<()> {
     info!(
-        "Initializing recording session: device={}, id={}, folder={:?}, sample_rate={:?}",
-        device_identifier, recording_id, output_folder, sample_rate
+        "Initializing recording session: device={}, id={}, folder={:?}, sample_rate={:?}, vad={:?}, pre_roll_ms={:?}, canonical_output={:?}, format={:?}, streaming={}, failover_to_default={:?}, limits={:?}",
+        device_identifier, recording_id, output_folder, sample_rate, vad, pre_roll_ms, canonical_output, format, streaming.is_some(), failover_to_default, limits
     );
 
     // Determine output directory
-    let recordings_dir = if let Some(folder) = output_folder {
-        // Use user-specified folder
-        let path = PathBuf::from(folder);
-        // Validate the path exists and is a directory
-        if !path.exists() {
-            return Err(format!("Output folder does not exist: {:?}", path));
-        }
-        if !path.is_dir() {
-            return Err(format!("Output path is not a directory: {:?}", path));
-        }
-        path
-    } else {
-        // Use default app data directory
-        let app_data_dir = app_handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-
-        let default_dir = app_data_dir.join("recordings");
-        std::fs::create_dir_all(&default_dir)
-            .map_err(|e| format!("Failed to create recordings dir: {}", e))?;
-        default_dir
-    };
+    let recordings_dir = resolve_recordings_dir(&app_handle, output_folder)?;
 
     // Initialize the session with optional sample rate
     let mut recorder = state
         .recorder
         .lock()
         .map_err(|e| format!("Failed to lock recorder: {}", e))?;
-    recorder.init_session(device_identifier, recordings_dir, recording_id, sample_rate)
+    let config = SessionConfig {
+        preferred_sample_rate: sample_rate,
+        vad,
+        pre_roll_ms: pre_roll_ms.unwrap_or(0),
+        canonical_output: canonical_output.unwrap_or(false),
+        format: format.unwrap_or_default(),
+        streaming: streaming.map(|config| StreamingSession {
+            channel: config.on_chunk.channel_on(webview),
+            config,
+        }),
+        failover: failover_to_default.unwrap_or(false),
+        limits,
+        on_auto_stop: Some(Box::new({
+            let app_handle = app_handle.clone();
+            move || spawn_auto_finalize(app_handle.clone())
+        })),
+    };
+    recorder.init_session(device_identifier, recordings_dir, recording_id, config, app_handle)
 }
 
+/// `recording_id` is required when the session already recorded - it names the new file
 #[tauri::command]
 pub async fn start_recording(
+    recording_id: Option<String>,
     state: State<'_, AppData>, 
     app_handle: tauri::AppHandle
 ) -> Result<()> {
@@ -84,7 +192,7 @@ pub async fn start_recording(
             .recorder
             .lock()
             .map_err(|e| format!("Failed to lock recorder: {}", e))?;
-        recorder.start_recording()
+        recorder.start_recording(recording_id)
     };
     
     // Show recording indicator if recording started successfully
@@ -117,6 +225,25 @@ pub async fn stop_recording(
     result
 }
 
+/// Pause the recording, keeping the file and recording ID
+#[tauri::command]
+pub async fn pause_recording(state: State<'_, AppData>) -> Result<()> {
+    let mut recorder = state
+        .recorder
+        .lock()
+        .map_err(|e| format!("Failed to lock recorder: {}", e))?;
+    recorder.pause_recording()
+}
+
+#[tauri::command]
+pub async fn resume_recording(state: State<'_, AppData>) -> Result<()> {
+    let mut recorder = state
+        .recorder
+        .lock()
+        .map_err(|e| format!("Failed to lock recorder: {}", e))?;
+    recorder.resume_recording()
+}
+
 #[tauri::command]
 pub async fn cancel_recording(
     state: State<'_, AppData>,
@@ -156,6 +283,45 @@ pub async fn get_current_recording_id(state: State<'_, AppData>) -> Result<Optio
     Ok(recorder.get_current_recording_id())
 }
 
+/// Status, device, format and progress of the recorder, to resync the UI
+#[tauri::command]
+pub async fn get_recorder_status(state: State<'_, AppData>) -> Result<RecorderStatusInfo> {
+    let recorder = state
+        .recorder
+        .lock()
+        .map_err(|e| format!("Failed to lock recorder: {}", e))?;
+    recorder.get_status()
+}
+
+#[tauri::command]
+pub async fn get_input_level(state: State<'_, AppData>) -> Result<AudioLevel> {
+    let recorder = state
+        .recorder
+        .lock()
+        .map_err(|e| format!("Failed to lock recorder: {}", e))?;
+    Ok(recorder.get_input_level())
+}
+
+/// Recordings repaired after an interrupted session (found at startup, plus any in
+/// `output_folder` when a custom folder is used). The folder is scanned again, so a
+/// call made before startup recovery finished still finds everything; repaired files
+/// are not reported twice.
+#[tauri::command]
+pub async fn get_recovered_recordings(
+    output_folder: Option<String>,
+    state: State<'_, AppData>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<RecoveredRecording>> {
+    let dir = resolve_recordings_dir(&app_handle, output_folder)?;
+    recover_into_state(&app_handle, &dir)?;
+
+    let recovered = state
+        .recovered
+        .lock()
+        .map_err(|e| format!("Failed to lock recovered recordings: {}", e))?;
+    Ok(recovered.clone())
+}
+
 #[tauri::command]
 pub async fn show_recording_overlay(app_handle: tauri::AppHandle) -> Result<()> {
     if let Some(window) = app_handle.get_webview_window("recording-overlay") {
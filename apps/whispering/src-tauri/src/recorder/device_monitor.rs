@@ -0,0 +1,228 @@
+use crate::recorder::devices::device_ids;
+use crate::recorder::recorder::AudioFormat;
+use crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tracing::{debug, warn};
+
+/// How often the device list is compared for added and removed devices
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Supervisor checks without a single audio callback before the input counts as lost.
+///
+/// Counted in checks rather than wall time so a system sleep does not look like a stall.
+const STALL_CHECKS: u32 = 30;
+
+/// The device a session is capturing from
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveDevice {
+    pub id: String,
+    pub name: String,
+    pub format: AudioFormat,
+}
+
+/// Current device of a session, replaced when it fails over
+pub type SharedDevice = Arc<Mutex<Option<ActiveDevice>>>;
+
+/// Payload of the `device-changed` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceChangedEvent {
+    pub added: Vec<String>, // Device identifiers
+    pub removed: Vec<String>,
+}
+
+/// Payload of the `device-lost` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceLostEvent {
+    pub device_id: String,
+    pub device_name: String,
+    pub reason: String,                        // "disconnected" or "stalled"
+    pub fallback_device: Option<ActiveDevice>, // Where recording continues, if anywhere
+}
+
+/// Signals from the audio callbacks, read by the stream thread
+#[derive(Default)]
+pub struct StreamHealth {
+    callbacks: AtomicU64,
+    disconnected: AtomicBool,
+}
+
+impl StreamHealth {
+    /// Called from the audio callback for every buffer
+    pub fn beat(&self) {
+        self.callbacks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Called from the error callback when the device went away
+    pub fn mark_disconnected(&self) {
+        self.disconnected.store(true, Ordering::Release);
+    }
+}
+
+/// Watches one stream for disconnection or stalling
+pub struct StallDetector {
+    health: Arc<StreamHealth>,
+    last_count: u64,
+    idle_checks: u32,
+}
+
+impl StallDetector {
+    pub fn new(health: Arc<StreamHealth>) -> Self {
+        Self {
+            health,
+            last_count: 0,
+            idle_checks: 0,
+        }
+    }
+
+    /// Why the stream counts as lost, if it does. Call once per supervisor check.
+    pub fn check(&mut self) -> Option<&'static str> {
+        if self.health.disconnected.load(Ordering::Acquire) {
+            return Some("disconnected");
+        }
+
+        let count = self.health.callbacks.load(Ordering::Relaxed);
+        if count != self.last_count {
+            self.last_count = count;
+            self.idle_checks = 0;
+            return None;
+        }
+        self.idle_checks += 1;
+        (self.idle_checks >= STALL_CHECKS).then_some("stalled")
+    }
+}
+
+/// Emits `device-changed` when input devices are added or removed.
+///
+/// Devices are listed on a thread of its own, since a listing can block for seconds
+/// (e.g. an ALSA card that does not answer) and the stream thread must keep checking
+/// the input meanwhile.
+pub struct DeviceMonitor {
+    rebase: Sender<()>, // Dropped to stop the thread
+}
+
+impl DeviceMonitor {
+    /// Start from the current device list. Devices the session holds open may be
+    /// missing from it (ALSA lists only devices it can open), which is fine as long as
+    /// the monitor is started after the stream.
+    pub fn start(app_handle: AppHandle) -> Self {
+        let (rebase, requests) = unbounded();
+        // Not joined: stopping a recording should not wait for a slow listing
+        thread::spawn(move || watch_devices(app_handle, requests));
+        Self { rebase }
+    }
+
+    /// Take the current list as the baseline (e.g. after reopening the input)
+    pub fn rebase(&self) {
+        let _ = self.rebase.send(());
+    }
+}
+
+/// Body of the monitor thread: compare the device list with the last one every
+/// `POLL_INTERVAL` until the monitor is dropped
+fn watch_devices(app_handle: AppHandle, requests: Receiver<()>) {
+    let mut known = device_ids().unwrap_or_default();
+
+    loop {
+        match requests.recv_timeout(POLL_INTERVAL) {
+            Ok(()) => {
+                known = device_ids().unwrap_or_default();
+                continue;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        let current = match device_ids() {
+            Ok(ids) => ids,
+            Err(e) => {
+                warn!("Failed to list input devices: {}", e);
+                continue;
+            }
+        };
+        let added: Vec<_> = current
+            .iter()
+            .filter(|id| !known.contains(id))
+            .cloned()
+            .collect();
+        let removed: Vec<_> = known
+            .iter()
+            .filter(|id| !current.contains(id))
+            .cloned()
+            .collect();
+        known = current;
+
+        // The listing may have taken a while: the session may have ended or moved on
+        match requests.try_recv() {
+            Ok(()) => {
+                known = device_ids().unwrap_or_default();
+                continue;
+            }
+            Err(TryRecvError::Disconnected) => return,
+            Err(TryRecvError::Empty) => {}
+        }
+
+        if !added.is_empty() || !removed.is_empty() {
+            debug!("Input devices changed: +{:?} -{:?}", added, removed);
+            let _ = app_handle.emit("device-changed", DeviceChangedEvent { added, removed });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector() -> (Arc<StreamHealth>, StallDetector) {
+        let health = Arc::new(StreamHealth::default());
+        (health.clone(), StallDetector::new(health))
+    }
+
+    #[test]
+    fn reports_a_disconnect_at_once() {
+        let (health, mut detector) = detector();
+        health.beat();
+        assert_eq!(detector.check(), None);
+
+        health.beat();
+        health.mark_disconnected();
+        assert_eq!(detector.check(), Some("disconnected"));
+        assert_eq!(detector.check(), Some("disconnected"));
+    }
+
+    #[test]
+    fn stalls_after_enough_silent_checks() {
+        let (_health, mut detector) = detector();
+        for _ in 1..STALL_CHECKS {
+            assert_eq!(detector.check(), None);
+        }
+        assert_eq!(detector.check(), Some("stalled"));
+        assert_eq!(detector.check(), Some("stalled"));
+    }
+
+    #[test]
+    fn a_heartbeat_restarts_the_count() {
+        let (health, mut detector) = detector();
+        for _ in 1..STALL_CHECKS {
+            assert_eq!(detector.check(), None);
+        }
+        health.beat();
+        assert_eq!(detector.check(), None);
+
+        for _ in 1..STALL_CHECKS {
+            assert_eq!(detector.check(), None);
+        }
+        assert_eq!(detector.check(), Some("stalled"));
+
+        // A stream that comes back is no longer stalled
+        health.beat();
+        assert_eq!(detector.check(), None);
+    }
+}
@@ -0,0 +1,439 @@
+use cpal::traits::{DeviceTrait, HostTrait};
+use cpal::{Device, Host, SampleFormat};
+use serde::Serialize;
+use tracing::warn;
+
+/// Rates reported as supported when a device accepts a continuous range
+const COMMON_SAMPLE_RATES: [u32; 9] =
+    [8000, 11025, 16000, 22050, 32000, 44100, 48000, 88200, 96000];
+
+/// An input device - returned to frontend
+///
+/// The identifier is the host API and the platform's own device id where it has one: the
+/// CoreAudio device UID or the WASAPI endpoint id. ALSA devices are identified by their PCM
+/// name, which names the card (e.g. "sysdefault:CARD=Yeti") and survives reboots. Other
+/// hosts fall back to the device name, with `#2`, `#3`... appended for devices sharing a
+/// name (e.g. two identical USB mics, in the order the host lists them) - such identifiers
+/// can point at the other mic when devices are replugged in a different order.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingDevice {
+    pub id: String, // e.g. "ALSA:sysdefault:CARD=Yeti", "CoreAudio:AppleUSBAudioEngine:..."
+    pub name: String,
+    pub host_api: String, // e.g. "ALSA", "CoreAudio", "WASAPI"
+    pub is_default: bool,
+    pub sample_rates: Vec<u32>,
+    pub channel_counts: Vec<u16>,
+    pub sample_formats: Vec<String>, // e.g. "f32", "i16"
+}
+
+/// Lowercase name of a cpal sample format ("f32", "i16", ...)
+pub fn sample_format_name(format: SampleFormat) -> String {
+    format!("{:?}", format).to_lowercase()
+}
+
+/// List the input devices of every available host API
+pub fn enumerate_devices() -> Result<Vec<RecordingDevice>, String> {
+    let default_host = cpal::default_host().id();
+    let mut devices = Vec::new();
+
+    for host_id in cpal::available_hosts() {
+        let host = match cpal::host_from_id(host_id) {
+            Ok(host) => host,
+            Err(e) => {
+                warn!("Skipping host {}: {}", host_id.name(), e);
+                continue;
+            }
+        };
+        let host_devices = match host_devices(&host) {
+            Ok(host_devices) => host_devices,
+            Err(e) => {
+                warn!("Skipping host {}: {}", host_id.name(), e);
+                continue;
+            }
+        };
+        let default_name = if host_id == default_host {
+            host.default_input_device().and_then(|d| d.name().ok())
+        } else {
+            None
+        };
+
+        let mut default_seen = false;
+        for device in host_devices {
+            let is_default = !default_seen && default_name.as_deref() == Some(device.name.as_str());
+            default_seen |= is_default;
+            devices.push(describe(device, host_id.name(), is_default));
+        }
+    }
+
+    Ok(devices)
+}
+
+/// Find an input device by identifier, by name-based identifier or plain name (settings
+/// saved by older versions) or the host's default for "default". Returns the device's
+/// identifier too.
+pub fn find_device(identifier: &str) -> Result<(String, Device), String> {
+    if identifier.eq_ignore_ascii_case("default") {
+        return default_device();
+    }
+
+    // Identifier of a device on any available host
+    for host_id in cpal::available_hosts() {
+        if !identifier.starts_with(&format!("{}:", host_id.name())) {
+            continue;
+        }
+        let Ok(host) = cpal::host_from_id(host_id) else {
+            continue;
+        };
+        if let Some(device) = host_devices(&host)?
+            .into_iter()
+            .find(|d| d.id == identifier || d.name_id == identifier)
+        {
+            return Ok((device.id, device.device));
+        }
+    }
+
+    // Plain name, first match on the default host
+    let host = cpal::default_host();
+    host_devices(&host)?
+        .into_iter()
+        .find(|d| d.name == identifier)
+        .map(|d| (d.id, d.device))
+        .ok_or_else(|| format!("Device '{}' not found", identifier))
+}
+
+/// The default host's default input device and its identifier
+pub fn default_device() -> Result<(String, Device), String> {
+    let host = cpal::default_host();
+    let device = host
+        .default_input_device()
+        .ok_or_else(|| "No default input device available".to_string())?;
+    let name = device.name().unwrap_or_default();
+    let id = host_devices(&host)
+        .ok()
+        .and_then(|devices| devices.into_iter().find(|d| d.name == name))
+        .map(|d| d.id)
+        .unwrap_or_else(|| format!("{}:{}", host.id().name(), name));
+    Ok((id, device))
+}
+
+/// Identifiers of the input devices of every available host, cheaper than
+/// `enumerate_devices`. Fails when an available host cannot list its devices, so a broken
+/// listing is not mistaken for the devices being removed.
+pub fn device_ids() -> Result<Vec<String>, String> {
+    let mut ids = Vec::new();
+    for host_id in cpal::available_hosts() {
+        let Ok(host) = cpal::host_from_id(host_id) else {
+            continue;
+        };
+        let devices = host_devices(&host).map_err(|e| format!("{} ({})", e, host_id.name()))?;
+        ids.extend(devices.into_iter().map(|d| d.id));
+    }
+    Ok(ids)
+}
+
+/// An input device of a host with its identifiers
+struct HostDevice {
+    id: String,
+    name_id: String, // Host API and name, the identifier used by older versions
+    name: String,
+    device: Device,
+}
+
+/// Identifiers, name and device of each input device of `host`, in host order
+fn host_devices(host: &Host) -> Result<Vec<HostDevice>, String> {
+    let host_name = host.id().name();
+    let native = native_ids(host);
+    let mut seen: Vec<String> = Vec::new();
+    let mut devices = Vec::new();
+
+    for device in host
+        .input_devices()
+        .map_err(|e| format!("Failed to get input devices: {}", e))?
+    {
+        let Ok(name) = device.name() else {
+            continue;
+        };
+        let occurrence = seen.iter().filter(|seen| **seen == name).count() + 1;
+        seen.push(name.clone());
+
+        let name_id = if occurrence == 1 {
+            format!("{}:{}", host_name, name)
+        } else {
+            format!("{}:{}#{}", host_name, name, occurrence)
+        };
+        // Both lists follow the platform's order, so the n-th device of a name is the same
+        let id = native
+            .iter()
+            .filter(|(native_name, _)| *native_name == name)
+            .nth(occurrence - 1)
+            .map(|(_, native_id)| format!("{}:{}", host_name, native_id))
+            .unwrap_or_else(|| name_id.clone());
+        devices.push(HostDevice {
+            id,
+            name_id,
+            name,
+            device,
+        });
+    }
+
+    Ok(devices)
+}
+
+/// Name and platform id of each input device of `host`, in host order - empty for hosts
+/// without one
+fn native_ids(host: &Host) -> Vec<(String, String)> {
+    #[cfg(target_os = "macos")]
+    if host.id() == cpal::HostId::CoreAudio {
+        return coreaudio::input_ids();
+    }
+    #[cfg(target_os = "windows")]
+    if host.id() == cpal::HostId::Wasapi {
+        return wasapi::input_ids();
+    }
+    let _ = host;
+    Vec::new()
+}
+
+/// Supported rates, channel counts and formats of `device` (empty when it cannot be queried)
+fn describe(device: HostDevice, host_api: &str, is_default: bool) -> RecordingDevice {
+    let mut sample_rates = Vec::new();
+    let mut channel_counts = Vec::new();
+    let mut sample_formats = Vec::new();
+
+    if let Ok(configs) = device.device.supported_input_configs() {
+        for config in configs {
+            let (min, max) = (config.min_sample_rate().0, config.max_sample_rate().0);
+            sample_rates.extend([min, max]);
+            sample_rates.extend(
+                COMMON_SAMPLE_RATES
+                    .iter()
+                    .filter(|&&rate| rate > min && rate < max),
+            );
+            channel_counts.push(config.channels());
+            let format = sample_format_name(config.sample_format());
+            if !sample_formats.contains(&format) {
+                sample_formats.push(format);
+            }
+        }
+    }
+    sample_rates.sort_unstable();
+    sample_rates.dedup();
+    channel_counts.sort_unstable();
+    channel_counts.dedup();
+
+    RecordingDevice {
+        id: device.id,
+        name: device.name,
+        host_api: host_api.to_string(),
+        is_default,
+        sample_rates,
+        channel_counts,
+        sample_formats,
+    }
+}
+
+/// CoreAudio device UIDs, which stay the same across reboots and replugging
+#[cfg(target_os = "macos")]
+mod coreaudio {
+    use core_foundation::base::TCFType;
+    use core_foundation::string::CFString;
+    use coreaudio_sys::{
+        kAudioDevicePropertyDeviceNameCFString, kAudioDevicePropertyDeviceUID,
+        kAudioDevicePropertyScopeOutput, kAudioDevicePropertyStreamConfiguration,
+        kAudioHardwareNoError, kAudioHardwarePropertyDevices, kAudioObjectPropertyElementMaster,
+        kAudioObjectPropertyScopeGlobal, kAudioObjectPropertyScopeInput, kAudioObjectSystemObject,
+        AudioBuffer, AudioBufferList, AudioDeviceID, AudioObjectGetPropertyData,
+        AudioObjectGetPropertyDataSize, AudioObjectID, AudioObjectPropertyAddress,
+        AudioObjectPropertyScope, AudioObjectPropertySelector, CFStringRef,
+    };
+    use std::mem;
+    use std::ptr::null;
+    use std::slice;
+
+    /// Name and UID of each device with input channels, in CoreAudio order (as cpal lists them)
+    pub fn input_ids() -> Vec<(String, String)> {
+        let devices = unsafe {
+            property_data(
+                kAudioObjectSystemObject,
+                kAudioHardwarePropertyDevices,
+                kAudioObjectPropertyScopeGlobal,
+            )
+        };
+        let Some((data, size)) = devices else {
+            return Vec::new();
+        };
+        let count = size / mem::size_of::<AudioDeviceID>();
+        let devices =
+            unsafe { slice::from_raw_parts(data.as_ptr() as *const AudioDeviceID, count) };
+
+        devices
+            .iter()
+            .filter(|&&device| unsafe { input_channels(device) } > 0)
+            .filter_map(|&device| unsafe {
+                // cpal reads the name from the output scope
+                let name = string_property(
+                    device,
+                    kAudioDevicePropertyDeviceNameCFString,
+                    kAudioDevicePropertyScopeOutput,
+                )?;
+                let uid = string_property(
+                    device,
+                    kAudioDevicePropertyDeviceUID,
+                    kAudioObjectPropertyScopeGlobal,
+                )?;
+                Some((name, uid))
+            })
+            .collect()
+    }
+
+    /// Raw value of a property and its size in bytes (8-byte aligned for buffer lists)
+    unsafe fn property_data(
+        object: AudioObjectID,
+        selector: AudioObjectPropertySelector,
+        scope: AudioObjectPropertyScope,
+    ) -> Option<(Vec<u64>, usize)> {
+        let address = AudioObjectPropertyAddress {
+            mSelector: selector,
+            mScope: scope,
+            mElement: kAudioObjectPropertyElementMaster,
+        };
+        let mut size = 0u32;
+        let status = AudioObjectGetPropertyDataSize(object, &address, 0, null(), &mut size);
+        if status != kAudioHardwareNoError as i32 {
+            return None;
+        }
+
+        let mut data = vec![0u64; (size as usize).div_ceil(mem::size_of::<u64>())];
+        let status = AudioObjectGetPropertyData(
+            object,
+            &address,
+            0,
+            null(),
+            &mut size,
+            data.as_mut_ptr() as *mut _,
+        );
+        if status != kAudioHardwareNoError as i32 {
+            return None;
+        }
+        Some((data, size as usize))
+    }
+
+    /// Total input channels of `device`, as cpal counts them to list input devices
+    unsafe fn input_channels(device: AudioDeviceID) -> u32 {
+        let Some((data, size)) = property_data(
+            device,
+            kAudioDevicePropertyStreamConfiguration,
+            kAudioObjectPropertyScopeInput,
+        ) else {
+            return 0;
+        };
+        if size < mem::size_of::<AudioBufferList>() {
+            return 0;
+        }
+
+        let list = data.as_ptr() as *const AudioBufferList;
+        let buffers: &[AudioBuffer] =
+            slice::from_raw_parts((*list).mBuffers.as_ptr(), (*list).mNumberBuffers as usize);
+        buffers.iter().map(|buffer| buffer.mNumberChannels).sum()
+    }
+
+    /// A CFString property of `device`, released after reading
+    unsafe fn string_property(
+        device: AudioDeviceID,
+        selector: AudioObjectPropertySelector,
+        scope: AudioObjectPropertyScope,
+    ) -> Option<String> {
+        let address = AudioObjectPropertyAddress {
+            mSelector: selector,
+            mScope: scope,
+            mElement: kAudioObjectPropertyElementMaster,
+        };
+        let mut value: CFStringRef = null();
+        let mut size = mem::size_of::<CFStringRef>() as u32;
+        let status = AudioObjectGetPropertyData(
+            device,
+            &address,
+            0,
+            null(),
+            &mut size,
+            &mut value as *mut _ as *mut _,
+        );
+        if status != kAudioHardwareNoError as i32 || value.is_null() {
+            return None;
+        }
+        let value = CFString::wrap_under_create_rule(value as _);
+        Some(value.to_string())
+    }
+}
+
+/// WASAPI endpoint ids, which stay the same across reboots and replugging
+#[cfg(target_os = "windows")]
+mod wasapi {
+    use tracing::warn;
+    use windows::core::{Result, PWSTR};
+    use windows::Win32::Devices::Properties::DEVPKEY_Device_FriendlyName;
+    use windows::Win32::Media::Audio::{
+        eCapture, IMMDevice, IMMDeviceEnumerator, MMDeviceEnumerator, DEVICE_STATE_ACTIVE,
+    };
+    use windows::Win32::System::Com::StructuredStorage::PropVariantClear;
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoTaskMemFree, CoUninitialize, CLSCTX_ALL,
+        COINIT_MULTITHREADED, STGM_READ,
+    };
+    use windows::Win32::System::Variant::VT_LPWSTR;
+
+    /// Friendly name and endpoint id of each active capture endpoint, in WASAPI order (as
+    /// cpal lists them)
+    pub fn input_ids() -> Vec<(String, String)> {
+        unsafe {
+            // Fails harmlessly when the thread already joined another apartment
+            let initialized = CoInitializeEx(None, COINIT_MULTITHREADED).is_ok();
+            let ids = capture_endpoints().unwrap_or_else(|e| {
+                warn!("Failed to list WASAPI endpoint ids: {}", e);
+                Vec::new()
+            });
+            if initialized {
+                CoUninitialize();
+            }
+            ids
+        }
+    }
+
+    unsafe fn capture_endpoints() -> Result<Vec<(String, String)>> {
+        let enumerator: IMMDeviceEnumerator =
+            CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+        let collection = enumerator.EnumAudioEndpoints(eCapture, DEVICE_STATE_ACTIVE)?;
+
+        let mut endpoints = Vec::new();
+        for index in 0..collection.GetCount()? {
+            let device = collection.Item(index)?;
+            if let Some(name) = friendly_name(&device)? {
+                endpoints.push((name, endpoint_id(&device)?));
+            }
+        }
+        Ok(endpoints)
+    }
+
+    unsafe fn endpoint_id(device: &IMMDevice) -> Result<String> {
+        let id: PWSTR = device.GetId()?;
+        let value = id.to_string().unwrap_or_default();
+        CoTaskMemFree(Some(id.0 as *const _));
+        Ok(value)
+    }
+
+    /// The endpoint's friendly name, which cpal uses as the device name
+    unsafe fn friendly_name(device: &IMMDevice) -> Result<Option<String>> {
+        let store = device.OpenPropertyStore(STGM_READ)?;
+        let mut value = store.GetValue(&DEVPKEY_Device_FriendlyName as *const _ as *const _)?;
+        let variant = &value.as_raw().Anonymous.Anonymous;
+        let name = if variant.vt == VT_LPWSTR.0 {
+            let text = *(&variant.Anonymous as *const _ as *const *mut u16);
+            PWSTR(text).to_string().ok()
+        } else {
+            None
+        };
+        PropVariantClear(&mut value).ok();
+        Ok(name)
+    }
+}
@@ -0,0 +1,131 @@
+use crate::recorder::flac_writer::FlacWriter;
+use crate::recorder::opus_writer::{OpusWriter, OPUS_SAMPLE_RATES};
+use crate::recorder::wav_writer::{WavSampleFormat, WavWriter};
+use serde::Deserialize;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+
+/// Container/codec of the recording file - selected by the frontend per session
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RecordingFormat {
+    /// 32-bit IEEE float WAV (lossless, largest)
+    #[default]
+    WavFloat,
+    /// 16-bit PCM WAV (lossless for 16-bit sources, half the size)
+    WavPcm16,
+    /// 16-bit FLAC (lossless, typically 50-60% of PCM16)
+    Flac,
+    /// Ogg/Opus tuned for speech (lossy, a small fraction of the WAV size)
+    Opus,
+}
+
+impl RecordingFormat {
+    /// File extension without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            RecordingFormat::WavFloat | RecordingFormat::WavPcm16 => "wav",
+            RecordingFormat::Flac => "flac",
+            RecordingFormat::Opus => "opus",
+        }
+    }
+
+    /// MIME type to use when the frontend reads or uploads the file
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            RecordingFormat::WavFloat | RecordingFormat::WavPcm16 => "audio/wav",
+            RecordingFormat::Flac => "audio/flac",
+            RecordingFormat::Opus => "audio/ogg; codecs=opus",
+        }
+    }
+
+    /// Sample format name reported in `AudioRecording::output_format`
+    pub fn sample_format(&self) -> &'static str {
+        match self {
+            RecordingFormat::WavFloat => "f32",
+            RecordingFormat::WavPcm16 | RecordingFormat::Flac => "i16",
+            RecordingFormat::Opus => "opus",
+        }
+    }
+
+    /// Closest sample rate / channel count this format can store
+    pub fn supported_output(&self, sample_rate: u32, channels: u16) -> (u32, u16) {
+        match self {
+            // Opus only accepts a handful of rates and at most stereo (without a mapping table)
+            RecordingFormat::Opus => {
+                let sample_rate = if OPUS_SAMPLE_RATES.contains(&sample_rate) {
+                    sample_rate
+                } else {
+                    48000
+                };
+                (sample_rate, if channels > 2 { 1 } else { channels })
+            }
+            // FLAC frame headers carry at most 8 channels
+            RecordingFormat::Flac => (sample_rate, if channels > 8 { 1 } else { channels }),
+            RecordingFormat::WavFloat | RecordingFormat::WavPcm16 => (sample_rate, channels),
+        }
+    }
+}
+
+/// Progressive writer for one recording file.
+///
+/// Samples always arrive as interleaved f32 in the session's output format; each
+/// implementation converts them to whatever it stores.
+pub trait AudioEncoder: Send {
+    /// Append interleaved samples
+    fn write_samples(&mut self, samples: &[f32]) -> io::Result<()>;
+
+    /// Flush everything and make the file complete (safe to call more than once)
+    fn finalize(&mut self) -> io::Result<()>;
+
+    /// Keep only frames `start_frame..end_frame`. Only uncompressed formats can do this in place.
+    fn trim(&mut self, _start_frame: u64, _end_frame: u64) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Trimming is not supported for this format",
+        ))
+    }
+
+    /// Get audio metadata (sample rate, channels, duration in seconds)
+    fn get_metadata(&self) -> (u32, u16, f32);
+
+    /// Size of the file so far, including data still in the write buffer
+    fn bytes_written(&self) -> u64;
+}
+
+/// Create the writer for `format`. The rate/channels must come from `supported_output`.
+pub fn create_encoder(
+    format: RecordingFormat,
+    file_path: PathBuf,
+    sample_rate: u32,
+    channels: u16,
+) -> io::Result<Box<dyn AudioEncoder>> {
+    Ok(match format {
+        RecordingFormat::WavFloat => Box::new(WavWriter::new(
+            file_path,
+            sample_rate,
+            channels,
+            WavSampleFormat::Float32,
+        )?),
+        RecordingFormat::WavPcm16 => Box::new(WavWriter::new(
+            file_path,
+            sample_rate,
+            channels,
+            WavSampleFormat::Pcm16,
+        )?),
+        RecordingFormat::Flac => Box::new(FlacWriter::new(file_path, sample_rate, channels)?),
+        RecordingFormat::Opus => Box::new(OpusWriter::new(file_path, sample_rate, channels)?),
+    })
+}
+
+/// Bytes on disk plus bytes buffered in `writer`
+pub fn buffered_len(writer: &BufWriter<File>) -> u64 {
+    let on_disk = writer.get_ref().metadata().map(|m| m.len()).unwrap_or(0);
+    on_disk + writer.buffer().len() as u64
+}
+
+/// Convert a float sample to 16-bit PCM, clipping out-of-range input
+pub fn f32_to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16
+}
@@ -0,0 +1,623 @@
+use crate::recorder::encoder::{buffered_len, f32_to_i16, AudioEncoder};
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use tracing::info;
+
+/// Samples per channel in every frame except the last
+const BLOCK_SIZE: usize = 4096;
+
+/// Samples are stored as 16-bit integers
+const BITS_PER_SAMPLE: u32 = 16;
+
+/// Highest fixed-predictor order defined by the format
+const MAX_FIXED_ORDER: usize = 4;
+
+/// Finest residual partitioning that is searched (2^8 partitions)
+const MAX_PARTITION_ORDER: u32 = 8;
+
+/// Largest Rice parameter with 4-bit coding (15 is the escape code)
+const MAX_RICE_PARAMETER: usize = 14;
+
+/// Offset of the STREAMINFO body ("fLaC" + metadata block header)
+const STREAMINFO_POS: u64 = 8;
+
+/// Progressive FLAC writer (16-bit, fixed block size, fixed predictors + Rice coding).
+///
+/// Every frame is self-contained, so a file cut short by a crash is still decodable.
+/// STREAMINFO is rewritten with the final sample count and frame sizes on finalize.
+pub struct FlacWriter {
+    writer: BufWriter<File>,
+    sample_rate: u32,
+    channels: u16,
+    pending: Vec<Vec<i32>>, // Samples not yet encoded, per channel
+    frame_number: u64,
+    samples_written: u64, // Encoded samples per channel
+    min_frame_size: u32,
+    max_frame_size: u32,
+    file_path: PathBuf,
+}
+
+impl FlacWriter {
+    /// Create a new FLAC file and write the stream header
+    pub fn new(file_path: PathBuf, sample_rate: u32, channels: u16) -> io::Result<Self> {
+        if !(1..=8).contains(&channels) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("FLAC supports 1-8 channels, got {}", channels),
+            ));
+        }
+
+        let file = File::create(&file_path)?;
+        let mut writer = Self {
+            writer: BufWriter::new(file),
+            sample_rate,
+            channels,
+            pending: vec![Vec::with_capacity(BLOCK_SIZE); channels as usize],
+            frame_number: 0,
+            samples_written: 0,
+            min_frame_size: u32::MAX,
+            max_frame_size: 0,
+            file_path,
+        };
+
+        // Marker + last-metadata-block flag, type 0 (STREAMINFO), 34-byte body
+        writer.writer.write_all(b"fLaC")?;
+        writer.writer.write_all(&[0x80, 0x00, 0x00, 34])?;
+        let streaminfo = writer.streaminfo();
+        writer.writer.write_all(&streaminfo)?;
+        writer.writer.flush()?;
+
+        info!(
+            "Created FLAC file at {:?}: {}Hz, {} channels, {}-bit",
+            writer.file_path, sample_rate, channels, BITS_PER_SAMPLE
+        );
+
+        Ok(writer)
+    }
+
+    /// STREAMINFO body for what has been written so far (MD5 left unset)
+    fn streaminfo(&self) -> Vec<u8> {
+        let (min_frame, max_frame) = if self.max_frame_size == 0 {
+            (0, 0) // Unknown
+        } else {
+            (self.min_frame_size, self.max_frame_size)
+        };
+
+        let mut bits = BitWriter::new();
+        bits.write(BLOCK_SIZE as u64, 16); // Minimum block size
+        bits.write(BLOCK_SIZE as u64, 16); // Maximum block size
+        bits.write(min_frame as u64, 24);
+        bits.write(max_frame as u64, 24);
+        bits.write(self.sample_rate as u64, 20);
+        bits.write(self.channels as u64 - 1, 3);
+        bits.write(BITS_PER_SAMPLE as u64 - 1, 5);
+        bits.write(self.samples_written, 36);
+        for _ in 0..4 {
+            bits.write(0, 32); // MD5 signature
+        }
+        bits.into_bytes()
+    }
+
+    /// Encode the first `block_size` pending samples of every channel as one frame
+    fn encode_frame(&mut self, block_size: usize) -> io::Result<()> {
+        let mut bits = BitWriter::new();
+
+        // Frame header: sync code with fixed blocking strategy
+        bits.write(0b1111_1111_1111_1000, 16);
+        bits.write(0b0111, 4); // Block size stored as 16 bits at the end of the header
+        bits.write(0b0000, 4); // Sample rate taken from STREAMINFO
+        bits.write(self.channels as u64 - 1, 4); // Independent channels
+        bits.write(0b100, 3); // 16 bits per sample
+        bits.write(0, 1);
+        write_utf8_number(&mut bits, self.frame_number);
+        bits.write(block_size as u64 - 1, 16);
+        let header_crc = crc8(bits.bytes());
+        bits.write(header_crc as u64, 8);
+
+        for channel in &mut self.pending {
+            let samples: Vec<i32> = channel.drain(..block_size).collect();
+            encode_subframe(&mut bits, &samples);
+        }
+
+        // Frame footer
+        bits.align();
+        let frame_crc = crc16(bits.bytes());
+        bits.write(frame_crc as u64, 16);
+        let frame = bits.into_bytes();
+
+        self.writer.write_all(&frame)?;
+        self.writer.flush()?;
+
+        self.min_frame_size = self.min_frame_size.min(frame.len() as u32);
+        self.max_frame_size = self.max_frame_size.max(frame.len() as u32);
+        self.frame_number += 1;
+        self.samples_written += block_size as u64;
+
+        Ok(())
+    }
+
+    /// Get the current duration in seconds (including samples not yet encoded)
+    pub fn get_duration_seconds(&self) -> f32 {
+        let samples = self.samples_written + self.pending[0].len() as u64;
+        samples as f32 / self.sample_rate as f32
+    }
+}
+
+impl AudioEncoder for FlacWriter {
+    fn write_samples(&mut self, samples: &[f32]) -> io::Result<()> {
+        for frame in samples.chunks(self.channels as usize) {
+            for (channel, &sample) in frame.iter().enumerate() {
+                self.pending[channel].push(f32_to_i16(sample) as i32);
+            }
+        }
+
+        while self.pending[0].len() >= BLOCK_SIZE {
+            self.encode_frame(BLOCK_SIZE)?;
+        }
+
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> io::Result<()> {
+        // The last frame may be shorter than the block size
+        let remaining = self.pending[0].len();
+        if remaining > 0 {
+            self.encode_frame(remaining)?;
+        }
+
+        let streaminfo = self.streaminfo();
+        let end = self.writer.stream_position()?;
+        self.writer.seek(SeekFrom::Start(STREAMINFO_POS))?;
+        self.writer.write_all(&streaminfo)?;
+        self.writer.seek(SeekFrom::Start(end))?;
+        self.writer.flush()?;
+
+        info!(
+            "Finalized FLAC file {:?}: {} frames, {:.2} seconds",
+            self.file_path,
+            self.frame_number,
+            self.get_duration_seconds()
+        );
+
+        Ok(())
+    }
+
+    fn get_metadata(&self) -> (u32, u16, f32) {
+        (self.sample_rate, self.channels, self.get_duration_seconds())
+    }
+
+    fn bytes_written(&self) -> u64 {
+        buffered_len(&self.writer)
+    }
+}
+
+impl Drop for FlacWriter {
+    fn drop(&mut self) {
+        if let Err(e) = self.finalize() {
+            tracing::error!("Failed to finalize FLAC file on drop: {}", e);
+        }
+    }
+}
+
+/// Write one channel of a frame using whichever subframe type is smallest
+fn encode_subframe(bits: &mut BitWriter, samples: &[i32]) {
+    // Subframe header: zero pad bit, 6-bit type, no wasted bits
+    if samples.iter().all(|&s| s == samples[0]) {
+        bits.write(0b0000_0000, 8); // CONSTANT
+        bits.write_signed(samples[0], BITS_PER_SAMPLE);
+        return;
+    }
+
+    let verbatim_bits = samples.len() as u64 * BITS_PER_SAMPLE as u64;
+    let best = (0..=MAX_FIXED_ORDER.min(samples.len() - 1))
+        .map(|order| {
+            let residual = fixed_residual(samples, order);
+            let plan = plan_residual(&residual, samples.len(), order);
+            let cost = order as u64 * BITS_PER_SAMPLE as u64 + 6 + plan.bits;
+            (order, residual, plan, cost)
+        })
+        .min_by_key(|(_, _, _, cost)| *cost);
+
+    match best {
+        Some((order, residual, plan, cost)) if cost < verbatim_bits => {
+            bits.write(0b0001_0000 | ((order as u64) << 1), 8); // FIXED, order in the low bits
+            for &warmup in &samples[..order] {
+                bits.write_signed(warmup, BITS_PER_SAMPLE);
+            }
+            write_residual(bits, &residual, samples.len(), order, &plan);
+        }
+        _ => {
+            bits.write(0b0000_0010, 8); // VERBATIM
+            for &sample in samples {
+                bits.write_signed(sample, BITS_PER_SAMPLE);
+            }
+        }
+    }
+}
+
+/// Prediction error of the fixed polynomial predictor of the given order
+fn fixed_residual(samples: &[i32], order: usize) -> Vec<i32> {
+    (order..samples.len())
+        .map(|i| {
+            let s = |back: usize| samples[i - back];
+            match order {
+                0 => s(0),
+                1 => s(0) - s(1),
+                2 => s(0) - 2 * s(1) + s(2),
+                3 => s(0) - 3 * s(1) + 3 * s(2) - s(3),
+                _ => s(0) - 4 * s(1) + 6 * s(2) - 4 * s(3) + s(4),
+            }
+        })
+        .collect()
+}
+
+/// Chosen partitioning of a residual and the Rice parameter of each partition
+struct ResidualPlan {
+    partition_order: u32,
+    parameters: Vec<u32>,
+    bits: u64,
+}
+
+/// Per-partition totals from which the cost of every Rice parameter follows
+#[derive(Clone, Copy)]
+struct PartitionStats {
+    count: u64,
+    sums: [u64; MAX_RICE_PARAMETER + 1], // Sum of (folded >> k) for each k
+}
+
+impl PartitionStats {
+    fn best_parameter(&self) -> (u32, u64) {
+        (0..=MAX_RICE_PARAMETER)
+            .map(|k| (k as u32, self.count * (k as u64 + 1) + self.sums[k]))
+            .min_by_key(|(_, bits)| *bits)
+            .unwrap_or((0, 0))
+    }
+
+    fn merge(&self, other: &Self) -> Self {
+        let mut sums = self.sums;
+        for (sum, other_sum) in sums.iter_mut().zip(other.sums.iter()) {
+            *sum += other_sum;
+        }
+        Self {
+            count: self.count + other.count,
+            sums,
+        }
+    }
+}
+
+/// Find the partition order and Rice parameters that code the residual in the fewest bits
+fn plan_residual(residual: &[i32], block_size: usize, order: usize) -> ResidualPlan {
+    // Partitions must divide the block evenly and the first one must outlast the warm-up
+    let max_order = (0..=MAX_PARTITION_ORDER)
+        .rev()
+        .find(|&o| block_size.is_multiple_of(1 << o) && (block_size >> o) > order)
+        .unwrap_or(0);
+
+    // Gather stats at the finest level, then merge neighbours for each coarser level
+    let partition_size = block_size >> max_order;
+    let mut level: Vec<PartitionStats> = (0..1usize << max_order)
+        .map(|p| {
+            let (start, end) = partition_range(p, partition_size, order);
+            let mut stats = PartitionStats {
+                count: (end - start) as u64,
+                sums: [0; MAX_RICE_PARAMETER + 1],
+            };
+            for &value in &residual[start..end] {
+                let folded = fold(value) as u64;
+                for (k, sum) in stats.sums.iter_mut().enumerate() {
+                    *sum += folded >> k;
+                }
+            }
+            stats
+        })
+        .collect();
+
+    let mut best: Option<ResidualPlan> = None;
+    for partition_order in (0..=max_order).rev() {
+        let (parameters, bits): (Vec<u32>, Vec<u64>) =
+            level.iter().map(|stats| stats.best_parameter()).unzip();
+        let bits = bits.iter().sum::<u64>() + 4 * parameters.len() as u64;
+
+        if best.as_ref().is_none_or(|plan| bits < plan.bits) {
+            best = Some(ResidualPlan {
+                partition_order,
+                parameters,
+                bits,
+            });
+        }
+
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => a.merge(b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+
+    best.unwrap_or(ResidualPlan {
+        partition_order: 0,
+        parameters: vec![0],
+        bits: 0,
+    })
+}
+
+/// Residual index range of partition `p` (the first partition skips the warm-up samples)
+fn partition_range(p: usize, partition_size: usize, order: usize) -> (usize, usize) {
+    let start = (p * partition_size).max(order) - order;
+    let end = (p + 1) * partition_size - order;
+    (start, end)
+}
+
+fn write_residual(
+    bits: &mut BitWriter,
+    residual: &[i32],
+    block_size: usize,
+    order: usize,
+    plan: &ResidualPlan,
+) {
+    bits.write(0b00, 2); // Rice coding with 4-bit parameters
+    bits.write(plan.partition_order as u64, 4);
+
+    let partition_size = block_size >> plan.partition_order;
+    for (p, &parameter) in plan.parameters.iter().enumerate() {
+        bits.write(parameter as u64, 4);
+        let (start, end) = partition_range(p, partition_size, order);
+        for &value in &residual[start..end] {
+            bits.write_rice(fold(value), parameter);
+        }
+    }
+}
+
+/// Map signed residuals onto unsigned values (0, -1, 1, -2, ... -> 0, 1, 2, 3, ...)
+fn fold(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+/// Frame numbers use the extended UTF-8 style variable-length coding
+fn write_utf8_number(bits: &mut BitWriter, value: u64) {
+    if value < 0x80 {
+        bits.write(value, 8);
+        return;
+    }
+
+    let mut len = 2;
+    while len < 7 && value >= 1 << (5 * len + 1) {
+        len += 1;
+    }
+
+    let prefix = (0xFF00u64 >> len) & 0xFF;
+    bits.write(prefix | (value >> (6 * (len - 1))), 8);
+    for i in (0..len - 1).rev() {
+        bits.write(0x80 | ((value >> (6 * i)) & 0x3F), 8);
+    }
+}
+
+/// CRC-8 of the frame header (polynomial x^8 + x^2 + x + 1)
+fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for &byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// CRC-16 of the whole frame (polynomial x^16 + x^15 + x^2 + 1)
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// MSB-first bit packer
+struct BitWriter {
+    bytes: Vec<u8>,
+    accumulator: u64,
+    pending_bits: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            accumulator: 0,
+            pending_bits: 0,
+        }
+    }
+
+    /// Write the low `count` bits of `value` (count <= 32)
+    fn write(&mut self, value: u64, count: u32) {
+        let mask = (1u64 << count) - 1;
+        self.accumulator = (self.accumulator << count) | (value & mask);
+        self.pending_bits += count;
+
+        while self.pending_bits >= 8 {
+            self.pending_bits -= 8;
+            self.bytes
+                .push((self.accumulator >> self.pending_bits) as u8);
+        }
+        self.accumulator &= (1u64 << self.pending_bits) - 1;
+    }
+
+    /// Write a two's complement value in `count` bits
+    fn write_signed(&mut self, value: i32, count: u32) {
+        self.write(value as u32 as u64, count);
+    }
+
+    /// Rice code: quotient in unary (zeros terminated by a one), then `k` low bits
+    fn write_rice(&mut self, value: u32, k: u32) {
+        let mut quotient = value >> k;
+        while quotient >= 32 {
+            self.write(0, 32);
+            quotient -= 32;
+        }
+        self.write(1, quotient + 1);
+        self.write(value as u64, k);
+    }
+
+    /// Pad with zero bits up to the next byte boundary
+    fn align(&mut self) {
+        if self.pending_bits > 0 {
+            self.write(0, 8 - self.pending_bits);
+        }
+    }
+
+    /// Completed bytes (only meaningful when aligned)
+    fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        self.align();
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "noteflux-flac-{}-{}.flac",
+            name,
+            std::process::id()
+        ))
+    }
+
+    /// Interleaved test signal: a different tone per channel
+    fn tone(frames: usize, channels: u16) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                (0..channels).map(move |channel| {
+                    let t = i as f32 / 16000.0;
+                    (t * 440.0 * (channel + 1) as f32 * std::f32::consts::TAU).sin() * 0.5
+                })
+            })
+            .collect()
+    }
+
+    /// Encode `samples` in uneven chunks, then decode the file with claxon
+    fn round_trip(
+        name: &str,
+        channels: u16,
+        samples: &[f32],
+    ) -> (claxon::metadata::StreamInfo, Vec<i32>) {
+        let path = temp_file(name);
+        let mut writer = FlacWriter::new(path.clone(), 16000, channels).unwrap();
+        for chunk in samples.chunks(999 * channels as usize) {
+            writer.write_samples(chunk).unwrap();
+        }
+        writer.finalize().unwrap();
+        drop(writer);
+
+        let decoded = decode(&path);
+        std::fs::remove_file(&path).ok();
+        decoded
+    }
+
+    fn decode(path: &Path) -> (claxon::metadata::StreamInfo, Vec<i32>) {
+        let mut reader = claxon::FlacReader::open(path).unwrap();
+        let samples = reader.samples().collect::<Result<Vec<_>, _>>().unwrap();
+        (reader.streaminfo(), samples)
+    }
+
+    fn expected(samples: &[f32]) -> Vec<i32> {
+        samples.iter().map(|&s| f32_to_i16(s) as i32).collect()
+    }
+
+    #[test]
+    fn round_trips_mono() {
+        let samples = tone(3 * BLOCK_SIZE, 1);
+        let (info, decoded) = round_trip("mono", 1, &samples);
+
+        assert_eq!(
+            (info.sample_rate, info.channels, info.bits_per_sample),
+            (16000, 1, 16)
+        );
+        assert_eq!(info.samples, Some(samples.len() as u64));
+        assert_eq!(decoded, expected(&samples));
+    }
+
+    #[test]
+    fn round_trips_stereo() {
+        let samples = tone(2 * BLOCK_SIZE, 2);
+        let (info, decoded) = round_trip("stereo", 2, &samples);
+
+        assert_eq!(info.channels, 2);
+        assert_eq!(info.samples, Some(2 * BLOCK_SIZE as u64));
+        assert_eq!(decoded, expected(&samples));
+    }
+
+    #[test]
+    fn encodes_a_partial_last_block() {
+        // A last frame shorter than the widest predictor's warm-up
+        let samples = tone(BLOCK_SIZE + 3, 2);
+        let (info, decoded) = round_trip("partial", 2, &samples);
+
+        assert_eq!(info.samples, Some(BLOCK_SIZE as u64 + 3));
+        assert_eq!(
+            (info.min_block_size, info.max_block_size),
+            (BLOCK_SIZE as u16, BLOCK_SIZE as u16)
+        );
+        assert!(info
+            .min_frame_size
+            .is_some_and(|min| min < info.max_frame_size.unwrap()));
+        assert_eq!(decoded, expected(&samples));
+    }
+
+    #[test]
+    fn codes_constant_input_compactly() {
+        let samples = vec![0.25; 2 * BLOCK_SIZE];
+        let path = temp_file("constant");
+        let mut writer = FlacWriter::new(path.clone(), 16000, 1).unwrap();
+        writer.write_samples(&samples).unwrap();
+        writer.finalize().unwrap();
+        drop(writer);
+
+        // Header and STREAMINFO plus two frames of a handful of bytes each
+        assert!(std::fs::metadata(&path).unwrap().len() < 100);
+        let (_, decoded) = decode(&path);
+        assert_eq!(decoded, expected(&samples));
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn finalizing_again_keeps_the_file_intact() {
+        let samples = tone(BLOCK_SIZE + 500, 1);
+        let path = temp_file("refinalize");
+        let mut writer = FlacWriter::new(path.clone(), 16000, 1).unwrap();
+        writer.write_samples(&samples).unwrap();
+        writer.finalize().unwrap();
+        let len = std::fs::metadata(&path).unwrap().len();
+
+        // Finalized twice more: explicitly and on drop
+        writer.finalize().unwrap();
+        drop(writer);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), len);
+
+        let (info, decoded) = decode(&path);
+        assert_eq!(info.samples, Some(samples.len() as u64));
+        assert_eq!(decoded, expected(&samples));
+        std::fs::remove_file(&path).ok();
+    }
+}
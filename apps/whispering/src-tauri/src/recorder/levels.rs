@@ -0,0 +1,189 @@
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+/// Minimum interval between two `audio-level` events (~30 updates per second)
+const EMIT_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Floor used when converting to decibels so silence does not become -inf
+pub const MIN_DB: f32 = -100.0;
+
+/// Input level of a block of samples - emitted to the frontend
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioLevel {
+    pub rms: f32,  // Linear RMS, 0.0..=1.0
+    pub peak: f32, // Linear absolute peak, 0.0..=1.0
+    pub rms_db: f32,
+    pub peak_db: f32,
+}
+
+impl AudioLevel {
+    /// Level of complete silence
+    pub fn silent() -> Self {
+        Self {
+            rms: 0.0,
+            peak: 0.0,
+            rms_db: MIN_DB,
+            peak_db: MIN_DB,
+        }
+    }
+
+    fn from_linear(rms: f32, peak: f32) -> Self {
+        Self {
+            rms,
+            peak,
+            rms_db: to_db(rms),
+            peak_db: to_db(peak),
+        }
+    }
+}
+
+/// Convert a linear amplitude to dBFS, clamped to `MIN_DB`
+pub fn to_db(value: f32) -> f32 {
+    if value <= 0.0 {
+        MIN_DB
+    } else {
+        (20.0 * value.log10()).max(MIN_DB)
+    }
+}
+
+/// Accumulates RMS/peak over incoming buffers and yields a reading once per emit interval.
+///
+/// Runs on the recorder's processing thread, never on the real-time audio callback.
+pub struct LevelMeter {
+    sum_squares: f64,
+    sample_count: usize,
+    peak: f32,
+    last_emit: Instant,
+}
+
+impl LevelMeter {
+    pub fn new() -> Self {
+        Self {
+            sum_squares: 0.0,
+            sample_count: 0,
+            peak: 0.0,
+            last_emit: Instant::now(),
+        }
+    }
+
+    /// Add a buffer of interleaved samples. Returns a level when one is due.
+    pub fn process(&mut self, samples: &[f32]) -> Option<AudioLevel> {
+        self.process_at(samples, Instant::now())
+    }
+
+    fn process_at(&mut self, samples: &[f32], now: Instant) -> Option<AudioLevel> {
+        // Accumulate so short buffers between emits still contribute to the reading
+        for &sample in samples {
+            self.sum_squares += (sample as f64) * (sample as f64);
+            self.peak = self.peak.max(sample.abs());
+        }
+        self.sample_count += samples.len();
+
+        if now.duration_since(self.last_emit) < EMIT_INTERVAL || self.sample_count == 0 {
+            return None;
+        }
+
+        let rms = (self.sum_squares / self.sample_count as f64).sqrt() as f32;
+        let level = AudioLevel::from_linear(rms.min(1.0), self.peak.min(1.0));
+
+        self.sum_squares = 0.0;
+        self.sample_count = 0;
+        self.peak = 0.0;
+        self.last_emit = now;
+
+        Some(level)
+    }
+}
+
+impl Default for LevelMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The overlay is loaded from a file URL without IPC access, so push the level via eval
+pub fn update_overlay(app_handle: &AppHandle, rms: f32) {
+    if let Some(window) = app_handle.get_webview_window("recording-overlay") {
+        let _ = window.eval(format!(
+            "window.setAudioLevel && window.setAudioLevel({:.4})",
+            rms
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A meter whose next reading is due
+    fn due_meter() -> (LevelMeter, Instant) {
+        let meter = LevelMeter::new();
+        let due = meter.last_emit + EMIT_INTERVAL;
+        (meter, due)
+    }
+
+    #[test]
+    fn measures_rms_and_peak() {
+        let (mut meter, due) = due_meter();
+        // Square wave at half scale: RMS and peak are both 0.5
+        let level = meter.process_at(&[0.5, -0.5, 0.5, -0.5], due).unwrap();
+        assert_eq!((level.rms, level.peak), (0.5, 0.5));
+
+        // Full-scale sine: RMS is 1/sqrt(2) of the peak
+        let sine: Vec<f32> = (0..1000)
+            .map(|i| (i as f32 / 1000.0 * std::f32::consts::TAU).sin())
+            .collect();
+        let level = meter.process_at(&sine, due + EMIT_INTERVAL).unwrap();
+        assert!((level.rms - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-4);
+        assert!((level.peak - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn converts_to_dbfs() {
+        assert_eq!(to_db(1.0), 0.0);
+        assert!((to_db(0.5) + 6.0206).abs() < 1e-3);
+        assert!((to_db(0.1) + 20.0).abs() < 1e-4);
+        assert_eq!(to_db(0.0), MIN_DB);
+        assert_eq!(to_db(1e-9), MIN_DB);
+
+        let (mut meter, due) = due_meter();
+        let level = meter.process_at(&[0.0; 64], due).unwrap();
+        assert_eq!((level.rms_db, level.peak_db), (MIN_DB, MIN_DB));
+    }
+
+    #[test]
+    fn throttles_and_accumulates_between_readings() {
+        let (mut meter, due) = due_meter();
+        let start = meter.last_emit;
+
+        // Too early: the buffers are kept for the next reading
+        assert!(meter.process_at(&[1.0, 1.0], start).is_none());
+        assert!(meter
+            .process_at(&[0.0, 0.0], due - Duration::from_millis(1))
+            .is_none());
+
+        let level = meter.process_at(&[], due).unwrap();
+        assert!((level.rms - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+        assert_eq!(level.peak, 1.0);
+
+        // The next reading starts from scratch, an interval later
+        assert!(meter.process_at(&[0.25], due).is_none());
+        let level = meter.process_at(&[], due + EMIT_INTERVAL).unwrap();
+        assert_eq!((level.rms, level.peak), (0.25, 0.25));
+    }
+
+    #[test]
+    fn no_reading_without_samples() {
+        let (mut meter, due) = due_meter();
+        assert!(meter.process_at(&[], due).is_none());
+    }
+
+    #[test]
+    fn clips_overloaded_input_to_full_scale() {
+        let (mut meter, due) = due_meter();
+        let level = meter.process_at(&[2.0, -3.0], due).unwrap();
+        assert_eq!((level.rms, level.peak, level.peak_db), (1.0, 1.0, 0.0));
+    }
+}
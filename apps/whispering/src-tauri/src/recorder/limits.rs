@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+
+/// Room left under `max_bytes` for what finalizing still writes (encoder tail, headers)
+const FINALIZE_RESERVE: u64 = 64 * 1024;
+
+/// Bytes per sample assumed before anything has been written
+const INITIAL_BYTES_PER_SAMPLE: f64 = 4.0;
+
+/// Per-session recording limits - passed from the frontend in `init_recording_session`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RecordingLimits {
+    /// Longest recording, in seconds of audio (pauses excluded)
+    pub max_seconds: Option<f32>,
+    /// Largest file, e.g. a transcription provider's upload limit
+    pub max_bytes: Option<u64>,
+    /// How long before a limit the `recording-limit-warning` event fires
+    pub warning_seconds: f32,
+}
+
+impl Default for RecordingLimits {
+    fn default() -> Self {
+        Self {
+            max_seconds: None,
+            max_bytes: None,
+            warning_seconds: 10.0,
+        }
+    }
+}
+
+/// Payload of the `recording-limit-warning` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LimitWarning {
+    pub limit: String, // "max-duration" or "max-size"
+    pub remaining_seconds: f32,
+}
+
+/// Outcome of checking a buffer against the limits
+pub struct LimitCheck {
+    pub allowed: usize, // Leading samples of the buffer that may be written
+    pub warning: Option<LimitWarning>, // Sent once per recording
+    pub reached: Option<&'static str>, // The limit that cut the recording
+}
+
+/// Applies the limits to one recording at a time
+pub struct LimitTracker {
+    limits: RecordingLimits,
+    sample_rate: u32,
+    channels: u16,
+    base_bytes: Option<u64>, // File size before the first sample (headers)
+    samples_written: u64,
+    warned: bool,
+    reached: bool,
+}
+
+impl LimitTracker {
+    pub fn new(limits: RecordingLimits, sample_rate: u32, channels: u16) -> Self {
+        Self {
+            limits,
+            sample_rate,
+            channels,
+            base_bytes: None,
+            samples_written: 0,
+            warned: false,
+            reached: false,
+        }
+    }
+
+    /// Start over for a new recording
+    pub fn reset(&mut self) {
+        self.base_bytes = None;
+        self.samples_written = 0;
+        self.warned = false;
+        self.reached = false;
+    }
+
+    /// Whether a limit cut the current recording
+    pub fn reached(&self) -> bool {
+        self.reached
+    }
+
+    /// How much of a `samples`-long buffer fits, given the file holds `seconds` of audio
+    /// in `bytes`. The byte limit is estimated from the average size of a sample so far,
+    /// as compressed formats only know their size once encoded.
+    pub fn check(&mut self, samples: usize, seconds: f32, bytes: u64) -> LimitCheck {
+        let frame = self.channels.max(1) as usize;
+        let samples_per_second = self.sample_rate as f64 * frame as f64;
+        let mut allowed = samples;
+        let mut reached = None;
+        let mut remaining_seconds = f64::INFINITY;
+        let mut closest = "max-duration";
+
+        if let Some(max_seconds) = self.limits.max_seconds {
+            let remaining = (max_seconds - seconds).max(0.0) as f64;
+            let fit = (remaining * self.sample_rate as f64).round() as usize * frame;
+            if fit < allowed {
+                allowed = fit;
+                reached = Some("max-duration");
+            }
+            remaining_seconds = remaining - allowed as f64 / samples_per_second;
+        }
+
+        if let Some(max_bytes) = self.limits.max_bytes {
+            let base = *self.base_bytes.get_or_insert(bytes);
+            let bytes_per_sample = if self.samples_written > 0 && bytes > base {
+                (bytes - base) as f64 / self.samples_written as f64
+            } else {
+                INITIAL_BYTES_PER_SAMPLE
+            };
+            let budget = max_bytes.saturating_sub(FINALIZE_RESERVE.min(max_bytes / 8));
+            let remaining = budget.saturating_sub(bytes) as f64;
+            let fit = (remaining / bytes_per_sample) as usize / frame * frame;
+            if fit < allowed {
+                allowed = fit;
+                reached = Some("max-size");
+            }
+            let remaining = (remaining / bytes_per_sample - allowed as f64) / samples_per_second;
+            if remaining < remaining_seconds {
+                remaining_seconds = remaining;
+                closest = "max-size";
+            }
+        }
+
+        self.samples_written += allowed as u64;
+        self.reached |= reached.is_some();
+
+        let warning = if reached.is_none()
+            && !self.warned
+            && remaining_seconds <= self.limits.warning_seconds as f64
+        {
+            self.warned = true;
+            Some(LimitWarning {
+                limit: closest.to_string(),
+                remaining_seconds: remaining_seconds.max(0.0) as f32,
+            })
+        } else {
+            None
+        };
+
+        LimitCheck {
+            allowed,
+            warning,
+            reached,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(max_seconds: Option<f32>, max_bytes: Option<u64>, channels: u16) -> LimitTracker {
+        let limits = RecordingLimits {
+            max_seconds,
+            max_bytes,
+            warning_seconds: 2.0,
+        };
+        LimitTracker::new(limits, 1000, channels)
+    }
+
+    #[test]
+    fn warns_once_before_the_duration_limit() {
+        let mut limits = tracker(Some(10.0), None, 1);
+
+        // 3 seconds left after this buffer
+        let check = limits.check(1000, 6.0, 0);
+        assert_eq!((check.allowed, check.reached), (1000, None));
+        assert!(check.warning.is_none());
+
+        // 2 seconds left: within the warning window
+        let check = limits.check(1000, 7.0, 0);
+        let warning = check.warning.unwrap();
+        assert_eq!(warning.limit, "max-duration");
+        assert_eq!(warning.remaining_seconds, 2.0);
+
+        assert!(limits.check(1000, 8.0, 0).warning.is_none());
+        assert!(!limits.reached());
+    }
+
+    #[test]
+    fn cuts_the_buffer_at_the_duration_limit() {
+        let mut limits = tracker(Some(1.0), None, 2);
+
+        // Half a second of stereo left: 500 whole frames
+        let check = limits.check(4000, 0.5, 0);
+        assert_eq!((check.allowed, check.reached), (1000, Some("max-duration")));
+        assert!(check.warning.is_none());
+        assert!(limits.reached());
+
+        assert_eq!(limits.check(4000, 1.0, 0).allowed, 0);
+    }
+
+    #[test]
+    fn estimates_size_from_what_was_written() {
+        // 8000 bytes less a reserve of 1000 for finalizing; a 44-byte header
+        let mut limits = tracker(None, Some(8000), 1);
+
+        // Nothing written yet: 4 bytes per sample, (7000 - 44) / 4 = 1739 fit
+        let check = limits.check(1000, 0.0, 44);
+        assert_eq!((check.allowed, check.reached), (1000, None));
+
+        // The encoder turned 1000 samples into 1000 bytes: 1 byte per sample from now on
+        let check = limits.check(10_000, 1.0, 1044);
+        assert_eq!((check.allowed, check.reached), (5956, Some("max-size")));
+        assert!(limits.reached());
+    }
+
+    #[test]
+    fn warns_about_the_closer_limit() {
+        let mut limits = tracker(Some(100.0), Some(8000), 1);
+
+        // 1739 samples fit by size, under 2 seconds away; 100 seconds by duration
+        let check = limits.check(100, 0.0, 44);
+        let warning = check.warning.unwrap();
+        assert_eq!(warning.limit, "max-size");
+        assert!((warning.remaining_seconds - 1.639).abs() < 1e-3);
+    }
+
+    #[test]
+    fn reset_starts_a_new_recording() {
+        let mut limits = tracker(Some(1.0), None, 1);
+        limits.check(2000, 0.0, 0);
+        assert!(limits.reached());
+
+        limits.reset();
+        assert!(!limits.reached());
+        let check = limits.check(500, 0.0, 0);
+        assert_eq!((check.allowed, check.reached), (500, None));
+        assert!(check.warning.is_some());
+    }
+}
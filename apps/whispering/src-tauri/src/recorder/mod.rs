@@ -1,13 +1,37 @@
 pub mod commands;
+pub mod device_monitor;
+pub mod devices;
+pub mod encoder;
+pub mod flac_writer;
+pub mod levels;
+pub mod limits;
+pub mod opus_writer;
+pub mod pipeline;
+pub mod pre_roll;
 pub mod recorder;
+pub mod recovery;
+pub mod resampler;
+pub mod status;
+pub mod streaming;
+pub mod vad;
 pub mod wav_writer;
 
 // Export everything from commands for easy access
 pub use commands::{
     cancel_recording, close_recording_session, enumerate_recording_devices,
-    get_current_recording_id, hide_recording_overlay, init_recording_session,
+    get_current_recording_id, get_input_level, get_recorder_status, get_recovered_recordings,
+    hide_recording_overlay, init_recording_session, pause_recording, resume_recording,
     set_overlay_processing, show_recording_overlay, start_recording, stop_recording, AppData,
 };
 
 // Export key types from recorder
-pub use recorder::AudioRecording;
+pub use device_monitor::{ActiveDevice, DeviceChangedEvent, DeviceLostEvent};
+pub use devices::RecordingDevice;
+pub use encoder::RecordingFormat;
+pub use levels::AudioLevel;
+pub use limits::{LimitWarning, RecordingLimits};
+pub use recorder::{AudioFormat, AudioRecording, PauseMarker};
+pub use recovery::RecoveredRecording;
+pub use status::{RecorderStatus, RecorderStatusInfo};
+pub use streaming::{AudioChunk, StreamingConfig};
+pub use vad::VadConfig;
@@ -0,0 +1,382 @@
+use crate::recorder::encoder::{buffered_len, AudioEncoder};
+use audiopus::coder::Encoder;
+use audiopus::{Application, Bitrate, Channels, SampleRate};
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use tracing::info;
+
+/// Input rates libopus accepts
+pub const OPUS_SAMPLE_RATES: [u32; 5] = [8000, 12000, 16000, 24000, 48000];
+
+/// Ogg/Opus granule positions are always counted at 48 kHz
+const GRANULE_RATE: u64 = 48000;
+
+/// Encoded frame length
+const FRAME_MS: u32 = 20;
+
+/// Bitrate per channel - plenty for intelligible speech
+const BITRATE_PER_CHANNEL: i32 = 24000;
+
+/// Largest packet libopus can produce for one frame
+const MAX_PACKET_SIZE: usize = 4000;
+
+/// Close an Ogg page roughly every second so an interrupted file loses little audio
+const FRAMES_PER_PAGE: u64 = 50;
+
+/// Ogg/Opus writer using the VoIP application mode
+pub struct OpusWriter {
+    writer: PacketWriter<BufWriter<File>>,
+    encoder: Encoder,
+    sample_rate: u32,
+    channels: u16,
+    serial: u32,
+    frame_len: usize,     // Interleaved samples per encoded frame
+    pending: Vec<f32>,    // Interleaved samples not yet encoded
+    pre_skip: u64,        // Encoder delay, in 48 kHz samples
+    samples_written: u64, // Per channel, at the input rate (excluding padding)
+    frames_encoded: u64,
+    finalized: bool,
+    file_path: PathBuf,
+}
+
+impl OpusWriter {
+    /// Create a new Ogg/Opus file and write the identification and comment headers
+    pub fn new(file_path: PathBuf, sample_rate: u32, channels: u16) -> io::Result<Self> {
+        let opus_rate = SampleRate::try_from(sample_rate as i32).map_err(opus_error)?;
+        let opus_channels = match channels {
+            1 => Channels::Mono,
+            2 => Channels::Stereo,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Opus supports 1 or 2 channels, got {}", channels),
+                ))
+            }
+        };
+
+        let mut encoder =
+            Encoder::new(opus_rate, opus_channels, Application::Voip).map_err(opus_error)?;
+        encoder
+            .set_bitrate(Bitrate::BitsPerSecond(
+                BITRATE_PER_CHANNEL * channels as i32,
+            ))
+            .map_err(opus_error)?;
+        let lookahead = encoder.lookahead().map_err(opus_error)? as u64;
+        let pre_skip = lookahead * GRANULE_RATE / sample_rate as u64;
+
+        let file = File::create(&file_path)?;
+        let mut writer = PacketWriter::new(BufWriter::new(file));
+        let serial = std::process::id() ^ sample_rate;
+
+        // Identification header (RFC 7845 section 5.1) - must be alone on the first page
+        let mut head = Vec::with_capacity(19);
+        head.extend_from_slice(b"OpusHead");
+        head.push(1); // Version
+        head.push(channels as u8);
+        head.extend_from_slice(&(pre_skip as u16).to_le_bytes());
+        head.extend_from_slice(&sample_rate.to_le_bytes()); // Original input rate
+        head.extend_from_slice(&0i16.to_le_bytes()); // Output gain
+        head.push(0); // Channel mapping family (mono/stereo)
+        writer.write_packet(
+            head.into_boxed_slice(),
+            serial,
+            PacketWriteEndInfo::EndPage,
+            0,
+        )?;
+
+        // Comment header (RFC 7845 section 5.2)
+        let vendor = format!("noteflux {}", audiopus::version());
+        let mut tags = Vec::new();
+        tags.extend_from_slice(b"OpusTags");
+        tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        tags.extend_from_slice(vendor.as_bytes());
+        tags.extend_from_slice(&0u32.to_le_bytes()); // No user comments
+        writer.write_packet(
+            tags.into_boxed_slice(),
+            serial,
+            PacketWriteEndInfo::EndPage,
+            0,
+        )?;
+        writer.inner_mut().flush()?;
+
+        info!(
+            "Created Opus file at {:?}: {}Hz, {} channels, {} kbps",
+            file_path,
+            sample_rate,
+            channels,
+            BITRATE_PER_CHANNEL * channels as i32 / 1000
+        );
+
+        let frame_len = (sample_rate * FRAME_MS / 1000) as usize * channels as usize;
+        Ok(Self {
+            writer,
+            encoder,
+            sample_rate,
+            channels,
+            serial,
+            frame_len,
+            pending: Vec::with_capacity(frame_len * 2),
+            pre_skip,
+            samples_written: 0,
+            frames_encoded: 0,
+            finalized: false,
+            file_path,
+        })
+    }
+
+    /// Encode one full frame from the front of `pending`
+    fn encode_frame(&mut self, end_info: PacketWriteEndInfo) -> io::Result<()> {
+        let mut packet = vec![0u8; MAX_PACKET_SIZE];
+        let len = self
+            .encoder
+            .encode_float(&self.pending[..self.frame_len], &mut packet)
+            .map_err(opus_error)?;
+        packet.truncate(len);
+        self.pending.drain(..self.frame_len);
+        self.frames_encoded += 1;
+
+        // Granule of the last real sample in this packet, so decoders drop the padding
+        let granule = self.pre_skip + self.samples_written * GRANULE_RATE / self.sample_rate as u64;
+
+        let end_info = match end_info {
+            PacketWriteEndInfo::NormalPacket
+                if self.frames_encoded.is_multiple_of(FRAMES_PER_PAGE) =>
+            {
+                PacketWriteEndInfo::EndPage
+            }
+            other => other,
+        };
+        let flush = !matches!(end_info, PacketWriteEndInfo::NormalPacket);
+
+        self.writer
+            .write_packet(packet.into_boxed_slice(), self.serial, end_info, granule)?;
+        if flush {
+            self.writer.inner_mut().flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the current duration in seconds
+    pub fn get_duration_seconds(&self) -> f32 {
+        self.samples_written as f32 / self.sample_rate as f32
+    }
+}
+
+impl AudioEncoder for OpusWriter {
+    fn write_samples(&mut self, samples: &[f32]) -> io::Result<()> {
+        if self.finalized {
+            return Ok(()); // The stream has already been closed
+        }
+
+        self.pending.extend_from_slice(samples);
+        while self.pending.len() >= self.frame_len {
+            self.samples_written += (self.frame_len / self.channels as usize) as u64;
+            self.encode_frame(PacketWriteEndInfo::NormalPacket)?;
+        }
+
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> io::Result<()> {
+        if self.finalized {
+            return Ok(());
+        }
+        self.finalized = true;
+
+        // Opus only encodes whole frames: pad the remainder with silence and end the stream
+        self.samples_written += (self.pending.len() / self.channels as usize) as u64;
+        self.pending.resize(self.frame_len, 0.0);
+        self.encode_frame(PacketWriteEndInfo::EndStream)?;
+
+        info!(
+            "Finalized Opus file {:?}: {} frames, {:.2} seconds",
+            self.file_path,
+            self.frames_encoded,
+            self.get_duration_seconds()
+        );
+
+        Ok(())
+    }
+
+    fn get_metadata(&self) -> (u32, u16, f32) {
+        (self.sample_rate, self.channels, self.get_duration_seconds())
+    }
+
+    fn bytes_written(&self) -> u64 {
+        buffered_len(self.writer.inner())
+    }
+}
+
+impl Drop for OpusWriter {
+    fn drop(&mut self) {
+        if let Err(e) = self.finalize() {
+            tracing::error!("Failed to finalize Opus file on drop: {}", e);
+        }
+    }
+}
+
+fn opus_error(error: audiopus::Error) -> io::Error {
+    io::Error::other(format!("Opus error: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use audiopus::coder::Decoder;
+    use audiopus::packet::Packet;
+    use audiopus::MutSignals;
+    use ogg::reading::PacketReader;
+    use std::path::Path;
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "noteflux-opus-{}-{}.opus",
+            name,
+            std::process::id()
+        ))
+    }
+
+    /// Interleaved test signal: a different tone per channel
+    fn tone(frames: usize, channels: u16) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                (0..channels).map(move |channel| {
+                    let t = i as f32 / 16000.0;
+                    (t * 440.0 * (channel + 1) as f32 * std::f32::consts::TAU).sin() * 0.5
+                })
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    /// What a decoder sees in a finished file
+    struct Decoded {
+        head: Vec<u8>,
+        samples: Vec<f32>, // Interleaved, at 48 kHz, including the pre-skip
+        final_granule: u64,
+        end_of_streams: usize,
+    }
+
+    fn decode(path: &Path) -> Decoded {
+        let mut reader = PacketReader::new(File::open(path).unwrap());
+        let head = reader.read_packet().unwrap().unwrap().data;
+        let channels = head[9] as usize;
+        let tags = reader.read_packet().unwrap().unwrap();
+        assert!(tags.data.starts_with(b"OpusTags"));
+
+        let opus_channels = if channels == 1 {
+            Channels::Mono
+        } else {
+            Channels::Stereo
+        };
+        let mut decoder = Decoder::new(SampleRate::Hz48000, opus_channels).unwrap();
+        let mut frame = vec![0.0f32; 5760 * channels];
+        let mut decoded = Decoded {
+            head: head.clone(),
+            samples: Vec::new(),
+            final_granule: 0,
+            end_of_streams: 0,
+        };
+        while let Some(packet) = reader.read_packet().unwrap() {
+            let input = Packet::try_from(packet.data.as_slice()).unwrap();
+            let output = MutSignals::try_from(&mut frame[..]).unwrap();
+            let len = decoder.decode_float(Some(input), output, false).unwrap();
+            decoded.samples.extend_from_slice(&frame[..len * channels]);
+            if packet.last_in_stream() {
+                decoded.end_of_streams += 1;
+                decoded.final_granule = packet.absgp_page();
+            }
+        }
+        decoded
+    }
+
+    fn record(name: &str, channels: u16, samples: &[f32]) -> Decoded {
+        let path = temp_file(name);
+        let mut writer = OpusWriter::new(path.clone(), 16000, channels).unwrap();
+        for chunk in samples.chunks(999 * channels as usize) {
+            writer.write_samples(chunk).unwrap();
+        }
+        writer.finalize().unwrap();
+        drop(writer);
+
+        let decoded = decode(&path);
+        std::fs::remove_file(&path).ok();
+        decoded
+    }
+
+    /// Decoded audio without the encoder delay and the padding of the last frame
+    fn trimmed(decoded: &Decoded, channels: usize) -> &[f32] {
+        let pre_skip = u16::from_le_bytes([decoded.head[10], decoded.head[11]]) as usize;
+        &decoded.samples[pre_skip * channels..decoded.final_granule as usize * channels]
+    }
+
+    #[test]
+    fn round_trips_mono() {
+        // Half a second plus part of a frame
+        let samples = tone(8100, 1);
+        let decoded = record("mono", 1, &samples);
+
+        assert_eq!(&decoded.head[..10], b"OpusHead\x01\x01");
+        assert_eq!(
+            u32::from_le_bytes(decoded.head[12..16].try_into().unwrap()),
+            16000
+        );
+        assert_eq!(decoded.end_of_streams, 1);
+
+        // The final granule counts exactly the real audio, upsampled to 48 kHz
+        let audio = trimmed(&decoded, 1);
+        assert_eq!(audio.len(), samples.len() * 3);
+        assert!((rms(audio) / rms(&samples) - 1.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn round_trips_stereo() {
+        let samples = tone(8000, 2);
+        let decoded = record("stereo", 2, &samples);
+
+        assert_eq!(decoded.head[9], 2);
+        let audio = trimmed(&decoded, 2);
+        assert_eq!(audio.len(), samples.len() * 3);
+
+        // Lossy, but the level of each channel survives
+        let channel = |samples: &[f32], index: usize| -> Vec<f32> {
+            samples.iter().skip(index).step_by(2).copied().collect()
+        };
+        for index in 0..2 {
+            let expected = rms(&channel(&samples, index));
+            let actual = rms(&channel(audio, index));
+            assert!(
+                (actual / expected - 1.0).abs() < 0.1,
+                "{} vs {}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn finalizing_again_ends_the_stream_once() {
+        let samples = tone(4000, 1);
+        let path = temp_file("refinalize");
+        let mut writer = OpusWriter::new(path.clone(), 16000, 1).unwrap();
+        writer.write_samples(&samples).unwrap();
+        writer.finalize().unwrap();
+
+        // Later writes and finalizes (explicit and on drop) change nothing
+        writer.write_samples(&samples).unwrap();
+        writer.finalize().unwrap();
+        assert_eq!(writer.get_duration_seconds(), 0.25);
+        drop(writer);
+
+        let decoded = decode(&path);
+        assert_eq!(decoded.end_of_streams, 1);
+        assert_eq!(trimmed(&decoded, 1).len(), samples.len() * 3);
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,372 @@
+use crate::recorder::encoder::AudioEncoder;
+use crate::recorder::levels::{update_overlay, AudioLevel, LevelMeter};
+use crate::recorder::limits::LimitTracker;
+use crate::recorder::pre_roll::PreRollBuffer;
+use crate::recorder::resampler::FormatConverter;
+use crate::recorder::streaming::ChunkStreamer;
+use crate::recorder::vad::{VadEvent, VoiceActivityDetector};
+use crossbeam_channel::{bounded, Receiver, Sender, TrySendError};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::{debug, info};
+
+/// Maximum number of buffers queued between the audio callback and the processing thread.
+/// When processing falls behind, new buffers are dropped instead of blocking the callback.
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Payload of `speech-started` / `speech-ended` events
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeechEvent {
+    pub offset_seconds: f32, // Position in the recording
+}
+
+/// Payload of the `recording-auto-stopped` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoStopEvent {
+    pub reason: String,
+}
+
+/// Recorded audio forwarded to an in-process consumer (e.g. streaming transcription)
+#[derive(Debug, Clone)]
+pub enum TapMessage {
+    Audio(Vec<f32>), // What was just written, in the output format
+    End,             // The recording stopped
+}
+
+/// What the audio callback and the stream thread send to the processing thread
+pub enum PipelineInput {
+    Audio(Vec<f32>),           // Interleaved f32 samples in the device format
+    Reformat(FormatConverter), // The input switched devices; convert from the new format
+}
+
+/// Sending side of the pipeline, held by the audio callbacks and the stream thread
+#[derive(Clone)]
+pub struct PipelineSender {
+    sender: Sender<PipelineInput>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl PipelineSender {
+    /// Queue a buffer from the audio callback, dropping it if the processing thread is behind
+    pub fn send_audio(&self, buffer: Vec<f32>) {
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(PipelineInput::Audio(buffer)) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Convert from a new input format from now on (waits for room, never called from
+    /// the audio callback)
+    pub fn reformat(&self, converter: FormatConverter) -> Result<(), String> {
+        self.sender
+            .send(PipelineInput::Reformat(converter))
+            .map_err(|_| "Audio pipeline stopped".to_string())
+    }
+}
+
+/// Consumer of the recorded audio, swapped in and out while the session is open
+pub type AudioTap = Arc<Mutex<Option<UnboundedSender<TapMessage>>>>;
+
+/// Called from the processing thread when a limit or the silence timeout ends the
+/// recording. It must not wait for the recorder, which joins the processing thread when
+/// the session closes.
+pub type AutoStopCallback = Box<dyn Fn() + Send>;
+
+/// Everything the processing thread needs from the session
+pub struct PipelineContext {
+    pub app_handle: AppHandle,
+    pub writer: Arc<Mutex<Box<dyn AudioEncoder>>>,
+    pub is_recording: Arc<AtomicBool>,
+    pub is_paused: Arc<AtomicBool>, // Drop audio without ending the recording
+    pub vad: Option<Arc<Mutex<VoiceActivityDetector>>>,
+    pub converter: FormatConverter,
+    pub streamer: Option<ChunkStreamer>,
+    pub audio_tap: AudioTap,
+    pub sample_rate: u32, // Output (file) rate
+    pub channels: u16,    // Output (file) channels
+    pub pre_roll_ms: u32,
+    pub limits: Option<LimitTracker>,
+    pub limit_reached: Arc<AtomicBool>, // Set when a limit cut the recording
+    pub on_auto_stop: Option<AutoStopCallback>,
+}
+
+/// Processing thread fed by the audio callback.
+///
+/// The callback only converts samples to f32 and queues them; this thread does the
+/// metering, format conversion, pre-roll buffering, voice activity detection, file
+/// writing and chunk streaming, in arrival order.
+pub struct AudioPipeline {
+    thread: Option<JoinHandle<()>>,
+    latest_level: Arc<Mutex<AudioLevel>>,
+    dropped: Arc<AtomicU64>, // Buffers lost because processing fell behind
+}
+
+impl AudioPipeline {
+    /// Spawn the processing thread. Returns the pipeline and the sender for the audio callback.
+    ///
+    /// The thread exits once every sender has been dropped (i.e. when the stream stops).
+    pub fn spawn(context: PipelineContext) -> (Self, PipelineSender) {
+        let (sender, receiver) = bounded::<PipelineInput>(CHANNEL_CAPACITY);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let latest_level = Arc::new(Mutex::new(AudioLevel::silent()));
+        let latest_level_clone = latest_level.clone();
+
+        let thread = thread::spawn(move || {
+            run_pipeline(context, receiver, latest_level_clone);
+        });
+
+        (
+            Self {
+                thread: Some(thread),
+                latest_level,
+                dropped: dropped.clone(),
+            },
+            PipelineSender { sender, dropped },
+        )
+    }
+
+    /// Most recent input level (aggregated over the last emit interval)
+    pub fn latest_level(&self) -> AudioLevel {
+        self.latest_level
+            .lock()
+            .map(|level| *level)
+            .unwrap_or_else(|_| AudioLevel::silent())
+    }
+
+    /// Buffers dropped since the last call
+    pub fn take_dropped_buffers(&self) -> u64 {
+        self.dropped.swap(0, Ordering::Relaxed)
+    }
+
+    /// Wait for the processing thread to exit. Senders must be dropped first.
+    pub fn join(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn run_pipeline(
+    context: PipelineContext,
+    receiver: Receiver<PipelineInput>,
+    latest_level: Arc<Mutex<AudioLevel>>,
+) {
+    let app_handle = context.app_handle;
+    let mut converter = context.converter;
+    let mut streamer = context.streamer;
+    let mut limits = context.limits;
+    let mut meter = LevelMeter::new();
+    let mut pre_roll =
+        PreRollBuffer::new(context.sample_rate, context.channels, context.pre_roll_ms);
+    let mut was_recording = false;
+
+    while let Ok(input) = receiver.recv() {
+        let buffer = match input {
+            PipelineInput::Audio(buffer) => buffer,
+            PipelineInput::Reformat(new_converter) => {
+                converter = new_converter;
+                continue;
+            }
+        };
+
+        // Levels are reported even while idle so the user can check the mic
+        if let Some(level) = meter.process(&buffer) {
+            if let Ok(mut guard) = latest_level.lock() {
+                *guard = level;
+            }
+            let _ = app_handle.emit("audio-level", level);
+            update_overlay(&app_handle, level.rms);
+        }
+
+        // Everything past this point works in the output format
+        let buffer = converter.process(buffer);
+
+        if !context.is_recording.load(Ordering::Acquire) {
+            if was_recording {
+                if let Some(streamer) = streamer.as_mut() {
+                    streamer.finish();
+                }
+                send_to_tap(&context.audio_tap, || TapMessage::End);
+            }
+            was_recording = false;
+            pre_roll.push(&buffer);
+            continue;
+        }
+
+        // Paused: nothing is written, and resuming carries on in the same recording
+        if context.is_paused.load(Ordering::Acquire) {
+            continue;
+        }
+
+        // First buffer of a recording: start the limits over and prepend the audio
+        // captured just before it started
+        let mut buffer = if was_recording {
+            buffer
+        } else {
+            was_recording = true;
+            if let Some(streamer) = streamer.as_mut() {
+                streamer.reset();
+            }
+            if let Some(limits) = limits.as_mut() {
+                limits.reset();
+            }
+            let mut samples = pre_roll.take();
+            samples.extend_from_slice(&buffer);
+            samples
+        };
+
+        // Once a limit cut the recording nothing more is written (it is being finalized)
+        if limits.as_ref().is_some_and(|limits| limits.reached()) {
+            continue;
+        }
+
+        let mut limit_check = None;
+        let (wrote, events) = {
+            // Lock order is VAD, then writer - the recorder's finalize takes them the same way
+            let mut vad_guard = context.vad.as_ref().and_then(|vad| vad.lock().ok());
+
+            // After a silence timeout nothing more is written until the next recording
+            if vad_guard.as_ref().is_some_and(|vad| vad.timed_out()) {
+                continue;
+            }
+
+            // Check the flags under the writer lock so nothing lands after stop/pause
+            let wrote = match context.writer.lock() {
+                Ok(mut w)
+                    if context.is_recording.load(Ordering::Acquire)
+                        && !context.is_paused.load(Ordering::Acquire) =>
+                {
+                    // Only what fits within the session's limits
+                    if let Some(limits) = limits.as_mut() {
+                        let check =
+                            limits.check(buffer.len(), w.get_metadata().2, w.bytes_written());
+                        buffer.truncate(check.allowed);
+                        limit_check = Some(check);
+                    }
+                    let _ = w.write_samples(&buffer);
+                    true
+                }
+                _ => false,
+            };
+
+            let events = match vad_guard.as_mut() {
+                Some(vad) if wrote => vad.process(&buffer),
+                _ => Vec::new(),
+            };
+            (wrote, events)
+        };
+
+        if let Some(streamer) = streamer.as_mut().filter(|_| wrote) {
+            streamer.push(&buffer);
+        }
+        if wrote {
+            send_to_tap(&context.audio_tap, || TapMessage::Audio(buffer.clone()));
+        }
+
+        if let Some(check) = limit_check {
+            if let Some(warning) = check.warning {
+                info!(
+                    "Recording limit {} in {:.1}s",
+                    warning.limit, warning.remaining_seconds
+                );
+                let _ = app_handle.emit("recording-limit-warning", warning);
+            }
+            if let Some(reason) = check.reached {
+                info!("Recording limit {} reached, finalizing", reason);
+                let _ = app_handle.emit(
+                    "recording-auto-stopped",
+                    AutoStopEvent {
+                        reason: reason.to_string(),
+                    },
+                );
+                context.limit_reached.store(true, Ordering::Release);
+                if let Some(on_auto_stop) = &context.on_auto_stop {
+                    on_auto_stop();
+                }
+            }
+        }
+
+        for event in events {
+            if matches!(event, VadEvent::SilenceTimeout) {
+                send_to_tap(&context.audio_tap, || TapMessage::End);
+            }
+            if let Some(streamer) = streamer.as_mut() {
+                match event {
+                    VadEvent::SpeechEnded { .. } if streamer.segment_by_vad() => {
+                        streamer.end_segment()
+                    }
+                    VadEvent::SilenceTimeout => streamer.finish(),
+                    _ => {}
+                }
+            }
+            emit_vad_event(&app_handle, event, context.sample_rate);
+            if matches!(event, VadEvent::SilenceTimeout) {
+                if let Some(on_auto_stop) = &context.on_auto_stop {
+                    on_auto_stop();
+                }
+            }
+        }
+    }
+
+    // Session closed mid-recording - still tell the frontend the stream is over
+    if let Some(streamer) = streamer.as_mut() {
+        streamer.finish();
+    }
+
+    // Stream is gone - report silence so meters fall back to zero
+    if let Ok(mut guard) = latest_level.lock() {
+        *guard = AudioLevel::silent();
+    }
+    let _ = app_handle.emit("audio-level", AudioLevel::silent());
+    update_overlay(&app_handle, 0.0);
+    debug!("Audio pipeline thread stopped");
+}
+
+/// Hand a message to the tap's consumer, if one is attached. A consumer that went away
+/// is detached.
+fn send_to_tap(tap: &AudioTap, message: impl FnOnce() -> TapMessage) {
+    if let Ok(mut guard) = tap.lock() {
+        if guard.as_ref().is_some_and(|tx| tx.send(message()).is_err()) {
+            *guard = None;
+        }
+    }
+}
+
+fn emit_vad_event(app_handle: &AppHandle, event: VadEvent, sample_rate: u32) {
+    let to_seconds = |frame: u64| frame as f32 / sample_rate as f32;
+
+    match event {
+        VadEvent::SpeechStarted { frame } => {
+            debug!("Speech started at {:.2}s", to_seconds(frame));
+            let _ = app_handle.emit(
+                "speech-started",
+                SpeechEvent {
+                    offset_seconds: to_seconds(frame),
+                },
+            );
+        }
+        VadEvent::SpeechEnded { frame } => {
+            debug!("Speech ended at {:.2}s", to_seconds(frame));
+            let _ = app_handle.emit(
+                "speech-ended",
+                SpeechEvent {
+                    offset_seconds: to_seconds(frame),
+                },
+            );
+        }
+        VadEvent::SilenceTimeout => {
+            info!("Trailing silence limit reached, recording auto-stopped");
+            let _ = app_handle.emit(
+                "recording-auto-stopped",
+                AutoStopEvent {
+                    reason: "silence".to_string(),
+                },
+            );
+        }
+    }
+}
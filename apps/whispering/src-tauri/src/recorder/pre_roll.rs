@@ -0,0 +1,106 @@
+use std::collections::VecDeque;
+
+/// Upper bound for the pre-roll so an idle session cannot hold unbounded audio
+/// (the recording settings input stops at the same value)
+pub const MAX_PRE_ROLL_MS: u32 = 2000;
+
+/// Ring buffer holding the most recent audio captured while not recording.
+///
+/// Its contents are prepended to the file when recording starts, so speech that begins
+/// while the shortcut is still being pressed is not clipped.
+pub struct PreRollBuffer {
+    samples: VecDeque<f32>,
+    capacity: usize, // In samples, always a whole number of frames
+}
+
+impl PreRollBuffer {
+    pub fn new(sample_rate: u32, channels: u16, duration_ms: u32) -> Self {
+        let duration_ms = duration_ms.min(MAX_PRE_ROLL_MS) as u64;
+        let frames = (sample_rate as u64 * duration_ms / 1000) as usize;
+        let capacity = frames * channels.max(1) as usize;
+
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Append interleaved samples, discarding the oldest audio beyond the capacity
+    pub fn push(&mut self, samples: &[f32]) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        // Only the tail of an oversized buffer can survive anyway
+        let samples = &samples[samples.len().saturating_sub(self.capacity)..];
+        let overflow = (self.samples.len() + samples.len()).saturating_sub(self.capacity);
+        self.samples.drain(..overflow);
+        self.samples.extend(samples);
+    }
+
+    /// Take the buffered audio, leaving the buffer empty
+    pub fn take(&mut self) -> Vec<f32> {
+        self.samples.drain(..).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp(range: std::ops::Range<usize>) -> Vec<f32> {
+        range.map(|i| i as f32).collect()
+    }
+
+    #[test]
+    fn keeps_the_most_recent_audio_across_wraparound() {
+        // 10 ms of stereo at 1 kHz: 10 frames, 20 samples
+        let mut buffer = PreRollBuffer::new(1000, 2, 10);
+        buffer.push(&ramp(0..12));
+        buffer.push(&ramp(12..26));
+        buffer.push(&ramp(26..30));
+
+        assert_eq!(buffer.take(), ramp(10..30));
+    }
+
+    #[test]
+    fn oversized_pushes_keep_only_their_tail() {
+        let mut buffer = PreRollBuffer::new(1000, 1, 10);
+        buffer.push(&ramp(0..5));
+        buffer.push(&ramp(5..100));
+
+        assert_eq!(buffer.take(), ramp(90..100));
+    }
+
+    #[test]
+    fn duration_is_capped() {
+        let mut buffer = PreRollBuffer::new(1000, 1, 60_000);
+        buffer.push(&ramp(0..10_000));
+
+        let kept = buffer.take();
+        assert_eq!(kept.len(), MAX_PRE_ROLL_MS as usize);
+        assert_eq!(kept[0], (10_000 - MAX_PRE_ROLL_MS) as f32);
+    }
+
+    #[test]
+    fn take_drains_in_order() {
+        let mut buffer = PreRollBuffer::new(1000, 1, 10);
+        buffer.push(&ramp(0..4));
+        buffer.push(&ramp(4..7));
+
+        assert_eq!(buffer.take(), ramp(0..7));
+        assert!(buffer.take().is_empty());
+
+        // Filling again after a drain starts from scratch
+        buffer.push(&ramp(7..9));
+        assert_eq!(buffer.take(), ramp(7..9));
+    }
+
+    #[test]
+    fn zero_duration_keeps_nothing() {
+        let mut buffer = PreRollBuffer::new(48000, 2, 0);
+        buffer.push(&ramp(0..100));
+
+        assert!(buffer.take().is_empty());
+    }
+}
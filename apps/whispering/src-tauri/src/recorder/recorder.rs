@@ -1,16 +1,42 @@
-use crate::recorder::wav_writer::WavWriter;
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{Device, SampleFormat, Stream};
+use crate::recorder::device_monitor::{
+    ActiveDevice, DeviceLostEvent, DeviceMonitor, SharedDevice, StallDetector, StreamHealth,
+};
+use crate::recorder::devices::{self, sample_format_name, RecordingDevice};
+use crate::recorder::encoder::{create_encoder, AudioEncoder, RecordingFormat};
+use crate::recorder::levels::AudioLevel;
+use crate::recorder::limits::{LimitTracker, RecordingLimits};
+use crate::recorder::pipeline::{
+    AudioPipeline, AudioTap, AutoStopCallback, PipelineContext, PipelineSender, TapMessage,
+};
+use crate::recorder::resampler::{FormatConverter, CANONICAL_CHANNELS, CANONICAL_SAMPLE_RATE};
+use crate::recorder::status::{RecorderStatus, RecorderStatusInfo, SharedStatus};
+use crate::recorder::streaming::{ChunkStreamer, StreamingSession};
+use crate::recorder::vad::{VadConfig, VoiceActivityDetector};
+use cpal::traits::{DeviceTrait, StreamTrait};
+use cpal::{Device, SampleFormat, Stream, StreamError};
+use crossbeam_channel::{bounded, Sender};
 use serde::Serialize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
-use tracing::{debug, error, info};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use tracing::{debug, error, info, warn};
 
 /// Simple result type using String for errors
 pub type Result<T> = std::result::Result<T, String>;
 
+/// Sample rate, channel count and sample format of an audio stream
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub sample_format: String, // e.g. "f32", "i16"
+}
+
 /// Audio recording metadata - returned to frontend
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -18,8 +44,50 @@ pub struct AudioRecording {
     pub audio_data: Vec<f32>, // Empty for file-based recording
     pub sample_rate: u32,
     pub channels: u16,
-    pub duration_seconds: f32,
-    pub file_path: Option<String>, // Path to the WAV file
+    pub duration_seconds: f32, // Audio in the file, i.e. excluding pauses
+    pub file_path: Option<String>, // Path to the recording file
+    pub mime_type: String,     // e.g. "audio/wav", "audio/flac"
+    pub device_format: Option<AudioFormat>, // What the microphone delivered
+    pub output_format: Option<AudioFormat>, // What was written to the file
+    pub paused_seconds: f32,   // Total time spent paused
+    pub pauses: Vec<PauseMarker>, // Also saved next to the file as `<id>.pauses.json`
+    pub truncated: bool,       // Cut short by the session's limits
+}
+
+/// Where a recording was paused - returned to frontend and saved in the pauses sidecar
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PauseMarker {
+    pub offset_seconds: f32, // Position in the file where recording resumed
+    pub paused_seconds: f32, // How long recording was paused there
+}
+
+/// Optional per-session settings passed to `init_session`
+#[derive(Default)]
+pub struct SessionConfig {
+    pub preferred_sample_rate: Option<u32>,
+    pub vad: Option<VadConfig>,
+    pub pre_roll_ms: u32, // Audio kept from before start_recording (0 = none)
+    pub canonical_output: bool, // Convert to 16 kHz mono regardless of the device format
+    pub format: RecordingFormat,
+    pub streaming: Option<StreamingSession>, // Push chunks to the frontend while recording
+    pub failover: bool,                      // Continue on the default input if the device is lost
+    pub limits: Option<RecordingLimits>,     // Auto-finalize at a maximum duration or size
+    pub on_auto_stop: Option<AutoStopCallback>, // Finalizes a recording ended by a limit or silence
+}
+
+/// What the stream thread needs to open the input, and to reopen it on another device
+struct StreamSetup {
+    app_handle: AppHandle,
+    device: Device,
+    config: cpal::SupportedStreamConfig,
+    sample_tx: PipelineSender,
+    active_device: SharedDevice,
+    status: SharedStatus,
+    failover: bool,
+    preferred_sample_rate: Option<u32>,
+    output_rate: u32,
+    output_channels: u16,
 }
 
 /// Minimal wrapper to handle the Stream in its own thread
@@ -31,37 +99,30 @@ struct StreamHolder {
 }
 
 impl StreamHolder {
-    fn new<F>(create_stream: F, is_recording: Arc<AtomicBool>) -> Result<Self>
-    where
-        F: FnOnce() -> Result<Stream> + Send + 'static,
-    {
+    /// Open the input in its own thread, which then watches it until stopped
+    fn new(setup: StreamSetup, is_recording: Arc<AtomicBool>) -> Result<Self> {
         let should_stop = Arc::new(AtomicBool::new(false));
         let should_stop_clone = should_stop.clone();
+        let (ready_tx, ready_rx) = bounded(1);
 
         // Create and run the stream in its own thread
-        let thread = thread::spawn(move || {
-            // Create the stream in this thread
-            let stream = match create_stream() {
-                Ok(s) => s,
-                Err(e) => {
-                    error!("Failed to create stream in thread: {}", e);
-                    return;
-                }
-            };
-
-            // Keep the stream alive until told to stop
-            while !should_stop_clone.load(Ordering::Acquire) {
-                thread::sleep(std::time::Duration::from_millis(100));
+        let thread = thread::spawn(move || run_stream(setup, should_stop_clone, ready_tx));
+
+        match ready_rx.recv() {
+            Ok(Ok(())) => Ok(Self {
+                thread: Some(thread),
+                is_recording,
+                should_stop,
+            }),
+            Ok(Err(e)) => {
+                let _ = thread.join();
+                Err(e)
             }
-            // Stream drops here, which stops it
-            drop(stream);
-        });
-
-        Ok(Self {
-            thread: Some(thread),
-            is_recording,
-            should_stop,
-        })
+            Err(_) => {
+                let _ = thread.join();
+                Err("Audio stream thread exited unexpectedly".to_string())
+            }
+        }
     }
 
     fn stop(&mut self) {
@@ -73,6 +134,121 @@ impl StreamHolder {
     }
 }
 
+/// Body of the stream thread: keep the stream alive, report device changes, and when
+/// the device is lost, tell the frontend and optionally continue on the default input.
+/// The pipeline and file stay the same, only the format conversion changes.
+fn run_stream(setup: StreamSetup, should_stop: Arc<AtomicBool>, ready: Sender<Result<()>>) {
+    let health = Arc::new(StreamHealth::default());
+    let mut stream = match open_stream(&setup.device, &setup.config, &setup.sample_tx, &health) {
+        Ok(stream) => Some(stream),
+        Err(e) => {
+            error!("Failed to create stream in thread: {}", e);
+            let _ = ready.send(Err(e));
+            return;
+        }
+    };
+    let _ = ready.send(Ok(()));
+
+    let mut stall = StallDetector::new(health);
+    let monitor = DeviceMonitor::start(setup.app_handle.clone());
+
+    // Keep the stream alive until told to stop
+    while !should_stop.load(Ordering::Acquire) {
+        thread::sleep(std::time::Duration::from_millis(100));
+
+        if stream.is_none() {
+            continue;
+        }
+        let Some(reason) = stall.check() else {
+            continue;
+        };
+
+        // The dead stream is dropped before opening another one
+        stream = None;
+        let lost = setup
+            .active_device
+            .lock()
+            .ok()
+            .and_then(|device| device.clone());
+        let (device_id, device_name) = lost
+            .map(|device| (device.id, device.name))
+            .unwrap_or_default();
+        warn!("Input device '{}' lost ({})", device_name, reason);
+
+        let fallback_device = if setup.failover {
+            match fail_over(&setup, &device_id) {
+                Ok((new_stream, new_health, active)) => {
+                    info!("Recording continues on '{}'", active.name);
+                    stream = Some(new_stream);
+                    stall = StallDetector::new(new_health);
+                    monitor.rebase();
+                    Some(active)
+                }
+                Err(e) => {
+                    warn!("Failed to switch to the default input: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        if fallback_device.is_none() {
+            if let Ok(mut status) = setup.status.lock() {
+                status.fail(format!("Input device '{}' lost ({})", device_name, reason));
+            }
+        }
+
+        let _ = setup.app_handle.emit(
+            "device-lost",
+            DeviceLostEvent {
+                device_id,
+                device_name,
+                reason: reason.to_string(),
+                fallback_device,
+            },
+        );
+    }
+    // Stream drops here, which stops it
+    drop(stream);
+}
+
+/// Open the default input in place of the lost device `lost_id`
+fn fail_over(
+    setup: &StreamSetup,
+    lost_id: &str,
+) -> Result<(Stream, Arc<StreamHealth>, ActiveDevice)> {
+    let (id, device) = devices::default_device()?;
+    if id == lost_id {
+        return Err("The lost device is the default input".to_string());
+    }
+
+    let config = get_optimal_config(&device, setup.preferred_sample_rate)?;
+    let converter = FormatConverter::new(
+        config.sample_rate().0,
+        config.channels(),
+        setup.output_rate,
+        setup.output_channels,
+    );
+    setup.sample_tx.reformat(converter)?;
+
+    let health = Arc::new(StreamHealth::default());
+    let stream = open_stream(&device, &config, &setup.sample_tx, &health)?;
+
+    let active = ActiveDevice {
+        id,
+        name: device.name().unwrap_or_default(),
+        format: AudioFormat {
+            sample_rate: config.sample_rate().0,
+            channels: config.channels(),
+            sample_format: sample_format_name(config.sample_format()),
+        },
+    };
+    if let Ok(mut current) = setup.active_device.lock() {
+        *current = Some(active.clone());
+    }
+    Ok((stream, health, active))
+}
+
 impl Drop for StreamHolder {
     fn drop(&mut self) {
         self.stop();
@@ -82,10 +258,22 @@ impl Drop for StreamHolder {
 /// Simplified recorder state
 pub struct RecorderState {
     stream_holder: Option<StreamHolder>,
-    writer: Option<Arc<Mutex<WavWriter>>>,
+    writer: Option<Arc<Mutex<Box<dyn AudioEncoder>>>>,
+    pipeline: Option<AudioPipeline>,
+    vad: Option<Arc<Mutex<VoiceActivityDetector>>>,
     is_recording: Arc<AtomicBool>,
+    is_paused: Arc<AtomicBool>,
+    pauses: Vec<PauseMarker>,
+    paused_at: Option<(Instant, f32)>, // When and at which offset the open pause began
+    auto_stopped: Option<AudioRecording>, // Finalized on its own, not yet picked up by stop_recording
+    recorded: bool,                    // The session's file holds a finished recording
+    limit_reached: Arc<AtomicBool>,    // A limit cut the current recording
+    audio_tap: AudioTap,
     sample_rate: u32,
     channels: u16,
+    device: SharedDevice,
+    status: SharedStatus,
+    format: RecordingFormat,
     file_path: Option<PathBuf>,
 }
 
@@ -94,105 +282,203 @@ impl RecorderState {
         Self {
             stream_holder: None,
             writer: None,
+            pipeline: None,
+            vad: None,
             is_recording: Arc::new(AtomicBool::new(false)),
+            is_paused: Arc::new(AtomicBool::new(false)),
+            pauses: Vec::new(),
+            paused_at: None,
+            auto_stopped: None,
+            recorded: false,
+            limit_reached: Arc::new(AtomicBool::new(false)),
+            audio_tap: Arc::new(Mutex::new(None)),
             sample_rate: 0,
             channels: 0,
+            device: Arc::new(Mutex::new(None)),
+            status: SharedStatus::default(),
+            format: RecordingFormat::default(),
             file_path: None,
         }
     }
 
-    /// List available recording devices by name
-    pub fn enumerate_devices(&self) -> Result<Vec<String>> {
-        let host = cpal::default_host();
-        let devices = host
-            .input_devices()
-            .map_err(|e| format!("Failed to get input devices: {}", e))?
-            .filter_map(|device| device.name().ok())
-            .collect();
-
-        Ok(devices)
+    /// List available recording devices
+    pub fn enumerate_devices(&self) -> Result<Vec<RecordingDevice>> {
+        devices::enumerate_devices()
     }
 
-    /// Initialize recording session - creates stream and WAV writer
+    /// Initialize recording session - creates stream and file writer
     pub fn init_session(
         &mut self,
-        device_name: String,
+        device_identifier: String,
         output_folder: PathBuf,
         recording_id: String,
-        preferred_sample_rate: Option<u32>,
+        config: SessionConfig,
+        app_handle: AppHandle,
     ) -> Result<()> {
         // Clean up any existing session
         self.close_session()?;
+        if let Ok(mut status) = self.status.lock() {
+            status.set_app_handle(app_handle.clone());
+        }
 
         // Create file path
-        let file_path = output_folder.join(format!("{}.wav", recording_id));
+        let file_path =
+            output_folder.join(format!("{}.{}", recording_id, config.format.extension()));
 
         // Find the device
-        let host = cpal::default_host();
-        let device = find_device(&host, &device_name)?;
+        let (device_id, device) = devices::find_device(&device_identifier)?;
 
         // Get optimal config for voice with optional preferred sample rate
-        let config = get_optimal_config(&device, preferred_sample_rate)?;
-        let sample_format = config.sample_format();
-        let sample_rate = config.sample_rate().0;
-        let channels = config.channels();
-
-        // Create WAV writer
-        let writer = WavWriter::new(file_path.clone(), sample_rate, channels)
-            .map_err(|e| format!("Failed to create WAV file: {}", e))?;
-        let writer = Arc::new(Mutex::new(writer));
-
-        // Create stream config
-        let stream_config = cpal::StreamConfig {
-            channels,
-            sample_rate: cpal::SampleRate(sample_rate),
-            buffer_size: cpal::BufferSize::Default,
+        let device_config = get_optimal_config(&device, config.preferred_sample_rate)?;
+        let sample_format = device_config.sample_format();
+        let device_sample_rate = device_config.sample_rate().0;
+        let device_channels = device_config.channels();
+
+        // Output format is either the device format or canonical 16 kHz mono
+        let (sample_rate, channels) = if config.canonical_output {
+            (CANONICAL_SAMPLE_RATE, CANONICAL_CHANNELS)
+        } else {
+            (device_sample_rate, device_channels)
         };
+        // ...adjusted to what the file format can store
+        let (sample_rate, channels) = config.format.supported_output(sample_rate, channels);
+        let converter =
+            FormatConverter::new(device_sample_rate, device_channels, sample_rate, channels);
+
+        // Create the file writer for the requested format
+        let writer = create_encoder(config.format, file_path.clone(), sample_rate, channels)
+            .map_err(|e| format!("Failed to create recording file: {}", e))?;
+        let writer = Arc::new(Mutex::new(writer));
 
         // Create fresh recording flag
         self.is_recording = Arc::new(AtomicBool::new(false));
         let is_recording = self.is_recording.clone();
+        self.is_paused = Arc::new(AtomicBool::new(false));
+
+        // Optional voice activity detection, shared with the processing thread
+        let vad = config.vad.map(|vad_config| {
+            Arc::new(Mutex::new(VoiceActivityDetector::new(
+                vad_config,
+                sample_rate,
+                channels,
+            )))
+        });
 
-        // Create the stream holder with a closure that builds the stream
-        let writer_clone = writer.clone();
-        let is_recording_clone = is_recording.clone();
+        // Optional live chunks for the frontend, in the same format as the file
+        let streamer = config.streaming.map(|mut streaming| {
+            if streaming.config.segment_by_vad && vad.is_none() {
+                warn!("VAD-segmented streaming needs VAD enabled, using fixed-length chunks");
+                streaming.config.segment_by_vad = false;
+            }
+            ChunkStreamer::new(streaming, sample_rate, channels)
+        });
 
-        let stream_holder = StreamHolder::new(
-            move || match sample_format {
-                SampleFormat::F32 => {
-                    build_stream_f32(&device, &stream_config, is_recording_clone, writer_clone)
-                }
-                SampleFormat::I16 => {
-                    build_stream_i16(&device, &stream_config, is_recording_clone, writer_clone)
-                }
-                SampleFormat::U16 => {
-                    build_stream_u16(&device, &stream_config, is_recording_clone, writer_clone)
-                }
-                _ => Err("Unsupported sample format".to_string()),
+        // Processing thread does metering, VAD, writing and streaming off the audio callback
+        let (pipeline, sample_tx) = AudioPipeline::spawn(PipelineContext {
+            app_handle: app_handle.clone(),
+            writer: writer.clone(),
+            is_recording: is_recording.clone(),
+            is_paused: self.is_paused.clone(),
+            vad: vad.clone(),
+            converter,
+            streamer,
+            audio_tap: self.audio_tap.clone(),
+            sample_rate,
+            channels,
+            pre_roll_ms: config.pre_roll_ms,
+            limits: config
+                .limits
+                .map(|limits| LimitTracker::new(limits, sample_rate, channels)),
+            limit_reached: self.limit_reached.clone(),
+            on_auto_stop: config.on_auto_stop,
+        });
+
+        // What the stream thread reports as lost, updated when it fails over
+        let active_device = Arc::new(Mutex::new(Some(ActiveDevice {
+            id: device_id,
+            name: device.name().unwrap_or_default(),
+            format: AudioFormat {
+                sample_rate: device_sample_rate,
+                channels: device_channels,
+                sample_format: sample_format_name(sample_format),
             },
-            is_recording,
-        )?;
+        })));
+
+        let setup = StreamSetup {
+            app_handle,
+            device,
+            config: device_config,
+            sample_tx,
+            active_device: active_device.clone(),
+            status: self.status.clone(),
+            failover: config.failover,
+            preferred_sample_rate: config.preferred_sample_rate,
+            output_rate: sample_rate,
+            output_channels: channels,
+        };
+        let stream_holder = match StreamHolder::new(setup, is_recording) {
+            Ok(holder) => holder,
+            Err(e) => {
+                // No audio will ever reach the file - don't leave it behind
+                drop(writer);
+                std::fs::remove_file(&file_path).ok();
+                return Err(e);
+            }
+        };
 
         // Store everything
         self.stream_holder = Some(stream_holder);
         self.writer = Some(writer);
+        self.pipeline = Some(pipeline);
+        self.vad = vad;
         self.sample_rate = sample_rate;
         self.channels = channels;
+        self.device = active_device;
+        self.format = config.format;
         self.file_path = Some(file_path);
 
         info!(
-            "Recording session initialized: {} Hz, {} channels, file: {:?}",
-            sample_rate, channels, self.file_path
+            "Recording session initialized: device {} Hz / {} channels, output {:?} {} Hz / {} channels, file: {:?}",
+            device_sample_rate, device_channels, self.format, sample_rate, channels, self.file_path
         );
 
-        Ok(())
+        self.transition(RecorderStatus::SessionReady)
     }
 
-    /// Start recording - just set the flag
-    pub fn start_recording(&mut self) -> Result<()> {
+    /// Start recording - just set the flag (the pipeline prepends any pre-roll).
+    ///
+    /// The session can stay open between recordings so the pre-roll keeps filling. Every
+    /// recording after the first needs a new `recording_id`, which gets its own file.
+    pub fn start_recording(&mut self, recording_id: Option<String>) -> Result<()> {
         if self.stream_holder.is_none() {
             return Err("No recording session initialized".to_string());
         }
+        if self.is_paused.load(Ordering::Acquire) {
+            return Err("Recording is paused, resume it instead".to_string());
+        }
+        self.check_transition(RecorderStatus::Recording)?;
+
+        match recording_id {
+            Some(id) if self.session_id().as_deref() != Some(id.as_str()) => self.open_file(&id)?,
+            _ if self.recorded => {
+                return Err(
+                    "The session already recorded, start with a new recording ID".to_string(),
+                )
+            }
+            _ => {}
+        }
+        self.transition(RecorderStatus::Recording)?;
+        self.pauses.clear();
+        self.paused_at = None;
+        self.auto_stopped = None;
+        self.limit_reached.store(false, Ordering::Release);
+
+        // Fresh speech tracking for this recording
+        if let Some(vad) = &self.vad {
+            if let Ok(mut vad) = vad.lock() {
+                vad.reset();
+            }
+        }
 
         self.is_recording.store(true, Ordering::Release);
 
@@ -200,18 +486,201 @@ impl RecorderState {
         Ok(())
     }
 
+    /// Point the session's writer at a new file `<recording_id>.<ext>` in the same folder
+    fn open_file(&mut self, recording_id: &str) -> Result<()> {
+        let (Some(writer), Some(old_path)) = (&self.writer, &self.file_path) else {
+            return Err("No recording session initialized".to_string());
+        };
+        let file_path =
+            old_path.with_file_name(format!("{}.{}", recording_id, self.format.extension()));
+        let encoder = create_encoder(
+            self.format,
+            file_path.clone(),
+            self.sample_rate,
+            self.channels,
+        )
+        .map_err(|e| format!("Failed to create recording file: {}", e))?;
+
+        *writer
+            .lock()
+            .map_err(|e| format!("Failed to lock writer: {}", e))? = encoder;
+
+        // A file that never received a recording is only a header
+        if !self.recorded {
+            std::fs::remove_file(old_path).ok();
+        }
+        debug!("Recording file switched to {:?}", file_path);
+        self.file_path = Some(file_path);
+        self.recorded = false;
+        Ok(())
+    }
+
+    /// Pause recording - audio is dropped until `resume_recording`, into the same file
+    pub fn pause_recording(&mut self) -> Result<()> {
+        self.transition(RecorderStatus::Paused)?;
+        self.is_paused.store(true, Ordering::Release);
+
+        // Taking the writer lock waits out a write in progress, so the offset is final
+        let offset = self.written_seconds()?;
+        self.paused_at = Some((Instant::now(), offset));
+
+        info!("Recording paused at {:.2}s", offset);
+        Ok(())
+    }
+
+    /// Resume a paused recording
+    pub fn resume_recording(&mut self) -> Result<()> {
+        self.transition(RecorderStatus::Recording)?;
+        self.close_pause();
+        self.is_paused.store(false, Ordering::Release);
+
+        info!("Recording resumed");
+        Ok(())
+    }
+
+    /// Record the open pause, if any
+    fn close_pause(&mut self) {
+        if let Some((since, offset_seconds)) = self.paused_at.take() {
+            self.pauses.push(PauseMarker {
+                offset_seconds,
+                paused_seconds: since.elapsed().as_secs_f32(),
+            });
+        }
+    }
+
+    /// Duration of the audio written so far
+    fn written_seconds(&self) -> Result<f32> {
+        match &self.writer {
+            Some(writer) => Ok(writer
+                .lock()
+                .map_err(|e| format!("Failed to lock writer: {}", e))?
+                .get_metadata()
+                .2),
+            None => Ok(0.0),
+        }
+    }
+
     /// Stop recording - return file info
     pub fn stop_recording(&mut self) -> Result<AudioRecording> {
+        // Already finalized by a limit or the silence timeout
+        if let Some(recording) = self.auto_stopped.take() {
+            return Ok(recording);
+        }
+
+        let (status, error) = {
+            let tracker = self
+                .status
+                .lock()
+                .map_err(|e| format!("Failed to lock recorder status: {}", e))?;
+            (tracker.status(), tracker.error())
+        };
+        // Nothing is being recorded: report an empty recording, as before statuses existed
+        if matches!(status, RecorderStatus::Idle | RecorderStatus::SessionReady) {
+            return Ok(self.empty_recording());
+        }
+
+        self.transition(RecorderStatus::Finalizing)?;
+
         // Stop recording flag first
         self.is_recording.store(false, Ordering::Release);
+        self.is_paused.store(false, Ordering::Release);
+        self.close_pause();
+
+        if let Some(pipeline) = &self.pipeline {
+            let dropped = pipeline.take_dropped_buffers();
+            if dropped > 0 {
+                warn!("Audio processing fell behind, dropped {} buffers", dropped);
+            }
+        }
+
+        let result = self.finalize_recording();
+        self.recorded = true;
+        match &result {
+            // A broken session stays broken once its audio is saved, until it is closed
+            Ok(_) if status == RecorderStatus::Error => {
+                self.fail(error.unwrap_or_else(|| "Recording session failed".to_string()))
+            }
+            Ok(_) => self.transition(RecorderStatus::SessionReady)?,
+            Err(e) => self.fail(e.clone()),
+        }
+        result
+    }
+
+    /// What `stop_recording` returns when there is no recording to stop. The session's
+    /// file is only included while it is an unused header, so it can be deleted.
+    fn empty_recording(&self) -> AudioRecording {
+        AudioRecording {
+            audio_data: Vec::new(),
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            duration_seconds: 0.0,
+            paused_seconds: 0.0,
+            pauses: Vec::new(),
+            truncated: false,
+            file_path: self
+                .file_path
+                .as_ref()
+                .filter(|_| !self.recorded)
+                .map(|p| p.to_string_lossy().to_string()),
+            mime_type: self.format.mime_type().to_string(),
+            device_format: self.device_format(),
+            output_format: None,
+        }
+    }
+
+    /// Stop a recording that hit a limit or the silence timeout, keeping the result for
+    /// `stop_recording`. None when that recording has already been stopped.
+    pub fn finalize_auto_stopped(&mut self) -> Result<Option<AudioRecording>> {
+        let status = self
+            .status
+            .lock()
+            .map_err(|e| format!("Failed to lock recorder status: {}", e))?
+            .status();
+        let recording = matches!(status, RecorderStatus::Recording | RecorderStatus::Paused);
+        let truncated = self.limit_reached.load(Ordering::Acquire);
+        let timed_out = self
+            .vad
+            .as_ref()
+            .and_then(|vad| vad.lock().ok())
+            .is_some_and(|vad| vad.timed_out());
+        if !recording || !(truncated || timed_out) {
+            return Ok(None);
+        }
+
+        let mut recording = self.stop_recording()?;
+        recording.truncated = truncated;
+        self.auto_stopped = Some(recording.clone());
+        Ok(Some(recording))
+    }
+
+    /// Finalize the file (and trim silence) and describe it
+    fn finalize_recording(&mut self) -> Result<AudioRecording> {
+        // Speech region first: the processing thread locks the VAD before the writer, so
+        // taking them the other way round here could deadlock
+        let speech_region = self.speech_region_to_keep();
 
-        // Finalize the WAV file and get metadata
+        // Finalize the file and get metadata
+        let mut trimmed_start = 0;
         let (sample_rate, channels, duration) = if let Some(writer) = &self.writer {
             let mut w = writer
                 .lock()
                 .map_err(|e| format!("Failed to lock writer: {}", e))?;
             w.finalize()
-                .map_err(|e| format!("Failed to finalize WAV: {}", e))?;
+                .map_err(|e| format!("Failed to finalize recording: {}", e))?;
+
+            // Cut leading/trailing silence detected by the VAD (uncompressed formats only)
+            if let Some((start, end)) = speech_region {
+                match w.trim(start, end) {
+                    Err(e) if e.kind() == std::io::ErrorKind::Unsupported => {
+                        debug!("Skipping silence trim for {:?} recording", self.format);
+                    }
+                    result => {
+                        result.map_err(|e| format!("Failed to trim silence: {}", e))?;
+                        trimmed_start = start;
+                    }
+                }
+            }
+
             w.get_metadata()
         } else {
             (self.sample_rate, self.channels, 0.0)
@@ -222,14 +691,47 @@ impl RecorderState {
             .as_ref()
             .map(|p| p.to_string_lossy().to_string());
 
-        info!("Recording stopped: {:.2}s, file: {:?}", duration, file_path);
+        // Pause offsets move with the trimmed start
+        let trimmed_seconds = trimmed_start as f32 / sample_rate.max(1) as f32;
+        let pauses: Vec<PauseMarker> = self
+            .pauses
+            .iter()
+            .map(|pause| PauseMarker {
+                offset_seconds: (pause.offset_seconds - trimmed_seconds).clamp(0.0, duration),
+                paused_seconds: pause.paused_seconds,
+            })
+            .collect();
+        if !pauses.is_empty() {
+            if let Some(path) = &self.file_path {
+                write_pauses_sidecar(path, &pauses)?;
+            }
+        }
+        let paused_seconds = pauses.iter().map(|pause| pause.paused_seconds).sum();
+
+        info!(
+            "Recording stopped: {:.2}s ({} pauses, {:.2}s paused), file: {:?}",
+            duration,
+            pauses.len(),
+            paused_seconds,
+            file_path
+        );
 
         Ok(AudioRecording {
             audio_data: Vec::new(), // Empty for file-based recording
             sample_rate,
             channels,
             duration_seconds: duration,
+            paused_seconds,
+            pauses,
+            truncated: false,
             file_path,
+            mime_type: self.format.mime_type().to_string(),
+            device_format: self.device_format(),
+            output_format: Some(AudioFormat {
+                sample_rate,
+                channels,
+                sample_format: self.format.sample_format().to_string(),
+            }),
         })
     }
 
@@ -238,9 +740,10 @@ impl RecorderState {
         // Stop recording
         self.is_recording.store(false, Ordering::Release);
 
-        // Delete the file if it exists
+        // Delete the file (and any pauses sidecar) if it exists
         if let Some(file_path) = &self.file_path {
             std::fs::remove_file(file_path).ok(); // Ignore errors
+            std::fs::remove_file(pauses_sidecar_path(file_path)).ok();
             debug!("Deleted recording file: {:?}", file_path);
         }
 
@@ -254,12 +757,28 @@ impl RecorderState {
     pub fn close_session(&mut self) -> Result<()> {
         // Stop recording if active
         self.is_recording.store(false, Ordering::Release);
+        self.is_paused.store(false, Ordering::Release);
+        self.pauses.clear();
+        self.paused_at = None;
+        self.auto_stopped = None;
+        self.recorded = false;
 
         // Stop and drop the stream holder
         if let Some(mut holder) = self.stream_holder.take() {
             holder.stop();
         }
 
+        // The stream owned the sample sender, so the processing thread exits now
+        if let Some(mut pipeline) = self.pipeline.take() {
+            pipeline.join();
+        }
+        self.vad = None;
+
+        // Tap consumers see their channel close and wrap up
+        if let Ok(mut tap) = self.audio_tap.lock() {
+            *tap = None;
+        }
+
         // Finalize and drop the writer
         if let Some(writer) = self.writer.take() {
             if let Ok(mut w) = writer.lock() {
@@ -271,46 +790,146 @@ impl RecorderState {
         self.file_path = None;
         self.sample_rate = 0;
         self.channels = 0;
+        self.device = Arc::new(Mutex::new(None));
+        self.transition(RecorderStatus::Idle)?;
 
         debug!("Recording session closed");
         Ok(())
     }
 
+    /// Status, device and progress of the session
+    pub fn get_status(&self) -> Result<RecorderStatusInfo> {
+        let (status, error) = {
+            let tracker = self
+                .status
+                .lock()
+                .map_err(|e| format!("Failed to lock recorder status: {}", e))?;
+            (tracker.status(), tracker.error())
+        };
+
+        let (elapsed_seconds, bytes_written) = match &self.writer {
+            Some(writer) => {
+                let w = writer
+                    .lock()
+                    .map_err(|e| format!("Failed to lock writer: {}", e))?;
+                (w.get_metadata().2, w.bytes_written())
+            }
+            None => (0.0, 0),
+        };
+
+        Ok(RecorderStatusInfo {
+            status,
+            recording_id: self.session_id(),
+            device: self.device.lock().ok().and_then(|device| device.clone()),
+            output_format: self.writer.as_ref().map(|_| AudioFormat {
+                sample_rate: self.sample_rate,
+                channels: self.channels,
+                sample_format: self.format.sample_format().to_string(),
+            }),
+            elapsed_seconds,
+            bytes_written,
+            error,
+        })
+    }
+
+    /// Fail unless `next` is a valid transition
+    fn check_transition(&self, next: RecorderStatus) -> Result<()> {
+        self.status
+            .lock()
+            .map_err(|e| format!("Failed to lock recorder status: {}", e))?
+            .check(next)
+    }
+
+    /// Move to `next`, if that is a valid transition
+    fn transition(&self, next: RecorderStatus) -> Result<()> {
+        self.status
+            .lock()
+            .map_err(|e| format!("Failed to lock recorder status: {}", e))?
+            .transition(next)
+    }
+
+    fn fail(&self, message: String) {
+        if let Ok(mut status) = self.status.lock() {
+            status.fail(message);
+        }
+    }
+
+    /// Format of the device currently feeding the session
+    fn device_format(&self) -> Option<AudioFormat> {
+        let device = self.device.lock().ok()?;
+        device.as_ref().map(|device| device.format.clone())
+    }
+
+    /// Get the most recent input level (silence when no session is open)
+    pub fn get_input_level(&self) -> AudioLevel {
+        self.pipeline
+            .as_ref()
+            .map(|pipeline| pipeline.latest_level())
+            .unwrap_or_else(AudioLevel::silent)
+    }
+
+    /// Frames to keep when silence trimming is enabled and speech was heard
+    fn speech_region_to_keep(&self) -> Option<(u64, u64)> {
+        let vad = self.vad.as_ref()?.lock().ok()?;
+        if !vad.config().trim_silence {
+            return None;
+        }
+        vad.speech_region(self.sample_rate)
+    }
+
+    /// Receive the session's recorded audio (output rate and channels, returned alongside).
+    ///
+    /// Replaces any previous consumer. Audio flows while recording and a `TapMessage::End`
+    /// follows every recording.
+    pub fn attach_audio_tap(&self) -> Result<(UnboundedReceiver<TapMessage>, u32, u16)> {
+        if self.writer.is_none() {
+            return Err("No recording session initialized".to_string());
+        }
+
+        let (sender, receiver) = unbounded_channel();
+        let mut tap = self
+            .audio_tap
+            .lock()
+            .map_err(|e| format!("Failed to lock audio tap: {}", e))?;
+        *tap = Some(sender);
+        Ok((receiver, self.sample_rate, self.channels))
+    }
+
+    /// Path of the session's file, recording or not
+    pub fn get_file_path(&self) -> Option<PathBuf> {
+        self.file_path.clone()
+    }
+
     /// Get current recording ID if actively recording
     pub fn get_current_recording_id(&self) -> Option<String> {
         if self.is_recording.load(Ordering::Acquire) {
-            self.file_path
-                .as_ref()
-                .and_then(|path| path.file_stem())
-                .and_then(|stem| stem.to_str())
-                .map(|s| s.to_string())
+            self.session_id()
         } else {
             None
         }
     }
-}
 
-/// Find a recording device by name
-fn find_device(host: &cpal::Host, device_name: &str) -> Result<Device> {
-    // Handle "default" device
-    if device_name.to_lowercase() == "default" {
-        return host
-            .default_input_device()
-            .ok_or_else(|| "No default input device available".to_string());
+    /// Recording ID of the open session (the file stem)
+    fn session_id(&self) -> Option<String> {
+        self.file_path
+            .as_ref()
+            .and_then(|path| path.file_stem())
+            .and_then(|stem| stem.to_str())
+            .map(|s| s.to_string())
     }
+}
 
-    // Find specific device
-    let devices: Vec<_> = host.input_devices().map_err(|e| e.to_string())?.collect();
-
-    for device in devices {
-        if let Ok(name) = device.name() {
-            if name == device_name {
-                return Ok(device);
-            }
-        }
-    }
+/// `<id>.pauses.json` next to the recording file
+fn pauses_sidecar_path(file_path: &Path) -> PathBuf {
+    file_path.with_extension("pauses.json")
+}
 
-    Err(format!("Device '{}' not found", device_name))
+/// Save the pause markers next to the recording, for readers of the file alone
+fn write_pauses_sidecar(file_path: &Path, pauses: &[PauseMarker]) -> Result<()> {
+    let json = serde_json::to_vec_pretty(pauses)
+        .map_err(|e| format!("Failed to serialize pause markers: {}", e))?;
+    std::fs::write(pauses_sidecar_path(file_path), json)
+        .map_err(|e| format!("Failed to write pause markers: {}", e))
 }
 
 /// Get optimal configuration for voice recording
@@ -383,24 +1002,54 @@ fn get_optimal_config(
         .ok_or_else(|| "Failed to find suitable audio configuration".to_string())
 }
 
+/// Build and start an input stream for `config` on `device`
+fn open_stream(
+    device: &Device,
+    config: &cpal::SupportedStreamConfig,
+    sample_tx: &PipelineSender,
+    health: &Arc<StreamHealth>,
+) -> Result<Stream> {
+    let stream_config = cpal::StreamConfig {
+        channels: config.channels(),
+        sample_rate: config.sample_rate(),
+        buffer_size: cpal::BufferSize::Default,
+    };
+    let (sample_tx, health) = (sample_tx.clone(), health.clone());
+
+    match config.sample_format() {
+        SampleFormat::F32 => build_stream_f32(device, &stream_config, sample_tx, health),
+        SampleFormat::I16 => build_stream_i16(device, &stream_config, sample_tx, health),
+        SampleFormat::U16 => build_stream_u16(device, &stream_config, sample_tx, health),
+        _ => Err("Unsupported sample format".to_string()),
+    }
+}
+
+/// Log stream errors and flag the stream when its device went away
+fn error_callback(health: Arc<StreamHealth>) -> impl FnMut(StreamError) + Send + 'static {
+    move |err| {
+        error!("Audio stream error: {}", err);
+        if matches!(err, StreamError::DeviceNotAvailable) {
+            health.mark_disconnected();
+        }
+    }
+}
+
 /// Build stream for f32 samples
 fn build_stream_f32(
     device: &Device,
     config: &cpal::StreamConfig,
-    is_recording: Arc<AtomicBool>,
-    writer: Arc<Mutex<WavWriter>>,
+    sample_tx: PipelineSender,
+    health: Arc<StreamHealth>,
 ) -> Result<Stream> {
-    let err_fn = |err| error!("Audio stream error: {}", err);
+    let err_fn = error_callback(health.clone());
 
     let stream = device
         .build_input_stream(
             config,
             move |data: &[f32], _: &_| {
-                if is_recording.load(Ordering::Acquire) {
-                    if let Ok(mut w) = writer.lock() {
-                        let _ = w.write_samples_f32(data);
-                    }
-                }
+                // Hand the buffer to the processing thread
+                health.beat();
+                sample_tx.send_audio(data.to_vec());
             },
             err_fn,
             None,
@@ -419,20 +1068,19 @@ fn build_stream_f32(
 fn build_stream_i16(
     device: &Device,
     config: &cpal::StreamConfig,
-    is_recording: Arc<AtomicBool>,
-    writer: Arc<Mutex<WavWriter>>,
+    sample_tx: PipelineSender,
+    health: Arc<StreamHealth>,
 ) -> Result<Stream> {
-    let err_fn = |err| error!("Audio stream error: {}", err);
+    let err_fn = error_callback(health.clone());
 
     let stream = device
         .build_input_stream(
             config,
             move |data: &[i16], _: &_| {
-                if is_recording.load(Ordering::Acquire) {
-                    if let Ok(mut w) = writer.lock() {
-                        let _ = w.write_samples_i16(data);
-                    }
-                }
+                // Convert to f32 and hand the buffer to the processing thread
+                let samples = data.iter().map(|&s| s as f32 / i16::MAX as f32).collect();
+                health.beat();
+                sample_tx.send_audio(samples);
             },
             err_fn,
             None,
@@ -451,20 +1099,22 @@ fn build_stream_i16(
 fn build_stream_u16(
     device: &Device,
     config: &cpal::StreamConfig,
-    is_recording: Arc<AtomicBool>,
-    writer: Arc<Mutex<WavWriter>>,
+    sample_tx: PipelineSender,
+    health: Arc<StreamHealth>,
 ) -> Result<Stream> {
-    let err_fn = |err| error!("Audio stream error: {}", err);
+    let err_fn = error_callback(health.clone());
 
     let stream = device
         .build_input_stream(
             config,
             move |data: &[u16], _: &_| {
-                if is_recording.load(Ordering::Acquire) {
-                    if let Ok(mut w) = writer.lock() {
-                        let _ = w.write_samples_u16(data);
-                    }
-                }
+                // Convert to f32 and hand the buffer to the processing thread
+                let samples = data
+                    .iter()
+                    .map(|&s| (s as f32 / u16::MAX as f32) * 2.0 - 1.0)
+                    .collect();
+                health.beat();
+                sample_tx.send_audio(samples);
             },
             err_fn,
             None,
@@ -484,3 +1134,138 @@ impl Drop for RecorderState {
         let _ = self.close_session();
     }
 }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 1000;
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "noteflux-recorder-{}-{}.wav",
+            name,
+            std::process::id()
+        ))
+    }
+
+    /// A recording session on a WAV file, without a device
+    fn recording_session(name: &str) -> RecorderState {
+        let path = temp_file(name);
+        let encoder = create_encoder(RecordingFormat::WavFloat, path.clone(), RATE, 1).unwrap();
+        let mut state = RecorderState::new();
+        state.writer = Some(Arc::new(Mutex::new(encoder)));
+        state.file_path = Some(path);
+        state.sample_rate = RATE;
+        state.channels = 1;
+        state.format = RecordingFormat::WavFloat;
+        state.transition(RecorderStatus::SessionReady).unwrap();
+        state.transition(RecorderStatus::Recording).unwrap();
+        state.is_recording.store(true, Ordering::Release);
+        state
+    }
+
+    /// Write `seconds` of audio, as the processing thread would
+    fn record(state: &RecorderState, seconds: f32) {
+        let samples = vec![0.1; (seconds * RATE as f32) as usize];
+        let writer = state.writer.as_ref().unwrap();
+        writer.lock().unwrap().write_samples(&samples).unwrap();
+    }
+
+    fn status(state: &RecorderState) -> RecorderStatus {
+        state.status.lock().unwrap().status()
+    }
+
+    fn cleanup(path: &Path) {
+        std::fs::remove_file(path).ok();
+        std::fs::remove_file(pauses_sidecar_path(path)).ok();
+    }
+
+    #[test]
+    fn pause_and_resume_need_the_right_state() {
+        let mut idle = RecorderState::new();
+        assert!(idle.pause_recording().is_err());
+        assert!(idle.resume_recording().is_err());
+
+        let mut state = recording_session("pause-states");
+        let path = state.file_path.clone().unwrap();
+        assert!(state.resume_recording().is_err());
+
+        state.pause_recording().unwrap();
+        assert_eq!(status(&state), RecorderStatus::Paused);
+        assert!(state.is_paused.load(Ordering::Acquire));
+        assert!(state.pause_recording().is_err());
+
+        state.resume_recording().unwrap();
+        assert_eq!(status(&state), RecorderStatus::Recording);
+        assert!(!state.is_paused.load(Ordering::Acquire));
+        assert!(state.resume_recording().is_err());
+
+        drop(state);
+        cleanup(&path);
+    }
+
+    #[test]
+    fn pauses_are_marked_at_their_offset_in_the_file() {
+        let mut state = recording_session("pause-markers");
+        let path = state.file_path.clone().unwrap();
+
+        record(&state, 1.0);
+        state.pause_recording().unwrap();
+        state.resume_recording().unwrap();
+        record(&state, 0.5);
+        state.pause_recording().unwrap();
+        state.resume_recording().unwrap();
+        record(&state, 0.25);
+
+        let recording = state.stop_recording().unwrap();
+        assert_eq!(recording.duration_seconds, 1.75);
+        let offsets: Vec<f32> = recording.pauses.iter().map(|p| p.offset_seconds).collect();
+        assert_eq!(offsets, [1.0, 1.5]);
+        let paused: f32 = recording.pauses.iter().map(|p| p.paused_seconds).sum();
+        assert_eq!(recording.paused_seconds, paused);
+
+        // The sidecar holds the same markers, in the frontend's field names
+        let sidecar: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(pauses_sidecar_path(&path)).unwrap()).unwrap();
+        let markers = sidecar.as_array().unwrap();
+        assert_eq!(markers.len(), 2);
+        assert_eq!(markers[0]["offsetSeconds"], 1.0);
+        assert_eq!(markers[1]["offsetSeconds"], 1.5);
+        assert!(markers[0]["pausedSeconds"].is_number());
+
+        drop(state);
+        cleanup(&path);
+    }
+
+    #[test]
+    fn stopping_while_paused_closes_the_pause() {
+        let mut state = recording_session("pause-stop");
+        let path = state.file_path.clone().unwrap();
+
+        record(&state, 0.5);
+        state.pause_recording().unwrap();
+        let recording = state.stop_recording().unwrap();
+        assert_eq!(recording.pauses.len(), 1);
+        assert_eq!(recording.pauses[0].offset_seconds, 0.5);
+        assert_eq!(status(&state), RecorderStatus::SessionReady);
+        assert!(!state.is_paused.load(Ordering::Acquire));
+
+        drop(state);
+        cleanup(&path);
+    }
+
+    #[test]
+    fn no_sidecar_without_pauses() {
+        let mut state = recording_session("pause-none");
+        let path = state.file_path.clone().unwrap();
+
+        record(&state, 0.5);
+        let recording = state.stop_recording().unwrap();
+        assert!(recording.pauses.is_empty());
+        assert_eq!(recording.paused_seconds, 0.0);
+        assert!(!pauses_sidecar_path(&path).exists());
+
+        drop(state);
+        cleanup(&path);
+    }
+}
@@ -0,0 +1,308 @@
+use crate::recorder::wav_writer::{write_size_fields, DATA_SIZE_POS, DS64_POS};
+use serde::Serialize;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// A recording whose WAV/RF64 headers were stale (e.g. the app crashed mid-recording) and were
+/// repaired - returned to frontend
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveredRecording {
+    pub recording_id: String, // File stem, same as the id passed to init_recording_session
+    pub file_path: String,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub duration_seconds: f32,
+    pub size_bytes: u64,
+}
+
+/// Layout of a WAV file as found on disk
+struct WavLayout {
+    sample_rate: u32,
+    channels: u16,
+    byte_rate: u32,
+    block_align: u16,
+    riff_size: u64,      // From ds64 in RF64 files
+    has_ds64_slot: bool, // JUNK/ds64 chunk that allows switching to RF64
+    data_size_pos: u64,
+}
+
+/// Scan `dir` for WAV files with stale size fields and repair them in place.
+///
+/// `skip` is the file of the currently open session, whose headers are legitimately
+/// out of date. Only files laid out the way `WavWriter` writes them are touched, as
+/// the folder may be user-chosen and hold other WAV files.
+pub fn recover_interrupted_recordings(dir: &Path, skip: Option<&Path>) -> Vec<RecoveredRecording> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            debug!("Skipping recording recovery in {:?}: {}", dir, e);
+            return Vec::new();
+        }
+    };
+
+    let skip = skip.map(normalize_path);
+    let mut recovered = Vec::new();
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        let is_wav = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"));
+        if !is_wav || skip.as_deref() == Some(normalize_path(&path).as_path()) {
+            continue;
+        }
+
+        match repair_wav_file(&path) {
+            Ok(Some(recording)) => recovered.push(recording),
+            Ok(None) => {}
+            Err(e) => warn!("Failed to recover recording {:?}: {}", path, e),
+        }
+    }
+
+    if !recovered.is_empty() {
+        info!(
+            "Recovered {} interrupted recording(s) in {:?}",
+            recovered.len(),
+            dir
+        );
+    }
+
+    recovered
+}
+
+/// Rewrite the RIFF and data sizes from the real file length.
+///
+/// Returns `None` when the headers were already correct, the file was not written by the
+/// recorder, or no audio made it to disk.
+pub fn repair_wav_file(path: &Path) -> io::Result<Option<RecoveredRecording>> {
+    let mut file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+
+    let layout = match read_layout(&mut file, file_len)? {
+        Some(layout) => layout,
+        None => return Ok(None),
+    };
+
+    // Our own recordings only: the reserved ds64 slot right after the RIFF header and
+    // the data chunk right after the format chunk
+    if !layout.has_ds64_slot || layout.data_size_pos != DATA_SIZE_POS {
+        return Ok(None);
+    }
+
+    // A finalized file accounts for every byte; an interrupted one still carries the
+    // placeholder sizes or those of the last periodic header update
+    if layout.riff_size + 8 == file_len {
+        return Ok(None);
+    }
+    let mut file = OpenOptions::new().write(true).open(path)?;
+
+    // Drop a partially written frame at the end
+    let data_start = layout.data_size_pos + 4;
+    let block_align = layout.block_align.max(1) as u64;
+    let data_size = (file_len - data_start) / block_align * block_align;
+    let valid_len = data_start + data_size;
+
+    if valid_len < file_len {
+        file.set_len(valid_len)?;
+    }
+    write_size_fields(
+        &mut file,
+        layout.data_size_pos,
+        data_size,
+        data_size / block_align,
+        layout.has_ds64_slot,
+    )?;
+    file.sync_all()?;
+
+    info!(
+        "Repaired WAV headers of {:?}: {} bytes of audio",
+        path, data_size
+    );
+
+    if data_size == 0 {
+        return Ok(None); // Nothing to transcribe
+    }
+
+    let recording_id = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default()
+        .to_string();
+
+    Ok(Some(RecoveredRecording {
+        recording_id,
+        file_path: path.to_string_lossy().to_string(),
+        sample_rate: layout.sample_rate,
+        channels: layout.channels,
+        duration_seconds: data_size as f32 / layout.byte_rate.max(1) as f32,
+        size_bytes: valid_len,
+    }))
+}
+
+/// Walk the RIFF chunks up to the start of the data chunk
+fn read_layout(file: &mut File, file_len: u64) -> io::Result<Option<WavLayout>> {
+    let mut header = [0u8; 12];
+    if file_len < 12 || file.read_exact(&mut header).is_err() {
+        return Ok(None);
+    }
+    let is_rf64 = match &header[0..4] {
+        b"RIFF" => false,
+        b"RF64" => true,
+        _ => return Ok(None),
+    };
+    if &header[8..12] != b"WAVE" {
+        return Ok(None);
+    }
+    let mut riff_size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as u64;
+
+    let mut format: Option<(u16, u32, u32, u16)> = None;
+    let mut has_ds64_slot = false;
+    let mut position = 12u64;
+
+    while position + 8 <= file_len {
+        let mut chunk = [0u8; 8];
+        file.seek(SeekFrom::Start(position))?;
+        file.read_exact(&mut chunk)?;
+        let size = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+
+        match &chunk[0..4] {
+            b"JUNK" | b"ds64" if position == DS64_POS && size >= 28 => {
+                has_ds64_slot = true;
+                if is_rf64 && &chunk[0..4] == b"ds64" {
+                    let mut sizes = [0u8; 8];
+                    file.read_exact(&mut sizes)?;
+                    riff_size = u64::from_le_bytes(sizes);
+                }
+            }
+            b"fmt " => {
+                let mut fmt = [0u8; 16];
+                file.read_exact(&mut fmt)?;
+                format = Some((
+                    u16::from_le_bytes([fmt[2], fmt[3]]),
+                    u32::from_le_bytes([fmt[4], fmt[5], fmt[6], fmt[7]]),
+                    u32::from_le_bytes([fmt[8], fmt[9], fmt[10], fmt[11]]),
+                    u16::from_le_bytes([fmt[12], fmt[13]]),
+                ));
+            }
+            b"data" => {
+                // The data chunk runs to the end of the file in recordings we write
+                return Ok(
+                    format.map(
+                        |(channels, sample_rate, byte_rate, block_align)| WavLayout {
+                            sample_rate,
+                            channels,
+                            byte_rate,
+                            block_align,
+                            riff_size,
+                            has_ds64_slot,
+                            data_size_pos: position + 4,
+                        },
+                    ),
+                );
+            }
+            _ => {}
+        }
+
+        // Chunks are word aligned
+        position += 8 + size as u64 + (size as u64 & 1);
+    }
+
+    Ok(None)
+}
+
+/// Resolve symlinks and relative parts so paths from different sources compare equal
+fn normalize_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recorder::wav_writer::{WavSampleFormat, WavWriter};
+    use std::io::Write;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("noteflux-recovery-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A recording whose last writes never made it into the headers
+    fn interrupted_recording(path: &Path, frames: usize, extra_bytes: usize) {
+        let mut writer =
+            WavWriter::new(path.to_path_buf(), 16000, 1, WavSampleFormat::Float32).unwrap();
+        writer.write_samples_f32(&vec![0.25; frames]).unwrap();
+        drop(writer);
+
+        let mut file = OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(&vec![0u8; extra_bytes]).unwrap();
+    }
+
+    #[test]
+    fn repairs_interrupted_recordings() {
+        let dir = temp_dir("interrupted");
+        let path = dir.join("abc123.wav");
+        interrupted_recording(&path, 1000, 1600 * 4 + 3);
+
+        let recovered = recover_interrupted_recordings(&dir, None);
+        assert_eq!(recovered.len(), 1);
+        assert_eq!(recovered[0].recording_id, "abc123");
+        assert!((recovered[0].duration_seconds - 2600.0 / 16000.0).abs() < 1e-6);
+
+        // The partial sample is gone and the headers cover the rest
+        let bytes = fs::read(&path).unwrap();
+        assert_eq!(bytes.len() as u64, DATA_SIZE_POS + 4 + 2600 * 4);
+        let riff_size = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+        assert_eq!(riff_size as usize, bytes.len() - 8);
+        let pos = DATA_SIZE_POS as usize;
+        let data_size = u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap());
+        assert_eq!(data_size, 2600 * 4);
+
+        // Repaired files are left alone from then on
+        assert!(recover_interrupted_recordings(&dir, None).is_empty());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn skips_the_open_session() {
+        let dir = temp_dir("open-session");
+        let path = dir.join("open.wav");
+        interrupted_recording(&path, 1000, 4000);
+        let before = fs::read(&path).unwrap();
+
+        assert!(recover_interrupted_recordings(&dir, Some(&path)).is_empty());
+        assert_eq!(fs::read(&path).unwrap(), before);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn leaves_other_wav_files_untouched() {
+        let dir = temp_dir("foreign");
+
+        // Canonical 44-byte header whose sizes don't match the file (e.g. from another app)
+        let mut foreign = Vec::new();
+        foreign.extend_from_slice(b"RIFF");
+        foreign.extend_from_slice(&36u32.to_le_bytes());
+        foreign.extend_from_slice(b"WAVEfmt ");
+        foreign.extend_from_slice(&16u32.to_le_bytes());
+        foreign.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        foreign.extend_from_slice(&1u16.to_le_bytes()); // Mono
+        foreign.extend_from_slice(&16000u32.to_le_bytes());
+        foreign.extend_from_slice(&32000u32.to_le_bytes());
+        foreign.extend_from_slice(&2u16.to_le_bytes());
+        foreign.extend_from_slice(&16u16.to_le_bytes());
+        foreign.extend_from_slice(b"data");
+        foreign.extend_from_slice(&0u32.to_le_bytes());
+        foreign.extend_from_slice(&[1u8; 1001]);
+        let path = dir.join("interview.wav");
+        fs::write(&path, &foreign).unwrap();
+
+        assert!(recover_interrupted_recordings(&dir, None).is_empty());
+        assert_eq!(fs::read(&path).unwrap(), foreign);
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,331 @@
+use std::f64::consts::PI;
+
+/// Sample rate most speech-to-text models are trained on
+pub const CANONICAL_SAMPLE_RATE: u32 = 16000;
+
+/// Channel count of the canonical output
+pub const CANONICAL_CHANNELS: u16 = 1;
+
+/// Kernel half-width (in output-rate samples) - higher is sharper but slower
+const BASE_HALF_WIDTH: f64 = 16.0;
+
+/// Kernel table entries per input sample
+const TABLE_RESOLUTION: usize = 128;
+
+/// Cutoff relative to the lower Nyquist frequency, leaving room for the transition band
+const CUTOFF_MARGIN: f64 = 0.95;
+
+/// Average interleaved channels into a single mono channel
+pub fn downmix_to_mono(samples: &[f32], channels: u16) -> Vec<f32> {
+    if channels <= 1 {
+        return samples.to_vec();
+    }
+
+    samples
+        .chunks(channels as usize)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect()
+}
+
+/// Streaming band-limited resampler (windowed-sinc interpolation).
+///
+/// When downsampling, the kernel cutoff drops to the output Nyquist frequency so the
+/// conversion also acts as the anti-aliasing filter. State is carried between calls,
+/// so buffers of any size can be fed in as they arrive from the device.
+pub struct Resampler {
+    channels: usize,
+    step: f64, // Input samples advanced per output sample
+    half_width: usize,
+    table: Vec<f32>,
+    buffers: Vec<Vec<f32>>, // Pending input per channel
+    position: f64,          // Time of the next output sample, in input samples
+}
+
+impl Resampler {
+    pub fn new(input_rate: u32, output_rate: u32, channels: u16) -> Self {
+        let step = input_rate as f64 / output_rate as f64;
+        let cutoff = (1.0 / step).min(1.0) * CUTOFF_MARGIN;
+        let half_width = (BASE_HALF_WIDTH / cutoff).ceil() as usize;
+
+        // Blackman-windowed sinc, sampled finely so lookups can interpolate
+        let table = (0..=half_width * TABLE_RESOLUTION)
+            .map(|i| {
+                let x = i as f64 / TABLE_RESOLUTION as f64;
+                let sinc = if x == 0.0 {
+                    1.0
+                } else {
+                    (PI * cutoff * x).sin() / (PI * cutoff * x)
+                };
+                let n = 0.5 + x / (2.0 * half_width as f64);
+                let window = 0.42 - 0.5 * (2.0 * PI * n).cos() + 0.08 * (4.0 * PI * n).cos();
+                (cutoff * sinc * window) as f32
+            })
+            .collect();
+
+        // Start with a zeroed history so the first output sample has full support
+        let channels = channels.max(1) as usize;
+        Self {
+            channels,
+            step,
+            half_width,
+            table,
+            buffers: vec![vec![0.0; half_width]; channels],
+            position: half_width as f64,
+        }
+    }
+
+    /// Feed interleaved input, returning whatever interleaved output is ready
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        for frame in input.chunks(self.channels) {
+            for (channel, &sample) in frame.iter().enumerate() {
+                self.buffers[channel].push(sample);
+            }
+        }
+
+        let available = self.buffers[0].len();
+        let mut output =
+            Vec::with_capacity(((input.len() / self.channels) as f64 / self.step) as usize + 1);
+
+        while (self.position.floor() as usize) + self.half_width < available {
+            for buffer in &self.buffers {
+                output.push(self.interpolate(buffer, self.position));
+            }
+            self.position += self.step;
+        }
+
+        // Drop input that no future output sample can reach
+        let consumed = (self.position.floor() as usize).saturating_sub(self.half_width);
+        if consumed > 0 {
+            for buffer in &mut self.buffers {
+                buffer.drain(..consumed);
+            }
+            self.position -= consumed as f64;
+        }
+
+        output
+    }
+
+    fn interpolate(&self, buffer: &[f32], time: f64) -> f32 {
+        let center = time.floor() as usize;
+        let first = center + 1 - self.half_width;
+        let last = center + self.half_width;
+
+        (first..=last)
+            .map(|index| buffer[index] * self.kernel((time - index as f64).abs()))
+            .sum()
+    }
+
+    fn kernel(&self, distance: f64) -> f32 {
+        let scaled = distance * TABLE_RESOLUTION as f64;
+        let index = scaled.floor() as usize;
+        if index + 1 >= self.table.len() {
+            return 0.0;
+        }
+        let fraction = (scaled - index as f64) as f32;
+        self.table[index] + (self.table[index + 1] - self.table[index]) * fraction
+    }
+}
+
+/// Converts device audio to the session's output format (downmix, then resample)
+pub struct FormatConverter {
+    input_channels: u16,
+    downmix: bool,
+    resampler: Option<Resampler>,
+}
+
+impl FormatConverter {
+    pub fn new(input_rate: u32, input_channels: u16, output_rate: u32, output_channels: u16) -> Self {
+        let downmix = output_channels == 1 && input_channels > 1;
+        let resampler = (input_rate != output_rate)
+            .then(|| Resampler::new(input_rate, output_rate, output_channels));
+
+        Self {
+            input_channels,
+            downmix,
+            resampler,
+        }
+    }
+
+    /// Convert a buffer of interleaved device samples
+    pub fn process(&mut self, samples: Vec<f32>) -> Vec<f32> {
+        // Downmix first so the resampler only has to run once
+        let samples = if self.downmix {
+            downmix_to_mono(&samples, self.input_channels)
+        } else {
+            samples
+        };
+
+        match self.resampler.as_mut() {
+            Some(resampler) => resampler.process(&samples),
+            None => samples,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(frequency: f64, rate: u32, seconds: f64) -> Vec<f32> {
+        let count = (rate as f64 * seconds) as usize;
+        (0..count)
+            .map(|i| (2.0 * PI * frequency * i as f64 / rate as f64).sin() as f32)
+            .collect()
+    }
+
+    /// RMS of the output with the kernel's warm-up and tail cut off
+    fn steady_rms(samples: &[f32]) -> f32 {
+        let edge = samples.len() / 10;
+        let middle = &samples[edge..samples.len() - edge];
+        (middle.iter().map(|s| s * s).sum::<f32>() / middle.len() as f32).sqrt()
+    }
+
+    /// Gain of a sine at `frequency` through the resampler, in dB
+    fn gain_db(frequency: f64, input_rate: u32, output_rate: u32) -> f32 {
+        let output =
+            Resampler::new(input_rate, output_rate, 1).process(&sine(frequency, input_rate, 1.0));
+        20.0 * (steady_rms(&output) / std::f32::consts::FRAC_1_SQRT_2).log10()
+    }
+
+    fn assert_length(input_rate: u32, input_len: usize, output_len: usize) {
+        let expected = input_len as f64 * CANONICAL_SAMPLE_RATE as f64 / input_rate as f64;
+        // Output lags the input by the kernel's half width
+        let latency = BASE_HALF_WIDTH / CUTOFF_MARGIN + 1.0;
+        assert!(
+            output_len as f64 <= expected && output_len as f64 >= expected - latency,
+            "{} Hz: {} samples in, {} out, expected about {}",
+            input_rate,
+            input_len,
+            output_len,
+            expected
+        );
+    }
+
+    #[test]
+    fn output_length_matches_the_rate_ratio() {
+        for input_rate in [48000, 44100] {
+            let input = sine(440.0, input_rate, 2.0);
+            let output = Resampler::new(input_rate, CANONICAL_SAMPLE_RATE, 1).process(&input);
+            assert_length(input_rate, input.len(), output.len());
+        }
+    }
+
+    #[test]
+    fn passband_is_flat() {
+        for input_rate in [48000, 44100] {
+            for frequency in [200.0, 1000.0, 3000.0, 6000.0] {
+                let gain = gain_db(frequency, input_rate, CANONICAL_SAMPLE_RATE);
+                assert!(
+                    gain.abs() < 0.1,
+                    "{} Hz -> 16 kHz: {} Hz gain {:.3} dB",
+                    input_rate,
+                    frequency,
+                    gain
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn content_above_the_output_nyquist_is_filtered() {
+        for input_rate in [48000, 44100] {
+            for frequency in [9000.0, 12000.0, 20000.0] {
+                let gain = gain_db(frequency, input_rate, CANONICAL_SAMPLE_RATE);
+                assert!(
+                    gain < -60.0,
+                    "{} Hz -> 16 kHz: {} Hz aliased at {:.1} dB",
+                    input_rate,
+                    frequency,
+                    gain
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn streaming_matches_one_shot() {
+        for input_rate in [48000, 44100] {
+            let input = sine(1000.0, input_rate, 1.0);
+            let one_shot = Resampler::new(input_rate, CANONICAL_SAMPLE_RATE, 1).process(&input);
+
+            // Odd-sized chunks, like device buffers that don't divide the rate ratio
+            let mut resampler = Resampler::new(input_rate, CANONICAL_SAMPLE_RATE, 1);
+            let mut streamed = Vec::new();
+            for chunk in input.chunks(127) {
+                streamed.extend(resampler.process(chunk));
+            }
+
+            assert_eq!(streamed.len(), one_shot.len());
+            for (a, b) in streamed.iter().zip(&one_shot) {
+                assert!((a - b).abs() < 1e-5, "{} vs {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn channels_are_resampled_independently() {
+        let left = sine(500.0, 48000, 0.5);
+        let right: Vec<f32> = left.iter().map(|s| -s).collect();
+        let stereo: Vec<f32> = left
+            .iter()
+            .zip(&right)
+            .flat_map(|(l, r)| [*l, *r])
+            .collect();
+
+        let output = Resampler::new(48000, CANONICAL_SAMPLE_RATE, 2).process(&stereo);
+        assert_eq!(output.len() % 2, 0);
+        for frame in output.chunks(2) {
+            assert!((frame[0] + frame[1]).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn downmix_averages_stereo() {
+        let stereo = [1.0, 0.0, 0.5, 0.5, -1.0, 1.0];
+        assert_eq!(downmix_to_mono(&stereo, 2), vec![0.5, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn downmix_averages_more_than_two_channels() {
+        let quad = [1.0, 1.0, 0.0, 0.0, 0.4, 0.4, 0.4, 0.4];
+        assert_eq!(downmix_to_mono(&quad, 4), vec![0.5, 0.4]);
+
+        let surround = [0.6; 12]; // Two 5.1 frames
+        for sample in downmix_to_mono(&surround, 6) {
+            assert!((sample - 0.6).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn downmix_leaves_mono_alone() {
+        let mono = [0.1, -0.2, 0.3];
+        assert_eq!(downmix_to_mono(&mono, 1), mono.to_vec());
+    }
+
+    #[test]
+    fn converter_downmixes_and_resamples_across_chunks() {
+        let mono = sine(1000.0, 44100, 1.0);
+        let stereo: Vec<f32> = mono.iter().flat_map(|s| [*s, *s]).collect();
+
+        let expected = Resampler::new(44100, CANONICAL_SAMPLE_RATE, 1).process(&mono);
+
+        let mut converter =
+            FormatConverter::new(44100, 2, CANONICAL_SAMPLE_RATE, CANONICAL_CHANNELS);
+        let mut output = Vec::new();
+        for chunk in stereo.chunks(882) {
+            output.extend(converter.process(chunk.to_vec()));
+        }
+
+        assert_eq!(output.len(), expected.len());
+        for (a, b) in output.iter().zip(&expected) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn converter_passes_matching_formats_through() {
+        let samples = sine(1000.0, 16000, 0.1);
+        let mut converter = FormatConverter::new(16000, 1, 16000, 1);
+        assert_eq!(converter.process(samples.clone()), samples);
+    }
+}
@@ -0,0 +1,209 @@
+use crate::recorder::device_monitor::ActiveDevice;
+use crate::recorder::recorder::AudioFormat;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+use tracing::{debug, warn};
+
+/// Lifecycle of the native recorder
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RecorderStatus {
+    #[default]
+    Idle, // No session
+    SessionReady, // Stream open and file created, not recording
+    Recording,
+    Paused,
+    Finalizing, // Writing the file's final headers
+    Error,      // The session broke (e.g. device lost); stop or close it
+}
+
+impl RecorderStatus {
+    /// Whether the recorder may go from `self` to `next`
+    pub fn can_become(self, next: RecorderStatus) -> bool {
+        use RecorderStatus::*;
+
+        match (self, next) {
+            // Closing is always possible, failing needs a session
+            (_, Idle) => true,
+            (Idle, Error) => false,
+            (_, Error) => true,
+            (Idle, SessionReady) | (Finalizing, SessionReady) => true,
+            (SessionReady, Recording) | (Paused, Recording) => true,
+            (Recording, Paused) => true,
+            // A broken session can still save what it recorded
+            (Recording, Finalizing) | (Paused, Finalizing) | (Error, Finalizing) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Snapshot of the recorder - returned by `get_recorder_status`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecorderStatusInfo {
+    pub status: RecorderStatus,
+    pub recording_id: Option<String>,       // Id of the open session
+    pub device: Option<ActiveDevice>,       // What is being captured from
+    pub output_format: Option<AudioFormat>, // What is written to the file
+    pub elapsed_seconds: f32,               // Audio in the file so far
+    pub bytes_written: u64,
+    pub error: Option<String>,
+}
+
+/// Payload of the `recorder-status-changed` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecorderStatusEvent {
+    pub status: RecorderStatus,
+    pub previous: RecorderStatus,
+    pub error: Option<String>,
+}
+
+/// Current status, shared with the stream thread so it can report a lost device
+pub type SharedStatus = Arc<Mutex<StatusTracker>>;
+
+#[derive(Default)]
+pub struct StatusTracker {
+    status: RecorderStatus,
+    error: Option<String>,
+    app_handle: Option<AppHandle>, // Known once a session has been opened
+}
+
+impl StatusTracker {
+    pub fn status(&self) -> RecorderStatus {
+        self.status
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.error.clone()
+    }
+
+    pub fn set_app_handle(&mut self, app_handle: AppHandle) {
+        self.app_handle = Some(app_handle);
+    }
+
+    /// Fail unless the recorder may go to `next`
+    pub fn check(&self, next: RecorderStatus) -> Result<(), String> {
+        if self.status.can_become(next) {
+            Ok(())
+        } else {
+            Err(format!(
+                "Recorder is {:?} and cannot become {:?}",
+                self.status, next
+            ))
+        }
+    }
+
+    /// Move to `next` and tell the frontend
+    pub fn transition(&mut self, next: RecorderStatus) -> Result<(), String> {
+        self.check(next)?;
+        if next != RecorderStatus::Error {
+            self.error = None;
+        }
+        self.set(next);
+        Ok(())
+    }
+
+    /// Move to `Error` with the reason
+    pub fn fail(&mut self, message: String) {
+        if self.check(RecorderStatus::Error).is_err() {
+            warn!("Recorder error without a session: {}", message);
+            return;
+        }
+        self.error = Some(message);
+        self.set(RecorderStatus::Error);
+    }
+
+    fn set(&mut self, next: RecorderStatus) {
+        let previous = self.status;
+        self.status = next;
+        if previous == next {
+            return;
+        }
+
+        debug!("Recorder status: {:?} -> {:?}", previous, next);
+        if let Some(app_handle) = &self.app_handle {
+            let _ = app_handle.emit(
+                "recorder-status-changed",
+                RecorderStatusEvent {
+                    status: next,
+                    previous,
+                    error: self.error.clone(),
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use RecorderStatus::*;
+
+    const ALL: [RecorderStatus; 6] = [Idle, SessionReady, Recording, Paused, Finalizing, Error];
+
+    #[test]
+    fn transition_table() {
+        // Closing and failing again are allowed from where the recorder already is
+        let allowed = [
+            (Idle, Idle),
+            (Idle, SessionReady),
+            (SessionReady, Idle),
+            (SessionReady, Recording),
+            (SessionReady, Error),
+            (Recording, Idle),
+            (Recording, Paused),
+            (Recording, Finalizing),
+            (Recording, Error),
+            (Paused, Idle),
+            (Paused, Recording),
+            (Paused, Finalizing),
+            (Paused, Error),
+            (Finalizing, Idle),
+            (Finalizing, SessionReady),
+            (Finalizing, Error),
+            (Error, Idle),
+            (Error, Finalizing),
+            (Error, Error),
+        ];
+        for from in ALL {
+            for to in ALL {
+                let expected = allowed.contains(&(from, to));
+                assert_eq!(from.can_become(to), expected, "{:?} -> {:?}", from, to);
+            }
+        }
+    }
+
+    #[test]
+    fn failing_needs_a_session() {
+        let mut tracker = StatusTracker::default();
+        tracker.fail("device lost".to_string());
+        assert_eq!(tracker.status(), Idle);
+        assert_eq!(tracker.error(), None);
+    }
+
+    #[test]
+    fn a_failed_stop_stays_in_error_until_closed() {
+        let mut tracker = StatusTracker::default();
+        tracker.transition(SessionReady).unwrap();
+        tracker.transition(Recording).unwrap();
+        tracker.fail("device lost".to_string());
+
+        // Stopping saves what was recorded, but a failed stop leaves the session broken
+        tracker.transition(Finalizing).unwrap();
+        tracker.fail("Failed to finalize".to_string());
+        assert_eq!(tracker.status(), Error);
+        assert_eq!(tracker.error().as_deref(), Some("Failed to finalize"));
+
+        // A broken session cannot record again, only close
+        for next in [SessionReady, Recording, Paused] {
+            assert!(tracker.transition(next).is_err(), "Error -> {:?}", next);
+        }
+        assert_eq!(tracker.status(), Error);
+
+        tracker.transition(Idle).unwrap();
+        assert_eq!(tracker.status(), Idle);
+        assert_eq!(tracker.error(), None);
+    }
+}
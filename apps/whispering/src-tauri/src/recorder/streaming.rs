@@ -0,0 +1,290 @@
+use crate::recorder::encoder::f32_to_i16;
+use serde::{Deserialize, Serialize};
+use tauri::ipc::{Channel, JavaScriptChannelId};
+use tracing::{debug, warn};
+
+/// Streaming settings - passed from the frontend in `init_recording_session`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamingConfig {
+    /// Channel created on the JavaScript side (`new Channel<AudioChunk>()`)
+    pub on_chunk: JavaScriptChannelId,
+    /// Length of each chunk in fixed-duration mode
+    #[serde(default = "default_chunk_ms")]
+    pub chunk_ms: u32,
+    /// Cut chunks where the VAD hears speech end instead of at fixed intervals
+    #[serde(default)]
+    pub segment_by_vad: bool,
+    /// Longest chunk sent in VAD-segmented mode, for speakers who never pause
+    #[serde(default = "default_max_segment_ms")]
+    pub max_segment_ms: u32,
+}
+
+fn default_chunk_ms() -> u32 {
+    1000
+}
+
+fn default_max_segment_ms() -> u32 {
+    30000
+}
+
+/// Streaming settings with the channel resolved on the webview that opened the session
+pub struct StreamingSession {
+    pub config: StreamingConfig,
+    pub channel: Channel<AudioChunk>,
+}
+
+/// One block of recorded audio - sent to the frontend over the session's channel
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioChunk {
+    pub sequence: u64,       // 0 for the first chunk of each recording
+    pub offset_seconds: f32, // Start of the chunk in the recording
+    pub duration_seconds: f32,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub samples: Vec<i16>, // Interleaved 16-bit PCM
+    pub segment_end: bool, // The VAD heard speech end here
+    pub is_final: bool,    // Last chunk of the recording
+}
+
+/// Cuts the recorded audio into chunks and pushes them over a Tauri channel.
+///
+/// Runs on the processing thread next to the file writer, so the frontend receives
+/// exactly what is written (in the session's output format).
+pub struct ChunkStreamer {
+    channel: Channel<AudioChunk>,
+    sample_rate: u32,
+    channels: u16,
+    segment_by_vad: bool,
+    max_samples: usize, // Interleaved samples per chunk (or per segment at most)
+    buffer: Vec<f32>,
+    sequence: u64,
+    frames_sent: u64,
+    finished: bool,
+    send_failed: bool,
+}
+
+impl ChunkStreamer {
+    pub fn new(session: StreamingSession, sample_rate: u32, channels: u16) -> Self {
+        let StreamingSession { config, channel } = session;
+        let chunk_ms = if config.segment_by_vad {
+            config.max_segment_ms
+        } else {
+            config.chunk_ms
+        };
+        let frames = (sample_rate as u64 * chunk_ms.max(20) as u64 / 1000).max(1) as usize;
+
+        Self {
+            channel,
+            sample_rate,
+            channels,
+            segment_by_vad: config.segment_by_vad,
+            max_samples: frames * channels.max(1) as usize,
+            buffer: Vec::new(),
+            sequence: 0,
+            frames_sent: 0,
+            finished: true, // Nothing to send until a recording starts
+            send_failed: false,
+        }
+    }
+
+    /// Whether chunks follow speech boundaries rather than a fixed duration
+    pub fn segment_by_vad(&self) -> bool {
+        self.segment_by_vad
+    }
+
+    /// Start streaming a new recording
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.sequence = 0;
+        self.frames_sent = 0;
+        self.finished = false;
+    }
+
+    /// Add recorded samples, sending every chunk that is complete
+    pub fn push(&mut self, samples: &[f32]) {
+        if self.finished {
+            return;
+        }
+
+        self.buffer.extend_from_slice(samples);
+        while self.buffer.len() >= self.max_samples {
+            let chunk: Vec<f32> = self.buffer.drain(..self.max_samples).collect();
+            self.send(chunk, false, false);
+        }
+    }
+
+    /// Speech ended - send what was buffered as one segment
+    pub fn end_segment(&mut self) {
+        if self.finished || self.buffer.is_empty() {
+            return;
+        }
+        let chunk = std::mem::take(&mut self.buffer);
+        self.send(chunk, true, false);
+    }
+
+    /// Recording is over - send the remainder, flagged final (even when empty)
+    pub fn finish(&mut self) {
+        if self.finished {
+            return;
+        }
+        let chunk = std::mem::take(&mut self.buffer);
+        self.send(chunk, false, true);
+        self.finished = true;
+        debug!("Streamed {} chunk(s)", self.sequence);
+    }
+
+    fn send(&mut self, samples: Vec<f32>, segment_end: bool, is_final: bool) {
+        let frames = (samples.len() / self.channels.max(1) as usize) as u64;
+        let chunk = AudioChunk {
+            sequence: self.sequence,
+            offset_seconds: self.frames_sent as f32 / self.sample_rate as f32,
+            duration_seconds: frames as f32 / self.sample_rate as f32,
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            samples: samples.into_iter().map(f32_to_i16).collect(),
+            segment_end,
+            is_final,
+        };
+        self.sequence += 1;
+        self.frames_sent += frames;
+
+        // The webview may have been reloaded - keep recording, just stop complaining
+        if let Err(e) = self.channel.send(chunk) {
+            if !self.send_failed {
+                warn!("Failed to send audio chunk to the frontend: {}", e);
+                self.send_failed = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tauri::ipc::InvokeResponseBody;
+
+    type Sent = Arc<Mutex<Vec<serde_json::Value>>>;
+
+    /// A 1 kHz streamer whose chunks are collected instead of sent to a webview
+    fn streamer(config: serde_json::Value, channels: u16) -> (ChunkStreamer, Sent) {
+        let mut config = config;
+        config["onChunk"] = "__CHANNEL__:1".into();
+        let sent = Sent::default();
+        let sink = sent.clone();
+        let channel = Channel::new(move |body| {
+            if let InvokeResponseBody::Json(json) = body {
+                sink.lock()
+                    .unwrap()
+                    .push(serde_json::from_str(&json).unwrap());
+            }
+            Ok(())
+        });
+        let session = StreamingSession {
+            config: serde_json::from_value(config).unwrap(),
+            channel,
+        };
+        (ChunkStreamer::new(session, 1000, channels), sent)
+    }
+
+    fn take(sent: &Sent) -> Vec<serde_json::Value> {
+        std::mem::take(&mut *sent.lock().unwrap())
+    }
+
+    fn lengths(chunks: &[serde_json::Value]) -> Vec<usize> {
+        chunks
+            .iter()
+            .map(|chunk| chunk["samples"].as_array().unwrap().len())
+            .collect()
+    }
+
+    #[test]
+    fn cuts_fixed_chunks_across_pushes() {
+        let (mut streamer, sent) = streamer(serde_json::json!({ "chunkMs": 100 }), 2);
+        streamer.push(&[0.5; 100]);
+        assert!(
+            take(&sent).is_empty(),
+            "Nothing is sent before a recording starts"
+        );
+
+        streamer.reset();
+        streamer.push(&[0.5; 150]);
+        assert!(take(&sent).is_empty());
+        streamer.push(&[0.5; 300]);
+
+        // 100 stereo frames per chunk, carrying over what did not fill one
+        let chunks = take(&sent);
+        assert_eq!(lengths(&chunks), [200, 200]);
+        assert_eq!(chunks[0]["sequence"], 0);
+        assert_eq!(chunks[1]["sequence"], 1);
+        assert_eq!(chunks[1]["offsetSeconds"], 0.1);
+        assert_eq!(chunks[1]["durationSeconds"], 0.1);
+        assert_eq!(chunks[0]["channels"], 2);
+        assert_eq!(chunks[0]["samples"][0], f32_to_i16(0.5));
+        assert_eq!(chunks[0]["segmentEnd"], false);
+        assert_eq!(chunks[0]["isFinal"], false);
+    }
+
+    #[test]
+    fn finish_sends_the_remainder_once() {
+        let (mut streamer, sent) = streamer(serde_json::json!({ "chunkMs": 100 }), 1);
+        streamer.reset();
+        streamer.push(&[0.0; 130]);
+        streamer.finish();
+        streamer.finish();
+        streamer.push(&[0.0; 200]);
+
+        let chunks = take(&sent);
+        assert_eq!(lengths(&chunks), [100, 30]);
+        assert_eq!(chunks[1]["isFinal"], true);
+        assert_eq!(chunks[1]["offsetSeconds"], 0.1);
+        assert_eq!(chunks[1]["durationSeconds"], 0.03);
+
+        // The end of a recording is announced even without audio left over
+        streamer.reset();
+        streamer.push(&[0.0; 100]);
+        streamer.finish();
+        let chunks = take(&sent);
+        assert_eq!(lengths(&chunks), [100, 0]);
+        assert_eq!(chunks[0]["sequence"], 0);
+        assert_eq!(chunks[0]["offsetSeconds"], 0.0);
+        assert_eq!(chunks[1]["isFinal"], true);
+    }
+
+    #[test]
+    fn segments_follow_the_vad() {
+        let config = serde_json::json!({ "segmentByVad": true, "maxSegmentMs": 500 });
+        let (mut streamer, sent) = streamer(config, 1);
+        assert!(streamer.segment_by_vad());
+        streamer.reset();
+
+        // Chunk size plays no part: a segment runs until speech ends
+        streamer.push(&[0.0; 120]);
+        streamer.push(&[0.0; 200]);
+        assert!(take(&sent).is_empty());
+        streamer.end_segment();
+        streamer.end_segment();
+
+        // Speech that never pauses is cut at the longest segment
+        streamer.push(&[0.0; 600]);
+        streamer.finish();
+
+        let chunks = take(&sent);
+        assert_eq!(lengths(&chunks), [320, 500, 100]);
+        let flags: Vec<_> = chunks
+            .iter()
+            .map(|chunk| (chunk["segmentEnd"].clone(), chunk["isFinal"].clone()))
+            .collect();
+        assert_eq!(
+            flags,
+            [
+                (true.into(), false.into()),
+                (false.into(), false.into()),
+                (false.into(), true.into())
+            ]
+        );
+        assert_eq!(chunks[1]["offsetSeconds"], 0.32);
+    }
+}
@@ -0,0 +1,399 @@
+use crate::recorder::levels::to_db;
+use serde::Deserialize;
+
+/// Analysis frame length
+const FRAME_MS: u32 = 20;
+
+/// Frames used to seed the noise floor before any decision is made
+const CALIBRATION_FRAMES: u32 = 10;
+
+/// Frames quieter than this are never speech, whatever the noise floor
+const ABSOLUTE_MIN_DB: f32 = -55.0;
+
+/// Zero-crossing rate above which a quiet frame is treated as hiss/fricative noise
+const NOISE_ZCR: f32 = 0.35;
+
+/// Voice activity detection settings - passed from the frontend
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VadConfig {
+    /// Stop writing after this much trailing silence (None = never auto-stop)
+    pub auto_stop_silence_ms: Option<u32>,
+    /// Trim leading/trailing silence from the file when recording stops
+    pub trim_silence: bool,
+    /// Silence kept around speech when trimming
+    pub trim_padding_ms: u32,
+    /// How far above the noise floor a frame must be to count as speech
+    pub threshold_db: f32,
+    /// Consecutive speech needed before `speech-started` fires
+    pub min_speech_ms: u32,
+    /// Silence needed before `speech-ended` fires
+    pub hangover_ms: u32,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            auto_stop_silence_ms: Some(2000),
+            trim_silence: true,
+            trim_padding_ms: 250,
+            threshold_db: 12.0,
+            min_speech_ms: 120,
+            hangover_ms: 400,
+        }
+    }
+}
+
+/// Transitions reported by the detector. Positions are in frames since recording start.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VadEvent {
+    SpeechStarted { frame: u64 },
+    SpeechEnded { frame: u64 },
+    SilenceTimeout,
+}
+
+/// Energy + zero-crossing voice activity detector with an adaptive noise floor.
+///
+/// Works on interleaved input, downmixing to mono internally. All positions are
+/// reported in sample frames so they can be mapped directly onto the WAV file.
+pub struct VoiceActivityDetector {
+    config: VadConfig,
+    channels: usize,
+    frame_len: usize,
+    frame: Vec<f32>,
+    frames_seen: u64,
+    noise_floor_db: f32,
+    calibration_left: u32,
+    in_speech: bool,
+    speech_run_ms: u32,
+    silence_run_ms: u32,
+    speech_candidate_start: u64,
+    first_speech: Option<u64>,
+    last_speech: Option<u64>,
+    timed_out: bool,
+}
+
+impl VoiceActivityDetector {
+    pub fn new(config: VadConfig, sample_rate: u32, channels: u16) -> Self {
+        let frame_len = ((sample_rate * FRAME_MS) / 1000).max(1) as usize;
+        Self {
+            config,
+            channels: channels.max(1) as usize,
+            frame_len,
+            frame: Vec::with_capacity(frame_len),
+            frames_seen: 0,
+            noise_floor_db: ABSOLUTE_MIN_DB,
+            calibration_left: CALIBRATION_FRAMES,
+            in_speech: false,
+            speech_run_ms: 0,
+            silence_run_ms: 0,
+            speech_candidate_start: 0,
+            first_speech: None,
+            last_speech: None,
+            timed_out: false,
+        }
+    }
+
+    /// Forget everything about the previous recording (keeps the noise floor)
+    pub fn reset(&mut self) {
+        self.frame.clear();
+        self.frames_seen = 0;
+        self.in_speech = false;
+        self.speech_run_ms = 0;
+        self.silence_run_ms = 0;
+        self.first_speech = None;
+        self.last_speech = None;
+        self.timed_out = false;
+    }
+
+    /// Feed interleaved samples, returning any transitions they caused
+    pub fn process(&mut self, samples: &[f32]) -> Vec<VadEvent> {
+        let mut events = Vec::new();
+
+        for chunk in samples.chunks(self.channels) {
+            let mono = chunk.iter().sum::<f32>() / chunk.len() as f32;
+            self.frame.push(mono);
+            self.frames_seen += 1;
+
+            if self.frame.len() == self.frame_len {
+                let is_speech = self.classify_frame();
+                self.frame.clear();
+                self.update_state(is_speech, &mut events);
+            }
+        }
+
+        events
+    }
+
+    /// Region containing speech, padded by `trim_padding_ms` (None if nothing was heard)
+    pub fn speech_region(&self, sample_rate: u32) -> Option<(u64, u64)> {
+        let start = self.first_speech?;
+        let end = if self.in_speech {
+            self.frames_seen
+        } else {
+            self.last_speech.unwrap_or(self.frames_seen)
+        };
+
+        let padding = (sample_rate as u64 * self.config.trim_padding_ms as u64) / 1000;
+        Some((
+            start.saturating_sub(padding),
+            (end + padding).min(self.frames_seen),
+        ))
+    }
+
+    pub fn config(&self) -> &VadConfig {
+        &self.config
+    }
+
+    /// Whether the trailing-silence limit was hit in the current recording
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    fn classify_frame(&mut self) -> bool {
+        let len = self.frame.len() as f32;
+        let energy = self.frame.iter().map(|s| s * s).sum::<f32>() / len;
+        let energy_db = to_db(energy.sqrt());
+
+        let crossings = self
+            .frame
+            .windows(2)
+            .filter(|pair| (pair[0] >= 0.0) != (pair[1] >= 0.0))
+            .count();
+        let zcr = crossings as f32 / len;
+
+        // Seed the floor from the quietest of the first frames, which may already contain speech
+        if self.calibration_left > 0 {
+            if self.calibration_left == CALIBRATION_FRAMES {
+                self.noise_floor_db = energy_db;
+            } else {
+                self.noise_floor_db = self.noise_floor_db.min(energy_db);
+            }
+            self.calibration_left -= 1;
+            return false;
+        }
+
+        let above_floor = energy_db > self.noise_floor_db + self.config.threshold_db;
+        let loud_enough = energy_db > ABSOLUTE_MIN_DB;
+        // Noise tends to have high ZCR at low energy; voiced speech has low ZCR
+        let noisy = zcr > NOISE_ZCR
+            && energy_db < self.noise_floor_db + self.config.threshold_db * 2.0;
+        let is_speech = above_floor && loud_enough && !noisy;
+
+        // Only adapt the floor on non-speech so talking does not raise it
+        if !is_speech {
+            let rate = if energy_db < self.noise_floor_db { 0.2 } else { 0.02 };
+            self.noise_floor_db += (energy_db - self.noise_floor_db) * rate;
+        }
+
+        is_speech
+    }
+
+    fn update_state(&mut self, is_speech: bool, events: &mut Vec<VadEvent>) {
+        let frame_start = self.frames_seen - self.frame_len as u64;
+
+        if is_speech {
+            if self.speech_run_ms == 0 {
+                self.speech_candidate_start = frame_start;
+            }
+            self.speech_run_ms += FRAME_MS;
+            self.silence_run_ms = 0;
+
+            if !self.in_speech && self.speech_run_ms >= self.config.min_speech_ms {
+                self.in_speech = true;
+                self.first_speech.get_or_insert(self.speech_candidate_start);
+                events.push(VadEvent::SpeechStarted {
+                    frame: self.speech_candidate_start,
+                });
+            }
+            if self.in_speech {
+                self.last_speech = Some(self.frames_seen);
+            }
+            return;
+        }
+
+        self.speech_run_ms = 0;
+        self.silence_run_ms += FRAME_MS;
+
+        if self.in_speech && self.silence_run_ms >= self.config.hangover_ms {
+            self.in_speech = false;
+            events.push(VadEvent::SpeechEnded {
+                frame: self.last_speech.unwrap_or(frame_start),
+            });
+        }
+
+        // Only time out after something was said, so a slow start is not cut off
+        if let Some(timeout_ms) = self.config.auto_stop_silence_ms {
+            if !self.timed_out
+                && !self.in_speech
+                && self.first_speech.is_some()
+                && self.silence_run_ms >= timeout_ms
+            {
+                self.timed_out = true;
+                events.push(VadEvent::SilenceTimeout);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    fn config() -> VadConfig {
+        VadConfig {
+            auto_stop_silence_ms: None,
+            trim_padding_ms: 100,
+            ..VadConfig::default()
+        }
+    }
+
+    /// 200 Hz sine: voiced speech as far as the detector is concerned
+    fn tone(ms: usize, amplitude: f32) -> Vec<f32> {
+        (0..ms * 16)
+            .map(|i| amplitude * (i as f32 * 200.0 * std::f32::consts::TAU / RATE as f32).sin())
+            .collect()
+    }
+
+    /// Sign flipping on every sample: noise with the highest zero-crossing rate
+    fn hiss(ms: usize, amplitude: f32) -> Vec<f32> {
+        (0..ms * 16)
+            .map(|i| if i % 2 == 0 { amplitude } else { -amplitude })
+            .collect()
+    }
+
+    fn silence(ms: usize) -> Vec<f32> {
+        vec![0.0; ms * 16]
+    }
+
+    /// Feed the samples in buffers (of whole stereo frames) that do not line up with the
+    /// analysis frames
+    fn feed(vad: &mut VoiceActivityDetector, samples: &[f32]) -> Vec<VadEvent> {
+        samples
+            .chunks(440)
+            .flat_map(|chunk| vad.process(chunk))
+            .collect()
+    }
+
+    /// A detector whose noise floor was calibrated on silence
+    fn calibrated(config: VadConfig) -> VoiceActivityDetector {
+        let mut vad = VoiceActivityDetector::new(config, RATE, 1);
+        assert!(feed(&mut vad, &silence(200)).is_empty());
+        vad
+    }
+
+    #[test]
+    fn speech_and_silence_are_told_apart() {
+        let mut vad = calibrated(config());
+        assert_eq!(
+            feed(&mut vad, &tone(500, 0.3)),
+            [VadEvent::SpeechStarted { frame: 3200 }]
+        );
+        assert_eq!(
+            feed(&mut vad, &silence(1000)),
+            [VadEvent::SpeechEnded { frame: 11200 }]
+        );
+    }
+
+    #[test]
+    fn short_bursts_are_not_speech() {
+        let mut vad = calibrated(config());
+        // 100 ms is below min_speech_ms
+        assert!(feed(&mut vad, &tone(100, 0.3)).is_empty());
+        assert!(feed(&mut vad, &silence(500)).is_empty());
+        assert_eq!(vad.speech_region(RATE), None);
+    }
+
+    #[test]
+    fn hiss_is_not_speech() {
+        let mut vad = VoiceActivityDetector::new(config(), RATE, 1);
+        feed(&mut vad, &hiss(200, 0.001));
+        assert!(feed(&mut vad, &hiss(500, 0.01)).is_empty());
+        assert_eq!(
+            feed(&mut vad, &tone(200, 0.05)),
+            [VadEvent::SpeechStarted { frame: 11200 }]
+        );
+    }
+
+    #[test]
+    fn hangover_bridges_short_pauses() {
+        let mut vad = calibrated(config());
+        let mut events = feed(&mut vad, &tone(300, 0.3));
+        events.extend(feed(&mut vad, &silence(300)));
+        events.extend(feed(&mut vad, &tone(300, 0.3)));
+        assert_eq!(events, [VadEvent::SpeechStarted { frame: 3200 }]);
+
+        // Ends once the silence reaches hangover_ms, at the end of the speech
+        assert!(vad.process(&silence(380)).is_empty());
+        assert_eq!(
+            vad.process(&silence(20)),
+            [VadEvent::SpeechEnded { frame: 17600 }]
+        );
+    }
+
+    #[test]
+    fn times_out_only_after_speech() {
+        let mut vad = calibrated(VadConfig {
+            auto_stop_silence_ms: Some(1000),
+            ..config()
+        });
+        // Nothing said yet: a slow start is not cut off
+        assert!(feed(&mut vad, &silence(1500)).is_empty());
+
+        feed(&mut vad, &tone(300, 0.3));
+        assert!(vad
+            .process(&silence(980))
+            .contains(&VadEvent::SpeechEnded { frame: 32000 }));
+        assert!(!vad.timed_out());
+        assert_eq!(vad.process(&silence(20)), [VadEvent::SilenceTimeout]);
+        assert!(vad.timed_out());
+
+        // Reported once
+        assert!(feed(&mut vad, &silence(2000)).is_empty());
+
+        vad.reset();
+        assert!(!vad.timed_out());
+        assert_eq!(vad.speech_region(RATE), None);
+    }
+
+    #[test]
+    fn speech_region_is_padded_and_clamped() {
+        let mut vad = calibrated(config());
+        feed(&mut vad, &silence(800));
+        feed(&mut vad, &tone(500, 0.3));
+        // Still speaking: the region runs to the end of what was heard
+        assert_eq!(vad.speech_region(RATE), Some((14400, 24000)));
+
+        feed(&mut vad, &silence(1000));
+        assert_eq!(vad.speech_region(RATE), Some((14400, 25600)));
+
+        // Padding never reaches outside the recording
+        let mut vad = calibrated(VadConfig {
+            trim_padding_ms: 500,
+            ..config()
+        });
+        feed(&mut vad, &tone(500, 0.3));
+        assert_eq!(vad.speech_region(RATE), Some((0, 11200)));
+    }
+
+    #[test]
+    fn positions_are_in_frames_for_stereo() {
+        let stereo = |left: &[f32], right: &[f32]| -> Vec<f32> {
+            left.iter().zip(right).flat_map(|(l, r)| [*l, *r]).collect()
+        };
+        let mut vad = VoiceActivityDetector::new(config(), RATE, 2);
+        feed(&mut vad, &stereo(&silence(200), &silence(200)));
+
+        // Opposite channels cancel out in the downmix
+        let speech = tone(500, 0.3);
+        let inverted: Vec<f32> = speech.iter().map(|s| -s).collect();
+        assert!(feed(&mut vad, &stereo(&speech, &inverted)).is_empty());
+
+        assert_eq!(
+            feed(&mut vad, &stereo(&speech, &speech)),
+            [VadEvent::SpeechStarted { frame: 11200 }]
+        );
+    }
+}
@@ -1,32 +1,69 @@
-use std::fs::File;
-use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use crate::recorder::encoder::{buffered_len, f32_to_i16, AudioEncoder};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::PathBuf;
 use std::time::Instant;
 use tracing::{debug, info};
 
+/// Offset of the chunk reserved for the RF64 `ds64` chunk (right after "RIFF" + size + "WAVE")
+pub const DS64_POS: u64 = 12;
+
+/// Body of a `ds64` chunk: RIFF size, data size and sample count (u64) + table length (u32)
+const DS64_BODY_SIZE: u32 = 28;
+
+/// Offset of the data chunk's size field: the reserved chunk, then a 16-byte `fmt `, then `data`
+pub const DATA_SIZE_POS: u64 = DS64_POS + 8 + DS64_BODY_SIZE as u64 + 8 + 16 + 4;
+
+/// Size fields that do not fit in 32 bits are set to this and read from `ds64`
+const RF64_SIZE_MARKER: u32 = 0xFFFF_FFFF;
+
+/// Largest RIFF size a plain WAV header can hold
+const RIFF_SIZE_LIMIT: u64 = u32::MAX as u64;
+
+/// Sample encoding stored in the data chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WavSampleFormat {
+    Float32, // IEEE float (format tag 3)
+    Pcm16,   // Signed 16-bit integer PCM (format tag 1)
+}
+
 /// WAV file writer that supports progressive writing with header updates
 pub struct WavWriter {
     writer: BufWriter<File>,
     sample_rate: u32,
     channels: u16,
+    sample_format: WavSampleFormat,
     #[allow(dead_code)]
     bits_per_sample: u16,
     bytes_per_sample: u16,
     data_chunk_size_pos: u64,
-    riff_chunk_size_pos: u64,
     samples_written: u64,
     last_header_update: Instant,
     file_path: PathBuf,
+    riff_size_limit: u64, // Switch to RF64 above this (lowered in tests)
 }
 
 impl WavWriter {
     /// Create a new WAV file and write initial headers
-    pub fn new(file_path: PathBuf, sample_rate: u32, channels: u16) -> io::Result<Self> {
-        let file = File::create(&file_path)?;
+    pub fn new(
+        file_path: PathBuf,
+        sample_rate: u32,
+        channels: u16,
+        sample_format: WavSampleFormat,
+    ) -> io::Result<Self> {
+        // Opened read/write so the data can be trimmed in place after recording
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&file_path)?;
         let mut writer = BufWriter::new(file);
 
-        // We'll use 32-bit float format for consistency with the current implementation
-        let bits_per_sample = 32;
+        let (format_tag, bits_per_sample): (u16, u16) = match sample_format {
+            WavSampleFormat::Float32 => (3, 32),
+            WavSampleFormat::Pcm16 => (1, 16),
+        };
         let bytes_per_sample = bits_per_sample / 8;
 
         // Write initial WAV header with placeholder sizes
@@ -34,14 +71,18 @@ impl WavWriter {
 
         // RIFF header
         writer.write_all(b"RIFF")?;
-        let riff_chunk_size_pos = writer.stream_position()?;
         writer.write_all(&[0xFF, 0xFF, 0xFF, 0xFF])?; // Placeholder for file size - 8
         writer.write_all(b"WAVE")?;
 
+        // Space for a ds64 chunk, used if the recording grows past 4 GB (EBU Tech 3306)
+        writer.write_all(b"JUNK")?;
+        writer.write_all(&DS64_BODY_SIZE.to_le_bytes())?;
+        writer.write_all(&[0u8; DS64_BODY_SIZE as usize])?;
+
         // fmt chunk
         writer.write_all(b"fmt ")?;
         writer.write_all(&16u32.to_le_bytes())?; // Subchunk1Size (16 for PCM)
-        writer.write_all(&3u16.to_le_bytes())?; // AudioFormat (3 for IEEE Float)
+        writer.write_all(&format_tag.to_le_bytes())?; // AudioFormat (1 for PCM, 3 for IEEE Float)
         writer.write_all(&channels.to_le_bytes())?;
         writer.write_all(&sample_rate.to_le_bytes())?;
         let byte_rate = sample_rate * channels as u32 * bytes_per_sample as u32;
@@ -58,53 +99,34 @@ impl WavWriter {
         writer.flush()?;
 
         info!(
-            "Created WAV file at {:?}: {}Hz, {} channels, {}-bit float",
-            file_path, sample_rate, channels, bits_per_sample
+            "Created WAV file at {:?}: {}Hz, {} channels, {}-bit {:?}",
+            file_path, sample_rate, channels, bits_per_sample, sample_format
         );
 
         Ok(Self {
             writer,
             sample_rate,
             channels,
+            sample_format,
             bits_per_sample,
             bytes_per_sample,
             data_chunk_size_pos,
-            riff_chunk_size_pos,
             samples_written: 0,
             last_header_update: Instant::now(),
             file_path,
+            riff_size_limit: RIFF_SIZE_LIMIT,
         })
     }
 
     /// Write f32 samples to the WAV file
     pub fn write_samples_f32(&mut self, samples: &[f32]) -> io::Result<()> {
-        // Write samples as little-endian f32
-        for sample in samples {
-            self.writer.write_all(&sample.to_le_bytes())?;
-        }
-
-        self.samples_written += samples.len() as u64;
-
-        // Update headers periodically (every second)
-        if self.last_header_update.elapsed().as_secs() >= 1 {
-            self.update_headers()?;
-            self.last_header_update = Instant::now();
-        }
-
-        Ok(())
-    }
-
-    /// Write i16 samples to the WAV file (converting to f32)
-    pub fn write_samples_i16(&mut self, samples: &[i16]) -> io::Result<()> {
-        // Convert i16 to f32 and write
         for &sample in samples {
-            let f32_sample = sample as f32 / i16::MAX as f32;
-            self.writer.write_all(&f32_sample.to_le_bytes())?;
+            self.write_sample(sample)?;
         }
 
         self.samples_written += samples.len() as u64;
 
-        // Update headers periodically
+        // Update headers periodically (every second)
         if self.last_header_update.elapsed().as_secs() >= 1 {
             self.update_headers()?;
             self.last_header_update = Instant::now();
@@ -113,42 +135,28 @@ impl WavWriter {
         Ok(())
     }
 
-    /// Write u16 samples to the WAV file (converting to f32)
-    pub fn write_samples_u16(&mut self, samples: &[u16]) -> io::Result<()> {
-        // Convert u16 to f32 and write
-        for &sample in samples {
-            let f32_sample = (sample as f32 / u16::MAX as f32) * 2.0 - 1.0;
-            self.writer.write_all(&f32_sample.to_le_bytes())?;
-        }
-
-        self.samples_written += samples.len() as u64;
-
-        // Update headers periodically
-        if self.last_header_update.elapsed().as_secs() >= 1 {
-            self.update_headers()?;
-            self.last_header_update = Instant::now();
+    /// Write one sample in the file's encoding (little-endian)
+    fn write_sample(&mut self, sample: f32) -> io::Result<()> {
+        match self.sample_format {
+            WavSampleFormat::Float32 => self.writer.write_all(&sample.to_le_bytes()),
+            WavSampleFormat::Pcm16 => self.writer.write_all(&f32_to_i16(sample).to_le_bytes()),
         }
-
-        Ok(())
     }
 
     /// Update the WAV header size fields
     fn update_headers(&mut self) -> io::Result<()> {
         let current_pos = self.writer.stream_position()?;
 
-        // Calculate sizes
         let data_size = self.samples_written * self.bytes_per_sample as u64;
-        let file_size = 36 + data_size; // 36 = header size minus RIFF header
-
-        // Update RIFF chunk size
-        self.writer
-            .seek(SeekFrom::Start(self.riff_chunk_size_pos))?;
-        self.writer.write_all(&(file_size as u32).to_le_bytes())?;
-
-        // Update data chunk size
-        self.writer
-            .seek(SeekFrom::Start(self.data_chunk_size_pos))?;
-        self.writer.write_all(&(data_size as u32).to_le_bytes())?;
+        let frames = self.samples_written / self.channels as u64;
+        write_sizes(
+            &mut self.writer,
+            self.data_chunk_size_pos,
+            data_size,
+            frames,
+            true,
+            self.riff_size_limit,
+        )?;
 
         // Seek back to end and flush
         self.writer.seek(SeekFrom::Start(current_pos))?;
@@ -178,6 +186,54 @@ impl WavWriter {
         Ok(())
     }
 
+    /// Keep only frames `start_frame..end_frame`, shifting them to the start of the data chunk
+    pub fn trim(&mut self, start_frame: u64, end_frame: u64) -> io::Result<()> {
+        let channels = self.channels as u64;
+        let frame_bytes = self.bytes_per_sample as u64 * channels;
+        let total_frames = self.samples_written / channels;
+        let end_frame = end_frame.min(total_frames);
+        let start_frame = start_frame.min(end_frame);
+
+        if start_frame == 0 && end_frame == total_frames {
+            return Ok(());
+        }
+
+        // Work on the file directly - the buffer must be empty first
+        self.writer.flush()?;
+        let data_start = self.data_chunk_size_pos + 4;
+        let kept_bytes = (end_frame - start_frame) * frame_bytes;
+        let file = self.writer.get_mut();
+
+        if start_frame > 0 {
+            let source_start = data_start + start_frame * frame_bytes;
+            let mut buffer = vec![0u8; 64 * 1024];
+            let mut copied = 0u64;
+            while copied < kept_bytes {
+                let len = buffer.len().min((kept_bytes - copied) as usize);
+                file.seek(SeekFrom::Start(source_start + copied))?;
+                file.read_exact(&mut buffer[..len])?;
+                file.seek(SeekFrom::Start(data_start + copied))?;
+                file.write_all(&buffer[..len])?;
+                copied += len as u64;
+            }
+        }
+
+        file.set_len(data_start + kept_bytes)?;
+        self.writer.seek(SeekFrom::Start(data_start + kept_bytes))?;
+        self.samples_written = (end_frame - start_frame) * channels;
+        self.update_headers()?;
+
+        info!(
+            "Trimmed WAV file {:?} to frames {}..{} ({:.2} seconds)",
+            self.file_path,
+            start_frame,
+            end_frame,
+            self.get_duration_seconds()
+        );
+
+        Ok(())
+    }
+
     /// Get the current duration in seconds
     pub fn get_duration_seconds(&self) -> f32 {
         self.samples_written as f32 / (self.sample_rate as f32 * self.channels as f32)
@@ -199,6 +255,96 @@ impl WavWriter {
     }
 }
 
+/// Write the RIFF and data sizes of a file whose data chunk runs to the end.
+///
+/// Switches the file to RF64 when a size no longer fits in 32 bits (and back when it
+/// does again, e.g. after trimming). That needs the 28-byte chunk at `DS64_POS`; files
+/// without it (`has_ds64_slot == false`) are limited to 4 GB.
+pub fn write_size_fields<W: Write + Seek>(
+    writer: &mut W,
+    data_chunk_size_pos: u64,
+    data_size: u64,
+    frames: u64,
+    has_ds64_slot: bool,
+) -> io::Result<()> {
+    write_sizes(
+        writer,
+        data_chunk_size_pos,
+        data_size,
+        frames,
+        has_ds64_slot,
+        RIFF_SIZE_LIMIT,
+    )
+}
+
+fn write_sizes<W: Write + Seek>(
+    writer: &mut W,
+    data_chunk_size_pos: u64,
+    data_size: u64,
+    frames: u64,
+    has_ds64_slot: bool,
+    riff_size_limit: u64,
+) -> io::Result<()> {
+    let riff_size = data_chunk_size_pos + 4 + data_size - 8;
+
+    if riff_size <= riff_size_limit {
+        writer.seek(SeekFrom::Start(0))?;
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(riff_size as u32).to_le_bytes())?;
+        if has_ds64_slot {
+            writer.seek(SeekFrom::Start(DS64_POS))?;
+            writer.write_all(b"JUNK")?;
+        }
+        writer.seek(SeekFrom::Start(data_chunk_size_pos))?;
+        writer.write_all(&(data_size as u32).to_le_bytes())?;
+        return Ok(());
+    }
+
+    if !has_ds64_slot {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Recording exceeds 4 GB and has no room for an RF64 header",
+        ));
+    }
+
+    writer.seek(SeekFrom::Start(0))?;
+    writer.write_all(b"RF64")?;
+    writer.write_all(&RF64_SIZE_MARKER.to_le_bytes())?;
+    writer.seek(SeekFrom::Start(DS64_POS))?;
+    writer.write_all(b"ds64")?;
+    writer.write_all(&DS64_BODY_SIZE.to_le_bytes())?;
+    writer.write_all(&riff_size.to_le_bytes())?;
+    writer.write_all(&data_size.to_le_bytes())?;
+    writer.write_all(&frames.to_le_bytes())?;
+    writer.write_all(&0u32.to_le_bytes())?; // No table entries
+    writer.seek(SeekFrom::Start(data_chunk_size_pos))?;
+    writer.write_all(&RF64_SIZE_MARKER.to_le_bytes())?;
+
+    Ok(())
+}
+
+impl AudioEncoder for WavWriter {
+    fn write_samples(&mut self, samples: &[f32]) -> io::Result<()> {
+        self.write_samples_f32(samples)
+    }
+
+    fn finalize(&mut self) -> io::Result<()> {
+        WavWriter::finalize(self)
+    }
+
+    fn trim(&mut self, start_frame: u64, end_frame: u64) -> io::Result<()> {
+        WavWriter::trim(self, start_frame, end_frame)
+    }
+
+    fn get_metadata(&self) -> (u32, u16, f32) {
+        WavWriter::get_metadata(self)
+    }
+
+    fn bytes_written(&self) -> u64 {
+        buffered_len(&self.writer)
+    }
+}
+
 impl Drop for WavWriter {
     fn drop(&mut self) {
         // Ensure headers are updated when the writer is dropped
@@ -208,3 +354,236 @@ impl Drop for WavWriter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    /// What a reader finds in the file, sizes taken from ds64 when the header says RF64
+    struct ParsedWav {
+        rf64: bool,
+        riff_size: u64,
+        data_size: u64,
+        frames: Option<u64>, // ds64 sample count
+        format_tag: u16,
+        channels: u16,
+        sample_rate: u32,
+        samples: Vec<f32>,
+    }
+
+    fn read_u32(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    fn read_u64(bytes: &[u8], at: usize) -> u64 {
+        u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+    }
+
+    fn read_wav(path: &Path) -> ParsedWav {
+        let bytes = std::fs::read(path).unwrap();
+        let rf64 = match &bytes[0..4] {
+            b"RIFF" => false,
+            b"RF64" => true,
+            other => panic!("not a WAV file: {:?}", other),
+        };
+        assert_eq!(&bytes[8..12], b"WAVE");
+
+        let mut riff_size = read_u32(&bytes, 4) as u64;
+        let (mut ds64_data_size, mut frames) = (None, None);
+        let (mut format_tag, mut channels, mut sample_rate) = (0, 0, 0);
+        let mut position = 12;
+        loop {
+            let id = &bytes[position..position + 4];
+            let size = read_u32(&bytes, position + 4);
+            let body = position + 8;
+            match id {
+                b"ds64" => {
+                    assert!(rf64, "ds64 chunk in a RIFF file");
+                    riff_size = read_u64(&bytes, body);
+                    ds64_data_size = Some(read_u64(&bytes, body + 8));
+                    frames = Some(read_u64(&bytes, body + 16));
+                }
+                b"fmt " => {
+                    format_tag = u16::from_le_bytes([bytes[body], bytes[body + 1]]);
+                    channels = u16::from_le_bytes([bytes[body + 2], bytes[body + 3]]);
+                    sample_rate = read_u32(&bytes, body + 4);
+                }
+                b"data" => {
+                    let data_size = if size == RF64_SIZE_MARKER {
+                        assert!(rf64, "size marker in a RIFF file");
+                        ds64_data_size.expect("RF64 file without ds64")
+                    } else {
+                        size as u64
+                    };
+                    let data = &bytes[body..body + data_size as usize];
+                    let samples = match format_tag {
+                        3 => data
+                            .chunks_exact(4)
+                            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+                            .collect(),
+                        1 => data
+                            .chunks_exact(2)
+                            .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / i16::MAX as f32)
+                            .collect(),
+                        tag => panic!("unexpected format tag {}", tag),
+                    };
+                    return ParsedWav {
+                        rf64,
+                        riff_size,
+                        data_size,
+                        frames,
+                        format_tag,
+                        channels,
+                        sample_rate,
+                        samples,
+                    };
+                }
+                _ => {}
+            }
+            position = body + size as usize + (size as usize & 1);
+        }
+    }
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("noteflux-wav-{}-{}.wav", name, std::process::id()))
+    }
+
+    fn ramp(len: usize) -> Vec<f32> {
+        (0..len).map(|i| (i % 200) as f32 / 100.0 - 1.0).collect()
+    }
+
+    /// Writer that switches to RF64 once the RIFF size passes `limit` instead of 4 GB
+    fn writer_with_limit(
+        path: &Path,
+        channels: u16,
+        format: WavSampleFormat,
+        limit: u64,
+    ) -> WavWriter {
+        let mut writer = WavWriter::new(path.to_path_buf(), 16000, channels, format).unwrap();
+        writer.riff_size_limit = limit;
+        writer
+    }
+
+    /// RIFF size of a file holding `data_size` bytes of audio
+    fn riff_size_for(data_size: u64) -> u64 {
+        DATA_SIZE_POS + 4 + data_size - 8
+    }
+
+    #[test]
+    fn small_recordings_are_plain_wav() {
+        let path = temp_file("small");
+        let samples = ramp(1000);
+        let mut writer = WavWriter::new(path.clone(), 16000, 1, WavSampleFormat::Float32).unwrap();
+        writer.write_samples_f32(&samples).unwrap();
+        writer.finalize().unwrap();
+        drop(writer);
+
+        let wav = read_wav(&path);
+        assert!(!wav.rf64);
+        assert_eq!(wav.riff_size, std::fs::metadata(&path).unwrap().len() - 8);
+        assert_eq!(wav.data_size, 4000);
+        assert_eq!(
+            (wav.format_tag, wav.channels, wav.sample_rate),
+            (3, 1, 16000)
+        );
+        assert_eq!(wav.samples, samples);
+        // The reserved ds64 slot stays a JUNK chunk readers skip
+        assert_eq!(&std::fs::read(&path).unwrap()[12..16], b"JUNK");
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn switches_to_rf64_exactly_past_the_limit() {
+        let limit = riff_size_for(400);
+
+        // At the limit: still a plain WAV
+        let path = temp_file("at-limit");
+        let mut writer = writer_with_limit(&path, 1, WavSampleFormat::Float32, limit);
+        writer.write_samples_f32(&ramp(100)).unwrap();
+        writer.finalize().unwrap();
+        drop(writer);
+        let wav = read_wav(&path);
+        assert!(!wav.rf64);
+        assert_eq!(wav.riff_size, limit);
+        std::fs::remove_file(&path).ok();
+
+        // One sample more: RF64, with the real sizes in ds64
+        let path = temp_file("past-limit");
+        let mut writer = writer_with_limit(&path, 1, WavSampleFormat::Float32, limit);
+        writer.write_samples_f32(&ramp(101)).unwrap();
+        writer.finalize().unwrap();
+        drop(writer);
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(read_u32(&bytes, 4), RF64_SIZE_MARKER);
+        assert_eq!(read_u32(&bytes, DATA_SIZE_POS as usize), RF64_SIZE_MARKER);
+        let wav = read_wav(&path);
+        assert!(wav.rf64);
+        assert_eq!(wav.riff_size, limit + 4);
+        assert_eq!(wav.riff_size, bytes.len() as u64 - 8);
+        assert_eq!(wav.data_size, 404);
+        assert_eq!(wav.frames, Some(101));
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn rf64_round_trips_samples() {
+        let path = temp_file("rf64-float");
+        let samples = ramp(48000);
+        let mut writer = writer_with_limit(&path, 2, WavSampleFormat::Float32, 64 * 1024);
+        for chunk in samples.chunks(960) {
+            writer.write_samples_f32(chunk).unwrap();
+        }
+        writer.finalize().unwrap();
+        drop(writer);
+
+        let wav = read_wav(&path);
+        assert!(wav.rf64);
+        assert_eq!(wav.data_size, 48000 * 4);
+        assert_eq!(wav.frames, Some(24000));
+        assert_eq!(wav.channels, 2);
+        assert_eq!(wav.samples, samples);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn rf64_round_trips_pcm16() {
+        let path = temp_file("rf64-pcm16");
+        let samples = ramp(30000);
+        let mut writer = writer_with_limit(&path, 1, WavSampleFormat::Pcm16, 16 * 1024);
+        writer.write_samples_f32(&samples).unwrap();
+        writer.finalize().unwrap();
+        drop(writer);
+
+        let wav = read_wav(&path);
+        assert!(wav.rf64);
+        assert_eq!(wav.format_tag, 1);
+        assert_eq!(wav.data_size, 60000);
+        assert_eq!(wav.frames, Some(30000));
+        assert_eq!(wav.samples.len(), samples.len());
+        for (read, written) in wav.samples.iter().zip(&samples) {
+            assert!((read - written).abs() <= 1.0 / i16::MAX as f32);
+        }
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn trimming_below_the_limit_goes_back_to_riff() {
+        let path = temp_file("trim");
+        let samples = ramp(4000);
+        let mut writer = writer_with_limit(&path, 1, WavSampleFormat::Float32, 8 * 1024);
+        writer.write_samples_f32(&samples).unwrap();
+        writer.finalize().unwrap();
+        assert!(read_wav(&path).rf64);
+
+        writer.trim(1000, 1500).unwrap();
+        drop(writer);
+
+        let wav = read_wav(&path);
+        assert!(!wav.rf64);
+        assert_eq!(wav.riff_size, std::fs::metadata(&path).unwrap().len() - 8);
+        assert_eq!(wav.samples, samples[1000..1500]);
+        assert_eq!(&std::fs::read(&path).unwrap()[12..16], b"JUNK");
+        std::fs::remove_file(&path).ok();
+    }
+}
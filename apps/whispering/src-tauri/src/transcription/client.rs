@@ -0,0 +1,527 @@
+use crate::recorder::encoder::f32_to_i16;
+use crate::recorder::pipeline::TapMessage;
+use crate::recorder::resampler::FormatConverter;
+use crate::transcription::protocol::{Result, ServerEvent, StreamingTranscriptionConfig};
+use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::time::{sleep_until, timeout, Instant};
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+
+/// How long to wait for the handshake
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long to wait for the last transcripts once all audio has been sent
+const FINISH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Payload of `transcript-partial` / `transcript-final` events
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptUpdate {
+    pub segment_id: String,
+    pub text: String,       // Text of this segment so far
+    pub transcript: String, // Everything heard in the recording so far
+}
+
+/// Payload of the `transcription-finished` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptionFinished {
+    pub text: String,
+}
+
+/// Progress reported by a streaming session
+#[derive(Debug, Clone)]
+pub enum TranscriptionEvent {
+    Partial(TranscriptUpdate),
+    Final(TranscriptUpdate),
+    /// Error reported by the server that did not end the session
+    Error(String),
+}
+
+struct Segment {
+    id: String,
+    text: String,
+    is_final: bool,
+}
+
+/// Segments in the order the server announced them
+#[derive(Default)]
+struct Transcript {
+    segments: Vec<Segment>,
+}
+
+impl Transcript {
+    fn segment(&mut self, id: &str) -> &mut Segment {
+        let index = match self.segments.iter().position(|segment| segment.id == id) {
+            Some(index) => index,
+            None => {
+                self.segments.push(Segment {
+                    id: id.to_string(),
+                    text: String::new(),
+                    is_final: false,
+                });
+                self.segments.len() - 1
+            }
+        };
+        &mut self.segments[index]
+    }
+
+    fn text(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| segment.text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Every announced segment has its final transcript
+    fn is_settled(&self) -> bool {
+        self.segments.iter().all(|segment| segment.is_final)
+    }
+
+    /// Apply a transcript event, returning the update to report (if any)
+    fn apply(&mut self, event: ServerEvent) -> Option<TranscriptionEvent> {
+        let (segment_id, is_final) = match event {
+            ServerEvent::Committed { segment_id } => {
+                self.segment(&segment_id);
+                return None;
+            }
+            ServerEvent::Delta { segment_id, text } => {
+                if text.is_empty() {
+                    return None;
+                }
+                self.segment(&segment_id).text.push_str(&text);
+                (segment_id, false)
+            }
+            ServerEvent::Partial { segment_id, text } => {
+                let segment = self.segment(&segment_id);
+                if segment.is_final || segment.text == text {
+                    return None;
+                }
+                segment.text = text;
+                (segment_id, false)
+            }
+            ServerEvent::Final { segment_id, text } => {
+                let segment = self.segment(&segment_id);
+                segment.text = text;
+                segment.is_final = true;
+                (segment_id, true)
+            }
+            ServerEvent::Error { message } => return Some(TranscriptionEvent::Error(message)),
+        };
+
+        let update = TranscriptUpdate {
+            text: self.segment(&segment_id).text.clone(),
+            segment_id,
+            transcript: self.text(),
+        };
+        Some(if is_final {
+            TranscriptionEvent::Final(update)
+        } else {
+            TranscriptionEvent::Partial(update)
+        })
+    }
+}
+
+/// Stream recorded audio to the provider until the recording ends, reporting transcripts
+/// as they arrive.
+///
+/// `audio` carries the recorder's output (`input_rate` Hz, `input_channels` channels);
+/// it is converted to the mono 16-bit PCM the provider expects. Returns the full
+/// transcript once the server has answered for all audio, or after `FINISH_TIMEOUT`.
+pub async fn run_session(
+    config: StreamingTranscriptionConfig,
+    mut audio: UnboundedReceiver<TapMessage>,
+    input_rate: u32,
+    input_channels: u16,
+    mut on_event: impl FnMut(TranscriptionEvent),
+) -> Result<String> {
+    let provider = config.provider;
+    let request = provider.build_request(&config)?;
+    let endpoint = request.uri().clone();
+
+    let (socket, _) = timeout(CONNECT_TIMEOUT, connect_async(request))
+        .await
+        .map_err(|_| format!("Timed out connecting to {}", endpoint))?
+        .map_err(|e| format!("Failed to connect to {}: {}", endpoint, e))?;
+    let (mut sink, mut stream) = socket.split();
+    info!("Streaming transcription connected to {}", endpoint);
+
+    for message in provider.setup_messages(&config) {
+        sink.send(message)
+            .await
+            .map_err(|e| format!("Failed to configure transcription session: {}", e))?;
+    }
+
+    let mut converter = FormatConverter::new(input_rate, input_channels, provider.sample_rate(), 1);
+    let mut transcript = Transcript::default();
+    let mut deadline: Option<Instant> = None; // Set once all audio has been sent
+    let mut flushed = false; // The server acknowledged the end of the audio
+
+    loop {
+        tokio::select! {
+            message = audio.recv(), if deadline.is_none() => match message {
+                Some(TapMessage::Audio(samples)) => {
+                    let samples: Vec<i16> = converter
+                        .process(samples)
+                        .into_iter()
+                        .map(f32_to_i16)
+                        .collect();
+                    if !samples.is_empty() {
+                        sink.send(provider.audio_message(&samples))
+                            .await
+                            .map_err(|e| format!("Failed to send audio: {}", e))?;
+                    }
+                }
+                // Recording stopped, or the session was closed
+                Some(TapMessage::End) | None => {
+                    for message in provider.finish_messages() {
+                        sink.send(message)
+                            .await
+                            .map_err(|e| format!("Failed to finish audio stream: {}", e))?;
+                    }
+                    deadline = Some(Instant::now() + FINISH_TIMEOUT);
+                    debug!("All audio sent, waiting for the final transcript");
+                }
+            },
+            message = stream.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let Some(event) = provider.parse(text.as_str()) else {
+                        continue;
+                    };
+                    let finishing = deadline.is_some();
+                    if finishing && matches!(event, ServerEvent::Committed { .. }) {
+                        flushed = true;
+                    }
+                    match transcript.apply(event) {
+                        // Typically committing an empty buffer after the server VAD already took it all
+                        Some(TranscriptionEvent::Error(message)) if finishing => {
+                            debug!("Transcription server error after the end of audio: {}", message);
+                            flushed = true;
+                        }
+                        Some(event) => on_event(event),
+                        None => {}
+                    }
+                    if flushed && !provider.closes_after_finish() && transcript.is_settled() {
+                        break;
+                    }
+                }
+                Some(Ok(Message::Close(frame))) => {
+                    debug!("Transcription server closed the connection: {:?}", frame);
+                    break;
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(format!("Transcription connection failed: {}", e)),
+                None => break,
+            },
+            _ = sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                warn!("Timed out waiting for the final transcript");
+                break;
+            }
+        }
+    }
+
+    let _ = sink.close().await;
+    Ok(transcript.text())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::protocol::StreamingProvider;
+    use base64::Engine;
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+    use tokio::task::JoinHandle;
+
+    /// Accept one connection on a local port. `respond` answers each client message;
+    /// `None` drops the connection without a closing handshake.
+    async fn mock_server<F>(mut respond: F) -> (String, JoinHandle<Vec<Message>>)
+    where
+        F: FnMut(&Message) -> Option<Vec<Message>> + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/v1/listen", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            let mut received = Vec::new();
+            while let Some(Ok(message)) = socket.next().await {
+                if message.is_close() {
+                    break;
+                }
+                let Some(replies) = respond(&message) else {
+                    received.push(message);
+                    return received;
+                };
+                received.push(message);
+                for reply in replies {
+                    socket.send(reply).await.unwrap();
+                }
+            }
+            received
+        });
+        (url, server)
+    }
+
+    fn config(provider: StreamingProvider, url: String) -> StreamingTranscriptionConfig {
+        StreamingTranscriptionConfig {
+            provider,
+            api_key: "test-key".to_string(),
+            url: Some(url),
+            model: None,
+            language: None,
+        }
+    }
+
+    /// Queue `blocks` of audio, followed by the end of the recording if `end`
+    fn audio(
+        blocks: &[Vec<f32>],
+        end: bool,
+    ) -> (UnboundedSender<TapMessage>, UnboundedReceiver<TapMessage>) {
+        let (sender, receiver) = unbounded_channel();
+        for block in blocks {
+            sender.send(TapMessage::Audio(block.clone())).unwrap();
+        }
+        if end {
+            sender.send(TapMessage::End).unwrap();
+        }
+        (sender, receiver)
+    }
+
+    fn text_type(message: &Message) -> Option<String> {
+        let value: Value = serde_json::from_str(message.to_text().ok()?).ok()?;
+        value["type"].as_str().map(str::to_string)
+    }
+
+    fn deepgram_results(text: &str, is_final: bool) -> Message {
+        Message::text(
+            json!({
+                "type": "Results",
+                "start": 0.0,
+                "is_final": is_final,
+                "channel": { "alternatives": [{ "transcript": text }] },
+            })
+            .to_string(),
+        )
+    }
+
+    fn pcm16(bytes: &[u8]) -> Vec<i16> {
+        bytes
+            .chunks_exact(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn deepgram_streams_pcm_and_reports_transcripts() {
+        let mut blocks = 0;
+        let (url, server) = mock_server(move |message| {
+            Some(match message {
+                Message::Binary(_) => {
+                    blocks += 1;
+                    if blocks == 1 {
+                        vec![deepgram_results("hello", false)]
+                    } else {
+                        vec![deepgram_results("hello world", true)]
+                    }
+                }
+                _ if text_type(message).as_deref() == Some("CloseStream") => {
+                    vec![Message::Close(None)]
+                }
+                _ => Vec::new(),
+            })
+        })
+        .await;
+
+        // Stereo at the provider's rate, so only the downmix applies
+        let block: Vec<f32> = [0.5, 0.25].repeat(800);
+        let (_sender, receiver) = audio(&[block.clone(), block], true);
+        let mut events = Vec::new();
+        let transcript = run_session(
+            config(StreamingProvider::Deepgram, url),
+            receiver,
+            16000,
+            2,
+            |event| events.push(event),
+        )
+        .await
+        .unwrap();
+        assert_eq!(transcript, "hello world");
+
+        let received = server.await.unwrap();
+        assert_eq!(received.len(), 3);
+        for message in &received[..2] {
+            let Message::Binary(bytes) = message else {
+                panic!("expected audio, got {:?}", message);
+            };
+            let samples = pcm16(bytes);
+            assert_eq!(samples.len(), 800);
+            assert!(samples.iter().all(|&sample| sample == f32_to_i16(0.375)));
+        }
+        assert_eq!(text_type(&received[2]).as_deref(), Some("CloseStream"));
+
+        assert_eq!(events.len(), 2);
+        let TranscriptionEvent::Partial(partial) = &events[0] else {
+            panic!("expected a partial transcript, got {:?}", events[0]);
+        };
+        assert_eq!(partial.text, "hello");
+        let TranscriptionEvent::Final(last) = &events[1] else {
+            panic!("expected a final transcript, got {:?}", events[1]);
+        };
+        assert_eq!(last.segment_id, partial.segment_id);
+        assert_eq!(last.transcript, "hello world");
+    }
+
+    #[tokio::test]
+    async fn openai_configures_the_session_and_settles_after_commit() {
+        let (url, server) = mock_server(|message| {
+            Some(match text_type(message).as_deref() {
+                Some("input_audio_buffer.commit") => [
+                    json!({ "type": "input_audio_buffer.committed", "item_id": "item_1" }),
+                    json!({
+                        "type": "conversation.item.input_audio_transcription.delta",
+                        "item_id": "item_1",
+                        "delta": "Hi",
+                    }),
+                    json!({
+                        "type": "conversation.item.input_audio_transcription.completed",
+                        "item_id": "item_1",
+                        "transcript": "Hi there.",
+                    }),
+                ]
+                .iter()
+                .map(|event| Message::text(event.to_string()))
+                .collect(),
+                _ => Vec::new(),
+            })
+        })
+        .await;
+
+        let (_sender, receiver) = audio(&[vec![-0.5; 2400]], true);
+        let mut events = Vec::new();
+        // The server never closes the connection, the settled transcript ends the session
+        let transcript = run_session(
+            config(StreamingProvider::OpenAiRealtime, url),
+            receiver,
+            24000,
+            1,
+            |event| events.push(event),
+        )
+        .await
+        .unwrap();
+        assert_eq!(transcript, "Hi there.");
+
+        let received = server.await.unwrap();
+        let types: Vec<_> = received.iter().filter_map(text_type).collect();
+        assert_eq!(
+            types,
+            [
+                "transcription_session.update",
+                "input_audio_buffer.append",
+                "input_audio_buffer.commit"
+            ]
+        );
+        let append: Value = serde_json::from_str(received[1].to_text().unwrap()).unwrap();
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(append["audio"].as_str().unwrap())
+            .unwrap();
+        assert_eq!(pcm16(&bytes), vec![f32_to_i16(-0.5); 2400]);
+
+        assert!(matches!(&events[0], TranscriptionEvent::Partial(update) if update.text == "Hi"));
+        assert!(
+            matches!(&events[1], TranscriptionEvent::Final(update) if update.text == "Hi there.")
+        );
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_reported_without_ending_the_session() {
+        let (url, server) = mock_server(|message| {
+            Some(match message {
+                Message::Binary(_) => vec![Message::text(
+                    json!({ "type": "Error", "description": "Unsupported audio" }).to_string(),
+                )],
+                _ if text_type(message).as_deref() == Some("CloseStream") => {
+                    vec![deepgram_results("still here", true), Message::Close(None)]
+                }
+                _ => Vec::new(),
+            })
+        })
+        .await;
+
+        // Errors after the end of the audio are expected, so stop once this one is in
+        let (sender, receiver) = audio(&[vec![0.1; 160]], false);
+        let mut events = Vec::new();
+        let transcript = run_session(
+            config(StreamingProvider::Deepgram, url),
+            receiver,
+            16000,
+            1,
+            |event| {
+                if matches!(event, TranscriptionEvent::Error(_)) {
+                    sender.send(TapMessage::End).unwrap();
+                }
+                events.push(event);
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(transcript, "still here");
+        server.await.unwrap();
+
+        assert!(
+            matches!(&events[0], TranscriptionEvent::Error(message) if message == "Unsupported audio")
+        );
+        assert!(matches!(&events[1], TranscriptionEvent::Final(_)));
+    }
+
+    #[tokio::test]
+    async fn dropped_connection_fails_the_session() {
+        // The session update is the first thing sent, the server hangs up on it
+        let (url, server) = mock_server(|_| None).await;
+
+        // The recording is still running when the connection goes away
+        let (_sender, receiver) = audio(&[], false);
+        let error = run_session(
+            config(StreamingProvider::OpenAiRealtime, url),
+            receiver,
+            24000,
+            1,
+            |_| {},
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            error.starts_with("Transcription connection failed"),
+            "{}",
+            error
+        );
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn unreachable_server_fails_to_connect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/v1/listen", listener.local_addr().unwrap());
+        drop(listener);
+
+        let (_sender, receiver) = audio(&[], false);
+        let error = run_session(
+            config(StreamingProvider::Deepgram, url),
+            receiver,
+            16000,
+            1,
+            |_| {},
+        )
+        .await
+        .unwrap_err();
+        assert!(error.starts_with("Failed to connect"), "{}", error);
+    }
+}
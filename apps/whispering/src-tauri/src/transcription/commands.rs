@@ -0,0 +1,222 @@
+use crate::recorder::AppData;
+use crate::transcription::client::{run_session, TranscriptionEvent, TranscriptionFinished};
+use crate::transcription::local::LocalTranscriptionOptions;
+use crate::transcription::models::{checksum_model, list_models, LocalModel, ModelChecksum};
+use crate::transcription::protocol::{Result, StreamingTranscriptionConfig};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{info, warn};
+#[cfg(feature = "local-whisper")]
+use {
+    crate::transcription::local::LocalTranscriber,
+    crate::transcription::models::validate_model_path, std::path::Path, std::sync::Arc,
+};
+
+/// Application state holding the running streaming transcription and the loaded local model
+#[derive(Default)]
+pub struct TranscriptionState {
+    session: Mutex<Option<JoinHandle<()>>>,
+    #[cfg(feature = "local-whisper")]
+    local_model: Arc<Mutex<Option<Arc<LocalTranscriber>>>>, // Kept between recordings
+}
+
+/// Resolve the models folder - the user's choice or `<app data>/models`
+fn resolve_models_dir(app_handle: &AppHandle, models_dir: Option<String>) -> Result<PathBuf> {
+    if let Some(folder) = models_dir {
+        let path = PathBuf::from(folder);
+        if !path.is_dir() {
+            return Err(format!("Models folder does not exist: {:?}", path));
+        }
+        return Ok(path);
+    }
+
+    let app_data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let default_dir = app_data_dir.join("models");
+    std::fs::create_dir_all(&default_dir)
+        .map_err(|e| format!("Failed to create models dir: {}", e))?;
+    Ok(default_dir)
+}
+
+/// Reuse the loaded model when it is the one asked for, otherwise load (and keep) it
+#[cfg(feature = "local-whisper")]
+fn load_local_model(
+    cache: &Mutex<Option<Arc<LocalTranscriber>>>,
+    model_path: &Path,
+) -> Result<Arc<LocalTranscriber>> {
+    let model_path = validate_model_path(model_path)?;
+    // Held while loading so concurrent requests do not load the model twice
+    let mut cached = cache
+        .lock()
+        .map_err(|e| format!("Failed to lock local model: {}", e))?;
+    if let Some(model) = cached.as_ref().filter(|m| m.model_path() == model_path) {
+        return Ok(model.clone());
+    }
+
+    *cached = None; // Free the previous model before loading the next one
+    let model = Arc::new(LocalTranscriber::load(&model_path)?);
+    *cached = Some(model.clone());
+    Ok(model)
+}
+
+/// Stream the open recording session to a websocket STT endpoint.
+///
+/// Call after `init_recording_session`; audio flows while recording, and the session ends
+/// by itself when the recording stops. Progress is reported through `transcript-partial`,
+/// `transcript-final`, `transcription-error` and `transcription-finished` events.
+#[tauri::command]
+pub async fn start_streaming_transcription(
+    config: StreamingTranscriptionConfig,
+    app_data: State<'_, AppData>,
+    state: State<'_, TranscriptionState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    info!(
+        "Starting streaming transcription: provider={:?}, url={:?}",
+        config.provider, config.url
+    );
+
+    let (audio, sample_rate, channels) = {
+        let recorder = app_data
+            .recorder
+            .lock()
+            .map_err(|e| format!("Failed to lock recorder: {}", e))?;
+        recorder.attach_audio_tap()?
+    };
+
+    let handle = tauri::async_runtime::spawn(async move {
+        let events = app_handle.clone();
+        let result = run_session(config, audio, sample_rate, channels, move |event| {
+            let _ = match event {
+                TranscriptionEvent::Partial(update) => events.emit("transcript-partial", update),
+                TranscriptionEvent::Final(update) => events.emit("transcript-final", update),
+                TranscriptionEvent::Error(message) => events.emit("transcription-error", message),
+            };
+        })
+        .await;
+
+        match result {
+            Ok(text) => {
+                let _ = app_handle.emit("transcription-finished", TranscriptionFinished { text });
+            }
+            Err(e) => {
+                warn!("Streaming transcription failed: {}", e);
+                let _ = app_handle.emit("transcription-error", e);
+            }
+        }
+    });
+
+    let mut session = state
+        .session
+        .lock()
+        .map_err(|e| format!("Failed to lock transcription state: {}", e))?;
+    if let Some(previous) = session.replace(handle) {
+        previous.abort();
+    }
+    Ok(())
+}
+
+/// Drop the running streaming transcription without waiting for its final transcript
+#[tauri::command]
+pub async fn stop_streaming_transcription(state: State<'_, TranscriptionState>) -> Result<()> {
+    let mut session = state
+        .session
+        .lock()
+        .map_err(|e| format!("Failed to lock transcription state: {}", e))?;
+    if let Some(handle) = session.take() {
+        info!("Stopping streaming transcription");
+        handle.abort();
+    }
+    Ok(())
+}
+
+/// Whether this build can transcribe offline (`local-whisper` feature)
+#[tauri::command]
+pub async fn is_local_transcription_available() -> Result<bool> {
+    Ok(cfg!(feature = "local-whisper"))
+}
+
+/// Folder where whisper.cpp models are looked up by default
+#[tauri::command]
+pub async fn get_local_models_dir(app_handle: AppHandle) -> Result<String> {
+    let dir = resolve_models_dir(&app_handle, None)?;
+    Ok(dir.to_string_lossy().to_string())
+}
+
+/// Model files in `models_dir` (or the default folder) with their sizes
+#[tauri::command]
+pub async fn list_local_models(
+    models_dir: Option<String>,
+    app_handle: AppHandle,
+) -> Result<Vec<LocalModel>> {
+    let dir = resolve_models_dir(&app_handle, models_dir)?;
+    list_models(&dir)
+}
+
+/// SHA-256 of a model file, compared against `expected_sha256` when given
+#[tauri::command]
+pub async fn verify_local_model(
+    model_path: String,
+    expected_sha256: Option<String>,
+) -> Result<ModelChecksum> {
+    tauri::async_runtime::spawn_blocking(move || {
+        checksum_model(&PathBuf::from(model_path), expected_sha256.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Checksum task failed: {}", e))?
+}
+
+/// Transcribe a recording (e.g. the file returned by `stop_recording`) on the CPU
+#[tauri::command]
+pub async fn transcribe_recording_locally(
+    file_path: String,
+    model_path: String,
+    options: Option<LocalTranscriptionOptions>,
+    state: State<'_, TranscriptionState>,
+) -> Result<String> {
+    #[cfg(feature = "local-whisper")]
+    {
+        info!("Transcribing {} locally with {}", file_path, model_path);
+        let cache = state.local_model.clone();
+        let options = options.unwrap_or_default();
+        tauri::async_runtime::spawn_blocking(move || {
+            let model = load_local_model(&cache, Path::new(&model_path))?;
+            model.transcribe_file(Path::new(&file_path), &options)
+        })
+        .await
+        .map_err(|e| format!("Local transcription task failed: {}", e))?
+    }
+
+    #[cfg(not(feature = "local-whisper"))]
+    {
+        let _ = (file_path, model_path, options, state);
+        Err(
+            "Local transcription is not available in this build (local-whisper feature)"
+                .to_string(),
+        )
+    }
+}
+
+/// Free the memory held by the loaded local model
+#[tauri::command]
+pub async fn unload_local_model(state: State<'_, TranscriptionState>) -> Result<()> {
+    #[cfg(feature = "local-whisper")]
+    {
+        let mut cached = state
+            .local_model
+            .lock()
+            .map_err(|e| format!("Failed to lock local model: {}", e))?;
+        if cached.take().is_some() {
+            info!("Unloaded local whisper model");
+        }
+    }
+
+    #[cfg(not(feature = "local-whisper"))]
+    let _ = state;
+
+    Ok(())
+}
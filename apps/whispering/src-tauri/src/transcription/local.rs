@@ -0,0 +1,410 @@
+use serde::Deserialize;
+#[cfg(feature = "local-whisper")]
+use {
+    crate::recorder::resampler::FormatConverter,
+    crate::transcription::protocol::Result,
+    audiopus::coder::Decoder,
+    audiopus::packet::Packet,
+    audiopus::{Channels, MutSignals, SampleRate},
+    ogg::reading::PacketReader,
+    std::io::Cursor,
+    std::path::{Path, PathBuf},
+    tracing::info,
+    whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters},
+};
+
+/// Rate whisper models are trained on
+#[cfg(feature = "local-whisper")]
+const WHISPER_SAMPLE_RATE: u32 = 16000;
+
+/// Ogg/Opus streams always decode at 48 kHz (granule positions count at that rate)
+#[cfg(feature = "local-whisper")]
+const OPUS_DECODE_RATE: u32 = 48000;
+
+/// Longest Opus packet, 120 ms at 48 kHz
+#[cfg(feature = "local-whisper")]
+const OPUS_MAX_FRAME: usize = 5760;
+
+/// Local transcription settings - passed from the frontend
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalTranscriptionOptions {
+    /// ISO 639-1 code, or `None` to detect the language
+    pub language: Option<String>,
+    /// CPU threads (defaults to the available cores, at most 8)
+    pub threads: Option<u32>,
+    /// Translate to English instead of transcribing
+    #[serde(default)]
+    pub translate: bool,
+}
+
+/// A whisper.cpp model loaded for CPU inference (`local-whisper` feature)
+#[cfg(feature = "local-whisper")]
+pub struct LocalTranscriber {
+    model_path: PathBuf,
+    context: WhisperContext,
+}
+
+#[cfg(feature = "local-whisper")]
+impl LocalTranscriber {
+    /// Load a model file. Takes a few seconds for the larger models.
+    pub fn load(model_path: &Path) -> Result<Self> {
+        let mut params = WhisperContextParameters::default();
+        params.use_gpu(false);
+
+        let context = WhisperContext::new_with_params(model_path, params)
+            .map_err(|e| format!("Failed to load model {:?}: {}", model_path, e))?;
+        info!("Loaded whisper model {:?}", model_path);
+
+        Ok(Self {
+            model_path: model_path.to_path_buf(),
+            context,
+        })
+    }
+
+    pub fn model_path(&self) -> &Path {
+        &self.model_path
+    }
+
+    /// Transcribe a recording in any format, rate and channel count the recorder writes
+    pub fn transcribe_file(
+        &self,
+        path: &Path,
+        options: &LocalTranscriptionOptions,
+    ) -> Result<String> {
+        let (samples, sample_rate, channels) = read_recording(path)?;
+        let samples =
+            FormatConverter::new(sample_rate, channels, WHISPER_SAMPLE_RATE, 1).process(samples);
+        self.transcribe(&samples, options)
+    }
+
+    /// Transcribe 16 kHz mono audio
+    pub fn transcribe(
+        &self,
+        samples: &[f32],
+        options: &LocalTranscriptionOptions,
+    ) -> Result<String> {
+        let threads = options.threads.unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get().min(8) as u32)
+                .unwrap_or(4)
+        });
+
+        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        params.set_n_threads(threads.max(1) as i32);
+        params.set_translate(options.translate);
+        params.set_language(Some(options.language.as_deref().unwrap_or("auto")));
+        params.set_print_special(false);
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+
+        let mut state = self
+            .context
+            .create_state()
+            .map_err(|e| format!("Failed to create whisper state: {}", e))?;
+        state
+            .full(params, samples)
+            .map_err(|e| format!("Local transcription failed: {}", e))?;
+
+        let mut segments = Vec::new();
+        for segment in state.as_iter() {
+            let text = segment
+                .to_str_lossy()
+                .map_err(|e| format!("Failed to read transcript segment: {}", e))?;
+            let text = text.trim();
+            if !text.is_empty() {
+                segments.push(text.to_string());
+            }
+        }
+
+        info!(
+            "Transcribed {:.1}s of audio locally with {} thread(s)",
+            samples.len() as f32 / WHISPER_SAMPLE_RATE as f32,
+            threads
+        );
+        Ok(segments.join(" "))
+    }
+}
+
+/// Decode a recording into interleaved f32 samples, telling the formats apart by their
+/// signature rather than the file extension
+#[cfg(feature = "local-whisper")]
+fn read_recording(path: &Path) -> Result<(Vec<f32>, u32, u16)> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    match bytes.get(..4) {
+        Some(b"RIFF") | Some(b"RF64") => read_wav(&bytes, path),
+        Some(b"fLaC") => read_flac(&bytes, path),
+        Some(b"OggS") => read_opus(&bytes, path),
+        _ => Err(format!("Unsupported recording format: {:?}", path)),
+    }
+}
+
+/// Read a 32-bit float or 16-bit PCM WAV/RF64 file
+#[cfg(feature = "local-whisper")]
+fn read_wav(bytes: &[u8], path: &Path) -> Result<(Vec<f32>, u32, u16)> {
+    let not_wav = || format!("Not a WAV recording: {:?}", path);
+
+    if bytes.len() < 12 || &bytes[8..12] != b"WAVE" {
+        return Err(not_wav());
+    }
+    let is_rf64 = match &bytes[0..4] {
+        b"RIFF" => false,
+        b"RF64" => true,
+        _ => return Err(not_wav()),
+    };
+
+    let u16_at = |pos: usize| u16::from_le_bytes([bytes[pos], bytes[pos + 1]]);
+    let u32_at = |pos: usize| u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap());
+
+    let mut format: Option<(u16, u16, u32, u16)> = None; // tag, channels, rate, bits
+    let mut rf64_data_size: Option<u64> = None;
+    let mut position = 12usize;
+
+    while position + 8 <= bytes.len() {
+        let id = &bytes[position..position + 4];
+        let size = u32_at(position + 4) as usize;
+        let body = position + 8;
+
+        match id {
+            b"ds64" if is_rf64 && body + 16 <= bytes.len() => {
+                rf64_data_size = Some(u64::from_le_bytes(
+                    bytes[body + 8..body + 16].try_into().unwrap(),
+                ));
+            }
+            b"fmt " if size >= 16 && body + 16 <= bytes.len() => {
+                let mut tag = u16_at(body);
+                // WAVE_FORMAT_EXTENSIBLE keeps the real format in the sub-format GUID
+                if tag == 0xFFFE && size >= 26 && body + 26 <= bytes.len() {
+                    tag = u16_at(body + 24);
+                }
+                format = Some((tag, u16_at(body + 2), u32_at(body + 4), u16_at(body + 14)));
+            }
+            b"data" => {
+                let (tag, channels, sample_rate, bits) = format.ok_or_else(not_wav)?;
+                let declared = match rf64_data_size {
+                    Some(size64) if size as u32 == u32::MAX => size64 as usize,
+                    _ => size,
+                };
+                let data = &bytes[body..body + declared.min(bytes.len() - body)];
+
+                let samples = match (tag, bits) {
+                    (3, 32) => data
+                        .chunks_exact(4)
+                        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                        .collect(),
+                    (1, 16) => data
+                        .chunks_exact(2)
+                        .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+                        .collect(),
+                    _ => {
+                        return Err(format!(
+                            "Unsupported WAV encoding (format {}, {} bits) in {:?}",
+                            tag, bits, path
+                        ))
+                    }
+                };
+                return Ok((samples, sample_rate, channels.max(1)));
+            }
+            _ => {}
+        }
+
+        // Chunks are word aligned
+        position = body + size + (size & 1);
+    }
+
+    Err(not_wav())
+}
+
+/// Read a FLAC file of any bit depth
+#[cfg(feature = "local-whisper")]
+fn read_flac(bytes: &[u8], path: &Path) -> Result<(Vec<f32>, u32, u16)> {
+    let invalid = |e: claxon::Error| format!("Invalid FLAC recording {:?}: {}", path, e);
+    let mut reader = claxon::FlacReader::new(Cursor::new(bytes)).map_err(invalid)?;
+    let info = reader.streaminfo();
+    let scale = 1.0 / (1u64 << (info.bits_per_sample - 1)) as f32;
+
+    let samples = reader
+        .samples()
+        .map(|sample| sample.map(|sample| sample as f32 * scale))
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(invalid)?;
+    Ok((samples, info.sample_rate, info.channels as u16))
+}
+
+/// Read an Ogg/Opus file, dropping the encoder delay and the padding of the last frame
+#[cfg(feature = "local-whisper")]
+fn read_opus(bytes: &[u8], path: &Path) -> Result<(Vec<f32>, u32, u16)> {
+    let invalid = |e: String| format!("Invalid Opus recording {:?}: {}", path, e);
+    let mut reader = PacketReader::new(Cursor::new(bytes));
+
+    // Identification header (RFC 7845 section 5.1)
+    let head = reader
+        .read_packet()
+        .map_err(|e| invalid(e.to_string()))?
+        .filter(|packet| packet.data.len() >= 19 && packet.data.starts_with(b"OpusHead"))
+        .ok_or_else(|| invalid("missing OpusHead".to_string()))?;
+    let channels = head.data[9] as usize;
+    let pre_skip = u16::from_le_bytes([head.data[10], head.data[11]]) as usize;
+    let opus_channels = match channels {
+        1 => Channels::Mono,
+        2 => Channels::Stereo,
+        _ => return Err(invalid(format!("{} channels", channels))),
+    };
+
+    let mut decoder =
+        Decoder::new(SampleRate::Hz48000, opus_channels).map_err(|e| invalid(e.to_string()))?;
+    let mut frame = vec![0.0f32; OPUS_MAX_FRAME * channels];
+    let mut samples = Vec::new();
+    let mut end_granule = 0u64;
+
+    while let Some(packet) = reader.read_packet().map_err(|e| invalid(e.to_string()))? {
+        if packet.data.starts_with(b"OpusTags") {
+            continue;
+        }
+        let input = Packet::try_from(packet.data.as_slice()).map_err(|e| invalid(e.to_string()))?;
+        let output = MutSignals::try_from(&mut frame[..]).map_err(|e| invalid(e.to_string()))?;
+        let decoded = decoder
+            .decode_float(Some(input), output, false)
+            .map_err(|e| invalid(e.to_string()))?;
+        samples.extend_from_slice(&frame[..decoded * channels]);
+        if packet.last_in_page() {
+            end_granule = packet.absgp_page();
+        }
+    }
+
+    // The last granule position counts the real audio after the pre-skip
+    let frames = (end_granule as usize).saturating_sub(pre_skip);
+    let end = ((pre_skip + frames) * channels).min(samples.len());
+    let start = (pre_skip * channels).min(end);
+    Ok((
+        samples[start..end].to_vec(),
+        OPUS_DECODE_RATE,
+        channels as u16,
+    ))
+}
+
+#[cfg(all(test, feature = "local-whisper"))]
+mod tests {
+    use super::*;
+    use crate::recorder::encoder::AudioEncoder;
+    use crate::recorder::flac_writer::FlacWriter;
+    use crate::recorder::opus_writer::OpusWriter;
+    use crate::recorder::wav_writer::{WavSampleFormat, WavWriter};
+
+    type CreateEncoder = fn(PathBuf) -> Box<dyn AudioEncoder>;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("noteflux-local-{}-{}", std::process::id(), name))
+    }
+
+    /// Half a second of a stereo tone, different in each channel
+    fn tone(sample_rate: u32) -> Vec<f32> {
+        (0..sample_rate as usize / 2)
+            .flat_map(|i| {
+                let t = i as f32 / sample_rate as f32;
+                let left = (t * 440.0 * std::f32::consts::TAU).sin() * 0.5;
+                [left, left * 0.5]
+            })
+            .collect()
+    }
+
+    fn record(path: &Path, mut encoder: Box<dyn AudioEncoder>, samples: &[f32]) {
+        for chunk in samples.chunks(1000) {
+            encoder.write_samples(chunk).unwrap();
+        }
+        encoder.finalize().unwrap();
+        drop(encoder);
+        assert!(path.exists());
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    fn max_error(decoded: &[f32], expected: &[f32]) -> f32 {
+        assert_eq!(decoded.len(), expected.len());
+        decoded
+            .iter()
+            .zip(expected)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn reads_lossless_recordings() {
+        let samples = tone(44100);
+        let cases: [(&str, CreateEncoder, f32); 3] = [
+            (
+                "float.wav",
+                |path| Box::new(WavWriter::new(path, 44100, 2, WavSampleFormat::Float32).unwrap()),
+                0.0,
+            ),
+            (
+                "pcm16.wav",
+                |path| Box::new(WavWriter::new(path, 44100, 2, WavSampleFormat::Pcm16).unwrap()),
+                1.0 / 32767.0,
+            ),
+            (
+                "flac.flac",
+                |path| Box::new(FlacWriter::new(path, 44100, 2).unwrap()),
+                1.0 / 32767.0,
+            ),
+        ];
+
+        for (name, create, tolerance) in cases {
+            let path = temp_path(name);
+            record(&path, create(path.clone()), &samples);
+
+            let (decoded, sample_rate, channels) = read_recording(&path).unwrap();
+            assert_eq!((sample_rate, channels), (44100, 2), "{}", name);
+            assert!(max_error(&decoded, &samples) <= tolerance, "{}", name);
+            std::fs::remove_file(&path).ok();
+        }
+    }
+
+    #[test]
+    fn reads_opus_recordings_at_48_khz() {
+        let samples = tone(16000);
+        let path = temp_path("speech.opus");
+        record(
+            &path,
+            Box::new(OpusWriter::new(path.clone(), 16000, 2).unwrap()),
+            &samples,
+        );
+
+        let (decoded, sample_rate, channels) = read_recording(&path).unwrap();
+        assert_eq!((sample_rate, channels), (48000, 2));
+        // Same duration once the encoder delay and padding are gone
+        assert_eq!(decoded.len(), samples.len() * 3);
+
+        // Lossy, but the level of each channel survives
+        let channel = |samples: &[f32], index: usize| -> Vec<f32> {
+            samples.iter().skip(index).step_by(2).copied().collect()
+        };
+        for index in 0..2 {
+            let expected = rms(&channel(&samples, index));
+            let actual = rms(&channel(&decoded, index));
+            assert!(
+                (actual / expected - 1.0).abs() < 0.1,
+                "{} vs {}",
+                actual,
+                expected
+            );
+        }
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        let path = temp_path("notes.txt");
+        std::fs::write(&path, b"not audio").unwrap();
+        let error = read_recording(&path).unwrap_err();
+        assert!(
+            error.starts_with("Unsupported recording format"),
+            "{}",
+            error
+        );
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,18 @@
+pub mod client;
+pub mod commands;
+pub mod local;
+pub mod models;
+pub mod protocol;
+
+// Export everything from commands for easy access
+pub use commands::{
+    get_local_models_dir, is_local_transcription_available, list_local_models,
+    start_streaming_transcription, stop_streaming_transcription, transcribe_recording_locally,
+    unload_local_model, verify_local_model, TranscriptionState,
+};
+
+// Export key types
+pub use client::{TranscriptUpdate, TranscriptionFinished};
+pub use local::LocalTranscriptionOptions;
+pub use models::{LocalModel, ModelChecksum};
+pub use protocol::{StreamingProvider, StreamingTranscriptionConfig};
@@ -0,0 +1,109 @@
+use crate::transcription::protocol::Result;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+/// First four bytes of a whisper.cpp (ggml) model file
+const GGML_MAGIC: u32 = 0x6767_6d6c;
+
+/// A whisper.cpp model file found in the models folder - returned to frontend
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalModel {
+    pub name: String, // File name, e.g. "ggml-base.en.bin"
+    pub file_path: String,
+    pub size_bytes: u64,
+    pub is_valid: bool, // Starts with the ggml magic
+}
+
+/// Result of hashing a model file - returned to frontend
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelChecksum {
+    pub file_path: String,
+    pub size_bytes: u64,
+    pub sha256: String,        // Lowercase hex
+    pub matches: Option<bool>, // Only when an expected checksum was given
+}
+
+/// Model files (`*.bin`) in `dir`, sorted by name
+pub fn list_models(dir: &Path) -> Result<Vec<LocalModel>> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Failed to read models folder {:?}: {}", dir, e))?;
+
+    let mut models: Vec<LocalModel> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("bin"))
+        })
+        .filter_map(|path| {
+            let size_bytes = fs::metadata(&path).ok().filter(|m| m.is_file())?.len();
+            Some(LocalModel {
+                name: path.file_name()?.to_string_lossy().to_string(),
+                file_path: path.to_string_lossy().to_string(),
+                size_bytes,
+                is_valid: has_ggml_magic(&path),
+            })
+        })
+        .collect();
+
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+    debug!("Found {} model file(s) in {:?}", models.len(), dir);
+    Ok(models)
+}
+
+/// Hash a model file and compare it to `expected_sha256` (hex, any case) when given.
+///
+/// Reads the whole file - run it off the async runtime.
+pub fn checksum_model(path: &Path, expected_sha256: Option<&str>) -> Result<ModelChecksum> {
+    let mut file =
+        File::open(path).map_err(|e| format!("Failed to open model {:?}: {}", path, e))?;
+
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1 << 20];
+    let mut size_bytes = 0u64;
+    loop {
+        let read = match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(format!("Failed to read model {:?}: {}", path, e)),
+        };
+        hasher.update(&buffer[..read]);
+        size_bytes += read as u64;
+    }
+
+    let sha256 = format!("{:x}", hasher.finalize());
+    let matches = expected_sha256.map(|expected| expected.trim().eq_ignore_ascii_case(&sha256));
+    info!("Model {:?}: {} bytes, sha256 {}", path, size_bytes, sha256);
+
+    Ok(ModelChecksum {
+        file_path: path.to_string_lossy().to_string(),
+        size_bytes,
+        sha256,
+        matches,
+    })
+}
+
+/// Check that `path` looks like a whisper.cpp model before handing it to the loader
+pub fn validate_model_path(path: &Path) -> Result<PathBuf> {
+    if !path.is_file() {
+        return Err(format!("Model file does not exist: {:?}", path));
+    }
+    if !has_ggml_magic(path) {
+        return Err(format!("Not a whisper.cpp (ggml) model: {:?}", path));
+    }
+    Ok(path.to_path_buf())
+}
+
+fn has_ggml_magic(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|_| u32::from_le_bytes(magic) == GGML_MAGIC)
+}
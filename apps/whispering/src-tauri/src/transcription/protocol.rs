@@ -0,0 +1,230 @@
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::handshake::client::Request;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+
+pub type Result<T> = std::result::Result<T, String>;
+
+/// Wire protocol spoken by the streaming endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StreamingProvider {
+    /// OpenAI realtime transcription sessions (and compatible servers)
+    OpenAiRealtime,
+    /// Deepgram live transcription (and compatible servers)
+    Deepgram,
+}
+
+/// Streaming transcription settings - passed from the frontend.
+///
+/// Deliberately not `Debug`, so the API key never ends up in the logs.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamingTranscriptionConfig {
+    pub provider: StreamingProvider,
+    pub api_key: String,
+    /// Endpoint override for self-hosted or compatible servers (query parameters are appended)
+    pub url: Option<String>,
+    pub model: Option<String>,
+    pub language: Option<String>,
+}
+
+/// What the server told us, reduced to what the client acts on
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerEvent {
+    /// A segment was cut from the audio and will be transcribed (OpenAI)
+    Committed {
+        segment_id: String,
+    },
+    /// Text to append to a segment's transcript (OpenAI)
+    Delta {
+        segment_id: String,
+        text: String,
+    },
+    /// Interim transcript that replaces the segment's text (Deepgram)
+    Partial {
+        segment_id: String,
+        text: String,
+    },
+    /// Final transcript of a segment
+    Final {
+        segment_id: String,
+        text: String,
+    },
+    Error {
+        message: String,
+    },
+}
+
+impl StreamingProvider {
+    /// Rate of the mono 16-bit PCM the provider expects
+    pub fn sample_rate(self) -> u32 {
+        match self {
+            Self::OpenAiRealtime => 24000,
+            Self::Deepgram => 16000,
+        }
+    }
+
+    fn default_url(self) -> &'static str {
+        match self {
+            Self::OpenAiRealtime => "wss://api.openai.com/v1/realtime",
+            Self::Deepgram => "wss://api.deepgram.com/v1/listen",
+        }
+    }
+
+    /// Websocket handshake request, with the audio format and authentication
+    pub fn build_request(self, config: &StreamingTranscriptionConfig) -> Result<Request> {
+        let query = match self {
+            Self::OpenAiRealtime => "intent=transcription".to_string(),
+            Self::Deepgram => {
+                let mut query = format!(
+                    "encoding=linear16&sample_rate={}&channels=1&interim_results=true&smart_format=true&model={}",
+                    self.sample_rate(),
+                    config.model.as_deref().unwrap_or("nova-3")
+                );
+                if let Some(language) = config.language.as_deref() {
+                    query.push_str("&language=");
+                    query.push_str(language);
+                }
+                query
+            }
+        };
+
+        let base = config.url.as_deref().unwrap_or(self.default_url());
+        let separator = if base.contains('?') { '&' } else { '?' };
+        let url = format!("{}{}{}", base, separator, query);
+
+        let mut request = url
+            .as_str()
+            .into_client_request()
+            .map_err(|e| format!("Invalid streaming URL {}: {}", url, e))?;
+
+        let authorization = match self {
+            Self::OpenAiRealtime => format!("Bearer {}", config.api_key),
+            Self::Deepgram => format!("Token {}", config.api_key),
+        };
+        let headers = request.headers_mut();
+        headers.insert(
+            "Authorization",
+            HeaderValue::from_str(&authorization)
+                .map_err(|_| "API key contains invalid characters".to_string())?,
+        );
+        if self == Self::OpenAiRealtime {
+            headers.insert("OpenAI-Beta", HeaderValue::from_static("realtime=v1"));
+        }
+
+        Ok(request)
+    }
+
+    /// Messages sent right after connecting
+    pub fn setup_messages(self, config: &StreamingTranscriptionConfig) -> Vec<Message> {
+        match self {
+            Self::OpenAiRealtime => {
+                let mut transcription = json!({
+                    "model": config.model.as_deref().unwrap_or("gpt-4o-transcribe"),
+                });
+                if let Some(language) = config.language.as_deref() {
+                    transcription["language"] = json!(language);
+                }
+                let update = json!({
+                    "type": "transcription_session.update",
+                    "session": {
+                        "input_audio_format": "pcm16",
+                        "input_audio_transcription": transcription,
+                        "turn_detection": { "type": "server_vad" },
+                    },
+                });
+                vec![Message::text(update.to_string())]
+            }
+            // Everything is configured through the URL
+            Self::Deepgram => Vec::new(),
+        }
+    }
+
+    /// One block of mono 16-bit PCM at `sample_rate()`
+    pub fn audio_message(self, samples: &[i16]) -> Message {
+        let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        match self {
+            Self::OpenAiRealtime => {
+                let append = json!({
+                    "type": "input_audio_buffer.append",
+                    "audio": base64::engine::general_purpose::STANDARD.encode(bytes),
+                });
+                Message::text(append.to_string())
+            }
+            Self::Deepgram => Message::binary(bytes),
+        }
+    }
+
+    /// Messages that flush the remaining audio once the recording is over
+    pub fn finish_messages(self) -> Vec<Message> {
+        let message = match self {
+            Self::OpenAiRealtime => json!({ "type": "input_audio_buffer.commit" }),
+            Self::Deepgram => json!({ "type": "CloseStream" }),
+        };
+        vec![Message::text(message.to_string())]
+    }
+
+    /// Whether the server closes the connection by itself after `finish_messages`
+    pub fn closes_after_finish(self) -> bool {
+        self == Self::Deepgram
+    }
+
+    /// Interpret a text frame. Unknown or malformed messages are ignored.
+    pub fn parse(self, text: &str) -> Option<ServerEvent> {
+        let message: Value = serde_json::from_str(text).ok()?;
+        let kind = message["type"].as_str()?;
+        let string = |value: &Value| value.as_str().unwrap_or_default().to_string();
+
+        match self {
+            Self::OpenAiRealtime => match kind {
+                "input_audio_buffer.committed" => Some(ServerEvent::Committed {
+                    segment_id: string(&message["item_id"]),
+                }),
+                "conversation.item.input_audio_transcription.delta" => Some(ServerEvent::Delta {
+                    segment_id: string(&message["item_id"]),
+                    text: string(&message["delta"]),
+                }),
+                "conversation.item.input_audio_transcription.completed" => {
+                    Some(ServerEvent::Final {
+                        segment_id: string(&message["item_id"]),
+                        text: string(&message["transcript"]),
+                    })
+                }
+                // Both carry the details in an `error` object
+                "conversation.item.input_audio_transcription.failed" | "error" => {
+                    Some(ServerEvent::Error {
+                        message: message["error"]["message"]
+                            .as_str()
+                            .unwrap_or("Unknown transcription error")
+                            .to_string(),
+                    })
+                }
+                _ => None,
+            },
+            Self::Deepgram => match kind {
+                "Results" => {
+                    // Segments are keyed by where they start in the audio
+                    let segment_id = format!("{:.3}", message["start"].as_f64().unwrap_or(0.0));
+                    let text = string(&message["channel"]["alternatives"][0]["transcript"]);
+                    if message["is_final"].as_bool().unwrap_or(false) {
+                        Some(ServerEvent::Final { segment_id, text })
+                    } else {
+                        Some(ServerEvent::Partial { segment_id, text })
+                    }
+                }
+                "Error" => Some(ServerEvent::Error {
+                    message: message["description"]
+                        .as_str()
+                        .or(message["message"].as_str())
+                        .unwrap_or("Unknown transcription error")
+                        .to_string(),
+                }),
+                _ => None,
+            },
+        }
+    }
+}
@@ -0,0 +1,20 @@
+/**
+ * File formats the desktop recorder can write (`RecordingFormat` on the Rust side)
+ */
+
+export const DESKTOP_RECORDING_FORMATS = [
+	'wavFloat',
+	'wavPcm16',
+	'flac',
+	'opus',
+] as const;
+
+export type DesktopRecordingFormat =
+	(typeof DESKTOP_RECORDING_FORMATS)[number];
+
+export const DESKTOP_RECORDING_FORMAT_OPTIONS = [
+	{ label: 'WAV (32-bit float): Lossless, largest files', value: 'wavFloat' },
+	{ label: 'WAV (16-bit): Lossless for speech, half the size', value: 'wavPcm16' },
+	{ label: 'FLAC: Lossless and compressed', value: 'flac' },
+	{ label: 'Ogg/Opus: Smallest files, lossy', value: 'opus' },
+] as const satisfies { label: string; value: DesktopRecordingFormat }[];
@@ -4,6 +4,12 @@ export {
 	DEFAULT_BITRATE_KBPS,
 } from './bitrate';
 
+export {
+	DESKTOP_RECORDING_FORMAT_OPTIONS,
+	DESKTOP_RECORDING_FORMATS,
+	type DesktopRecordingFormat,
+} from './desktop-formats';
+
 export {
 	RECORDING_MODE_OPTIONS,
 	RECORDING_MODES,
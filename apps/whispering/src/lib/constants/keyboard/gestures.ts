@@ -0,0 +1,14 @@
+/**
+ * Gestures a Fn or modifier-only global shortcut can be bound to
+ */
+
+export const SHORTCUT_GESTURES = ['press', 'tap', 'doubleTap', 'hold'] as const;
+
+export type ShortcutGesture = (typeof SHORTCUT_GESTURES)[number];
+
+export const SHORTCUT_GESTURE_OPTIONS = [
+	{ label: 'Press', value: 'press' },
+	{ label: 'Tap', value: 'tap' },
+	{ label: 'Double tap', value: 'doubleTap' },
+	{ label: 'Hold', value: 'hold' },
+] as const satisfies { label: string; value: ShortcutGesture }[];
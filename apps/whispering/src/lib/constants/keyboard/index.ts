@@ -8,6 +8,12 @@ export {
 	type AcceleratorModifier,
 } from './accelerator/supported-keys';
 
+export {
+	SHORTCUT_GESTURE_OPTIONS,
+	SHORTCUT_GESTURES,
+	type ShortcutGesture,
+} from './gestures';
+
 export type { KeyboardEventPossibleKey } from './browser/possible-keys';
 
 export {
@@ -1,3 +1,5 @@
+import type { TextDeliveryOptions } from '$lib/services/clipboard';
+
 import * as services from '$lib/services';
 
 import { defineMutation } from './_client';
@@ -10,8 +12,26 @@ export const clipboard = {
 	}),
 	typeAtCursor: defineMutation({
 		mutationKey: ['clipboard', 'typeAtCursor'],
-		resultMutationFn: ({ text, keepWindowVisible, refocusWindow }: { text: string; keepWindowVisible?: boolean; refocusWindow?: boolean }) =>
-			services.clipboard.typeAtCursor(text, keepWindowVisible, refocusWindow),
+		resultMutationFn: ({
+			text,
+			keepWindowVisible,
+			refocusWindow,
+			restoreClipboardAfterMs,
+			delivery,
+		}: {
+			text: string;
+			keepWindowVisible?: boolean;
+			refocusWindow?: boolean;
+			restoreClipboardAfterMs?: number;
+			delivery?: TextDeliveryOptions;
+		}) =>
+			services.clipboard.typeAtCursor(
+				text,
+				keepWindowVisible,
+				refocusWindow,
+				restoreClipboardAfterMs,
+				delivery,
+			),
 	}),
 	pasteFromClipboard: defineMutation({
 		mutationKey: ['clipboard', 'pasteFromClipboard'],
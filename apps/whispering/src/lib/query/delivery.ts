@@ -1,5 +1,8 @@
 import type { NoteFluxError } from '$lib/result';
-import type { ClipboardServiceError } from '$lib/services/clipboard';
+import type {
+	ClipboardServiceError,
+	TextDeliveryOptions,
+} from '$lib/services/clipboard';
 
 import { WHISPERING_RECORDINGS_PATHNAME } from '$lib/constants/app';
 import { settings } from '$lib/stores/settings.svelte';
@@ -22,6 +25,8 @@ export const delivery = {
 	 * The user's preferences are read from:
 	 * - `transcription.clipboard.copyOnSuccess` - Whether to auto-copy
 	 * - `transcription.clipboard.pasteOnSuccess` - Whether to auto-paste
+	 * - `transcription.clipboard.restoreAfterPaste` - Whether to put the previous
+	 *   clipboard contents back after pasting (desktop writes the clipboard itself then)
 	 *
 	 * @param text - The transcribed text to deliver
 	 * @param toastId - Unique ID for toast notifications to prevent duplicates
@@ -142,14 +147,27 @@ export const delivery = {
 				return Ok(undefined);
 			}
 
+			// When the clipboard is restored after pasting, write_text snapshots it before
+			// copying the transcript, so the copy must not happen here
+			const restoreClipboardAfterMs =
+				window.__TAURI_INTERNALS__ &&
+				settings.value['transcription.clipboard.pasteOnSuccess'] &&
+				settings.value['transcription.clipboard.restoreAfterPaste']
+					? Number(settings.value['transcription.clipboard.restoreDelayMs'])
+					: undefined;
+
+			const textDelivery = getTextDeliveryOptions();
+
 			// Try to copy to clipboard
-			const { error: copyError } = await rpc.clipboard.copyToClipboard.execute({
-				text,
-			});
-			if (copyError) {
-				warnAutoCopyFailed(copyError);
-				offerManualCopy();
-				return Ok(undefined);
+			if (restoreClipboardAfterMs === undefined) {
+				const { error: copyError } = await rpc.clipboard.copyToClipboard.execute({
+					text,
+				});
+				if (copyError) {
+					warnAutoCopyFailed(copyError);
+					offerManualCopy();
+					return Ok(undefined);
+				}
 			}
 
 			// If user doesn't want auto-paste, confirm copy only
@@ -195,6 +213,8 @@ export const delivery = {
 				text,
 				keepWindowVisible: shouldKeepVisible || undefined,
 			refocusWindow: shouldRefocus || undefined,
+			restoreClipboardAfterMs,
+			delivery: textDelivery,
 			});
 			pasteError = error;
 			if (pasteError) {
@@ -205,6 +225,22 @@ export const delivery = {
 					settings.updateKey('onboarding.pasteTestCompleted', true);
 				}
 			}
+		} else if (
+			restoreClipboardAfterMs !== undefined ||
+			(textDelivery && textDelivery.strategy !== 'clipboardPaste')
+		) {
+				// Nothing was copied above, or the text is typed/inserted instead of pasted:
+				// write_text delivers it (and restores the clipboard after a paste)
+				const { error } = await rpc.clipboard.typeAtCursor.execute({
+					text,
+					keepWindowVisible: true,
+					restoreClipboardAfterMs,
+					delivery: textDelivery,
+				});
+				pasteError = error;
+				if (pasteError) {
+					console.error('[DELIVERY] typeAtCursor failed:', pasteError);
+				}
 		} else {
 				// For local shortcuts, use standard paste (Cmd+V/Ctrl+V)
 				const { error } = await rpc.clipboard.pasteFromClipboard.execute(undefined);
@@ -216,6 +252,10 @@ export const delivery = {
 			}
 
 			if (pasteError) {
+				// write_text may have failed before copying, leave the text for a manual paste
+				if (restoreClipboardAfterMs !== undefined) {
+					await rpc.clipboard.copyToClipboard.execute({ text });
+				}
 				warnPasteFailedButCopied(pasteError);
 				confirmTextInClipboard();
 				return Ok(undefined);
@@ -377,6 +417,8 @@ export const delivery = {
 				return Ok(undefined);
 			}
 
+			const textDelivery = getTextDeliveryOptions();
+
 			// Try to paste at cursor - use different methods based on how recording was initiated
 
 			// No delay needed since we use notifications instead of overlays
@@ -414,6 +456,7 @@ export const delivery = {
 				text,
 			keepWindowVisible: shouldKeepVisible || undefined,
 				refocusWindow: shouldRefocus || undefined,
+				delivery: textDelivery,
 			});
 			pasteError = error;
 			if (pasteError) {
@@ -424,6 +467,17 @@ export const delivery = {
 					settings.updateKey('onboarding.pasteTestCompleted', true);
 				}
 			}
+		} else if (textDelivery && textDelivery.strategy !== 'clipboardPaste') {
+				// Typed or inserted instead of pasted
+				const { error } = await rpc.clipboard.typeAtCursor.execute({
+					text,
+					keepWindowVisible: true,
+					delivery: textDelivery,
+				});
+				pasteError = error;
+				if (pasteError) {
+					console.error('[DELIVERY] typeAtCursor failed:', pasteError);
+				}
 		} else {
 				// For local shortcuts, use standard paste (Cmd+V/Ctrl+V)
 				const { error } = await rpc.clipboard.pasteFromClipboard.execute(undefined);
@@ -446,3 +500,17 @@ export const delivery = {
 		},
 	}),
 };
+
+/** Strategy settings passed to write_text; undefined on the web */
+function getTextDeliveryOptions(): TextDeliveryOptions | undefined {
+	if (!window.__TAURI_INTERNALS__) return undefined;
+	return {
+		strategy: settings.value['delivery.strategy'],
+		fallback: settings.value['delivery.fallback'],
+		typing: {
+			charDelayMs: Number(settings.value['delivery.typing.charDelayMs']),
+			unicode: settings.value['delivery.typing.unicode'],
+		},
+		profiles: settings.value['delivery.profiles'],
+	};
+}
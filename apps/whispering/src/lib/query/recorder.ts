@@ -20,6 +20,21 @@ const recorderKeys = {
 const invalidateRecorderState = () =>
 	queryClient.invalidateQueries({ queryKey: recorderKeys.currentRecordingId });
 
+/**
+ * Duration and size limits from the desktop settings, null when neither is set
+ */
+function desktopRecordingLimits() {
+	const maxSeconds = Number(
+		settings.value['recording.desktop.maxDurationSeconds'],
+	);
+	const maxSizeMb = Number(settings.value['recording.desktop.maxSizeMb']);
+	if (!maxSeconds && !maxSizeMb) return null;
+	return {
+		maxBytes: maxSizeMb ? maxSizeMb * 1024 * 1024 : undefined,
+		maxSeconds: maxSeconds || undefined,
+	};
+}
+
 export const recorder = {
 	cancelRecording: defineMutation({
 		mutationKey: recorderKeys.cancelRecording,
@@ -98,9 +113,22 @@ export const recorder = {
 				selectedDeviceId: settings.value['recording.selectedDeviceId'],
 				...(window.__TAURI_INTERNALS__
 					? {
+							format: settings.value['recording.desktop.format'],
+							limits: desktopRecordingLimits(),
 							outputFolder: settings.value['recording.desktop.outputFolder'],
 							platform: 'desktop' as const,
+							preRollMs: Number(settings.value['recording.desktop.preRollMs']),
 							sampleRate: settings.value['recording.desktop.sampleRate'],
+							vad: settings.value['recording.desktop.vad.enabled']
+								? {
+										autoStopSilenceMs:
+											Number(
+												settings.value['recording.desktop.vad.autoStopSilenceMs'],
+											) || null,
+										trimSilence:
+											settings.value['recording.desktop.vad.trimSilence'],
+									}
+								: null,
 						}
 					: {
 							bitrateKbps: settings.value['recording.navigator.bitrateKbps'],
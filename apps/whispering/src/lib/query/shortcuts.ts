@@ -1,13 +1,18 @@
-import type { KeyboardEventSupportedKey } from '$lib/constants/keyboard';
+import type { KeyboardEventSupportedKey, ShortcutGesture } from '$lib/constants/keyboard';
 import type { Accelerator } from '$lib/services/global-shortcut-manager';
 import type { CommandId } from '$lib/services/local-shortcut-manager';
 
 import { type Command, commandCallbacks } from '$lib/commands';
 import { IS_MACOS } from '$lib/constants/platform';
 import * as services from '$lib/services';
+import { settings } from '$lib/stores/settings.svelte';
 
 import { defineMutation } from './_client';
 
+/** 'press' is the plain binding: every press, release and tap of the key */
+const fnGesture = (gesture: ShortcutGesture) =>
+	gesture === 'press' ? undefined : gesture;
+
 export const shortcuts = {
 	registerCommandGlobally: defineMutation({
 		mutationKey: ['shortcuts', 'registerCommandGlobally'] as const,
@@ -33,6 +38,7 @@ export const shortcuts = {
 				accelerator,
 				command,
 				on: command.on,
+				gesture: fnGesture(settings.value[`shortcuts.gesture.${command.id}`]),
 			});
 		},
 	}),
@@ -56,12 +62,34 @@ export const shortcuts = {
 
 	unregisterCommandGlobally: defineMutation({
 		mutationKey: ['shortcuts', 'unregisterCommandGlobally'] as const,
-		resultMutationFn: async ({ accelerator }: { accelerator: Accelerator }) => {
+		resultMutationFn: async ({
+			accelerator,
+			gesture = 'press',
+		}: {
+			accelerator: Accelerator;
+			/** Without a gesture, every Fn binding of the accelerator is removed */
+			gesture?: ShortcutGesture;
+		}) => {
 			// Use hybrid manager to automatically route Fn shortcuts
-			return await services.hybridShortcutManager.unregister(accelerator);
+			return await services.hybridShortcutManager.unregister(
+				accelerator,
+				fnGesture(gesture),
+			);
 		},
 	}),
 
+	setGestureTiming: defineMutation({
+		mutationKey: ['shortcuts', 'setGestureTiming'] as const,
+		resultMutationFn: () =>
+			services.hybridShortcutManager.setGestureTiming({
+				tapMaxMs: Number(settings.value['shortcuts.gestureTiming.tapMaxMs']),
+				holdMinMs: Number(settings.value['shortcuts.gestureTiming.holdMinMs']),
+				doubleTapWindowMs: Number(
+					settings.value['shortcuts.gestureTiming.doubleTapWindowMs'],
+				),
+			}),
+	}),
+
 	unregisterCommandLocally: defineMutation({
 		mutationKey: ['shortcuts', 'unregisterCommandLocally'] as const,
 		resultMutationFn: async ({ commandId }: { commandId: CommandId }) =>
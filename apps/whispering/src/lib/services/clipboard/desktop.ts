@@ -2,11 +2,12 @@ import { NoteFluxWarningErr } from '$lib/result';
 import { invoke } from '@tauri-apps/api/core';
 import { writeText } from '@tauri-apps/plugin-clipboard-manager';
 import { type } from '@tauri-apps/plugin-os';
+import { extractErrorMessage } from 'wellcrafted/error';
 import { Err, Ok, tryAsync } from 'wellcrafted/result';
 
 import type { ClipboardService } from '.';
 
-import { ClipboardServiceErr } from './types';
+import { ClipboardServiceErr, type TextDeliveryStrategy } from './types';
 
 export function createClipboardServiceDesktop(): ClipboardService {
 	return {
@@ -22,7 +23,7 @@ export function createClipboardServiceDesktop(): ClipboardService {
 				try: () => writeText(text),
 			}),
 
-		typeAtCursor: (text, keepWindowVisible = false, refocusWindow = false) => {
+		typeAtCursor: (text, keepWindowVisible = false, refocusWindow = false, restoreClipboardAfterMs, delivery) => {
 			console.log('🚀 [CLIPBOARD] typeAtCursor called with text length:', text.length, 'keepWindowVisible:', keepWindowVisible, 'refocusWindow:', refocusWindow);
 			return tryAsync({
 				mapErr: (error) => {
@@ -36,9 +37,16 @@ export function createClipboardServiceDesktop(): ClipboardService {
 				},
 				try: async () => {
 					console.log('⌨️ [CLIPBOARD] Invoking write_text command with keepWindowVisible:', keepWindowVisible, 'refocusWindow:', refocusWindow);
-					const result = await invoke<void>('write_text', { text, keepWindowVisible, refocusWindow });
-					console.log('✅ [CLIPBOARD] write_text completed successfully');
-					return result;
+					const strategy = await invoke<TextDeliveryStrategy>('write_text', {
+						text,
+						keepWindowVisible,
+						refocusWindow,
+						restoreClipboardAfterMs: restoreClipboardAfterMs ?? null,
+						// write_text takes the profiles as their own argument
+						delivery: delivery ? { ...delivery, profiles: undefined } : null,
+						profiles: delivery?.profiles ?? null,
+					});
+					console.log('✅ [CLIPBOARD] write_text completed successfully with', strategy);
 				},
 			});
 		},
@@ -107,5 +115,15 @@ export function createClipboardServiceDesktop(): ClipboardService {
 			// If accessibility is enabled but write still failed, propagate original error
 			return Err(pasteError);
 		},
+
+		getTextInputBackend: () =>
+			tryAsync({
+				mapErr: (error) =>
+					ClipboardServiceErr({
+						cause: error,
+						message: extractErrorMessage(error),
+					}),
+				try: () => invoke<string>('get_text_input_backend'),
+			}),
 	};
 }
@@ -38,6 +38,12 @@ export function createClipboardServiceExtension(): ClipboardService {
 					return writeTextToCursor(text);
 				},
 			}),
+
+		getTextInputBackend: async () =>
+			ClipboardServiceErr({
+				cause: undefined,
+				message: 'Sending keys is not supported in the extension.',
+			}),
 	};
 }
 
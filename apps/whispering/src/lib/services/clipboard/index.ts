@@ -1,7 +1,13 @@
 import { createClipboardServiceDesktop } from './desktop';
 import { createClipboardServiceWeb } from './web';
 
-export type { ClipboardService, ClipboardServiceError } from './types';
+export type {
+	ClipboardService,
+	ClipboardServiceError,
+	DeliveryProfile,
+	TextDeliveryOptions,
+	TextDeliveryStrategy,
+} from './types';
 export { ClipboardServiceErr } from './types';
 
 export const ClipboardServiceLive = window.__TAURI_INTERNALS__
@@ -6,6 +6,37 @@ import { createTaggedError } from 'wellcrafted/error';
 const { ClipboardServiceErr, ClipboardServiceError } = createTaggedError(
 	'ClipboardServiceError',
 );
+/** How `typeAtCursor` inserts text on desktop (see `delivery/strategy.rs`) */
+export type TextDeliveryStrategy = 'clipboardPaste' | 'typing' | 'directInsert';
+
+/** Overrides for the app it names (see `delivery/profile.rs`) */
+export type DeliveryProfile = {
+	name: string;
+	/** Bundle identifiers, app names or window classes, compared case-insensitively */
+	apps: string[];
+	/** null keeps the global setting */
+	strategy: TextDeliveryStrategy | null;
+	unicode: 'unicode' | 'keystrokes' | null;
+	pasteKeys: 'standard' | 'controlShiftV' | 'shiftInsert';
+	/** Press Return after the text (sends the message in chat apps) */
+	trailingNewline: boolean;
+};
+
+export type TextDeliveryOptions = {
+	/** Tried first: clipboard paste, simulated typing, or AT-SPI insertion (Linux) */
+	strategy: TextDeliveryStrategy;
+	/** Try the other strategies when `strategy` fails */
+	fallback: boolean;
+	typing: {
+		/** Pause between typed characters */
+		charDelayMs: number;
+		/** 'keystrokes' presses real keys, for VMs and remote desktops (ASCII only) */
+		unicode: 'unicode' | 'keystrokes';
+	};
+	/** The first profile naming the focused app overrides the options above */
+	profiles: DeliveryProfile[];
+};
+
 export type ClipboardService = {
 	/**
 	 * Copies text to the system clipboard.
@@ -16,24 +47,26 @@ export type ClipboardService = {
 	) => Promise<Result<void, ClipboardServiceError>>;
 
 	/**
-	 * Types text directly at the current cursor position, character by character.
-	 * This bypasses the clipboard entirely and is ideal for situations where focus
-	 * may have changed or clipboard paste might not work reliably.
-	 *
-	 * **Note**: This is slower than paste for large text blocks but more reliable.
+	 * Inserts text at the current cursor position of the focused application.
 	 * Best used for global shortcuts where cursor focus needs to be maintained.
 	 *
-	 * - Desktop: Uses Enigo to simulate keyboard typing
+	 * - Desktop: Pastes through the clipboard by default; `delivery` selects simulated
+	 *   typing or AT-SPI insertion instead, falling back to the others on failure
 	 * - Web: Not implemented (falls back to paste)
 	 * - Mobile: Not implemented (falls back to paste)
 	 *
 	 * @param keepWindowVisible If true, keeps the main window visible
 	 * @param refocusWindow If true, refocuses the window before pasting (for onboarding)
+	 * @param restoreClipboardAfterMs If set, puts the previous clipboard contents back
+	 * this long after pasting (desktop only)
+	 * @param delivery Strategy and typing options (desktop only)
 	 */
 	typeAtCursor: (
 		text: string,
 		keepWindowVisible?: boolean,
 		refocusWindow?: boolean,
+		restoreClipboardAfterMs?: number,
+		delivery?: TextDeliveryOptions,
 	) => Promise<Result<void, ClipboardServiceError>>;
 
 	/**
@@ -50,6 +83,15 @@ export type ClipboardService = {
 	pasteFromClipboard: () => MaybePromise<
 		Result<void, ClipboardServiceError | NoteFluxError>
 	>;
+
+	/**
+	 * Name of the backend that sends keys for `typeAtCursor` and `pasteFromClipboard`.
+	 * Errors explain what is missing, e.g. on Wayland without wtype, ydotool or uinput.
+	 *
+	 * - Desktop: "enigo", or "wtype", "ydotool" or "uinput" in Wayland sessions
+	 * - Web: Not supported
+	 */
+	getTextInputBackend: () => Promise<Result<string, ClipboardServiceError>>;
 };
 export { ClipboardServiceErr, ClipboardServiceError };
 
@@ -48,5 +48,11 @@ export function createClipboardServiceWeb(): ClipboardService {
 					'Automatic paste is not supported in web browsers for security reasons. Please paste manually using Cmd/Ctrl+V.',
 			});
 		},
+
+		getTextInputBackend: async () =>
+			ClipboardServiceErr({
+				cause: undefined,
+				message: 'Sending keys is not supported in web browsers.',
+			}),
 	};
 }
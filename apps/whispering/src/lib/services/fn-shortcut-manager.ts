@@ -3,9 +3,28 @@ import { listen } from '@tauri-apps/api/event';
 import type { ShortcutTriggerState } from './_shortcut-trigger-state';
 import { createTaggedError } from 'wellcrafted/error';
 import { Err, Ok, type Result } from 'wellcrafted/result';
+import { describeShortcutError } from './shortcut-registry';
 
 type Accelerator = string;
 
+/**
+ * Gesture a command is bound to. Without one, a command receives every press,
+ * release and tap of its key. Tap and double-tap fire `fn-shortcut-triggered`;
+ * hold fires `fn-shortcut-pressed`/`fn-shortcut-released` once the key is held.
+ */
+export type FnShortcutGesture = 'tap' | 'doubleTap' | 'hold';
+
+/** Gesture timing in milliseconds (omitted values use the 300 ms defaults) */
+export type FnGestureTiming = {
+	tapMaxMs?: number;
+	holdMinMs?: number;
+	doubleTapWindowMs?: number;
+};
+
+/** Key of a registration: one accelerator can carry several gesture bindings */
+const registrationKey = (accelerator: Accelerator, gesture?: FnShortcutGesture) =>
+	gesture ? `${accelerator}#${gesture}` : accelerator;
+
 const { FnShortcutServiceErr, FnShortcutServiceError } = createTaggedError(
 	'FnShortcutServiceError'
 );
@@ -15,14 +34,35 @@ const { FnNotSupportedErr, FnNotSupportedError } = createTaggedError('FnNotSuppo
 type FnNotSupportedError = ReturnType<typeof FnNotSupportedError>;
 
 type RegisteredShortcut = {
+	accelerator: Accelerator;
 	callback: () => void;
 	commandId: string;
+	gesture?: FnShortcutGesture;
 	on: ShortcutTriggerState;
 };
 
+/**
+ * Whether a shortcut handles an event. Plain bindings follow their trigger state;
+ * a gesture binding handles its gesture's events (a hold ignores its release
+ * unless the command runs on both edges).
+ */
+function handles(
+	shortcut: RegisteredShortcut,
+	event: 'triggered' | 'pressed' | 'released'
+): boolean {
+	switch (event) {
+		case 'triggered':
+			return shortcut.gesture ? shortcut.gesture !== 'hold' : shortcut.on === 'Pressed';
+		case 'pressed':
+			return shortcut.gesture ? shortcut.gesture === 'hold' : shortcut.on === 'Both';
+		case 'released':
+			return shortcut.on === 'Both' && (!shortcut.gesture || shortcut.gesture === 'hold');
+	}
+}
+
 export function createFnShortcutManager() {
 	// Track registered shortcuts with their callbacks and trigger state
-	const shortcuts = new Map<Accelerator, RegisteredShortcut>();
+	const shortcuts = new Map<string, RegisteredShortcut>();
 
 	// console.log('[FnShortcut] Creating Fn shortcut manager');
 
@@ -33,7 +73,7 @@ export function createFnShortcutManager() {
 
 		// Find the matching shortcut
 		for (const [accelerator, shortcut] of shortcuts.entries()) {
-			if (shortcut.commandId === commandId && shortcut.on === 'Pressed') {
+			if (shortcut.commandId === commandId && handles(shortcut, 'triggered')) {
 				// console.log(`[FnShortcut] Executing tap command: ${shortcut.commandId} (${accelerator})`);
 				shortcut.callback();
 				break;
@@ -50,7 +90,7 @@ export function createFnShortcutManager() {
 
 		// Find the matching shortcut
 		for (const [accelerator, shortcut] of shortcuts.entries()) {
-			if (shortcut.commandId === commandId && shortcut.on === 'Both') {
+			if (shortcut.commandId === commandId && handles(shortcut, 'pressed')) {
 				// console.log(`[FnShortcut] Executing press for push-to-talk: ${shortcut.commandId} (${accelerator})`);
 				shortcut.callback();
 				break;
@@ -67,7 +107,7 @@ export function createFnShortcutManager() {
 
 		// Find the matching shortcut
 		for (const [accelerator, shortcut] of shortcuts.entries()) {
-			if (shortcut.commandId === commandId && shortcut.on === 'Both') {
+			if (shortcut.commandId === commandId && handles(shortcut, 'released')) {
 				// console.log(`[FnShortcut] Executing release for push-to-talk: ${shortcut.commandId} (${accelerator})`);
 				shortcut.callback();
 				break;
@@ -82,12 +122,14 @@ export function createFnShortcutManager() {
 			accelerator,
 			commandId,
 			callback,
-			on
+			on,
+			gesture
 		}: {
 			accelerator: Accelerator;
 			callback: () => void;
 			commandId: string;
 			on: ShortcutTriggerState;
+			gesture?: FnShortcutGesture;
 		}): Promise<Result<void, FnShortcutServiceError | FnNotSupportedError>> {
 			try {
 				// console.log(`[FnShortcut] Registering: ${accelerator} -> ${commandId}`);
@@ -95,10 +137,17 @@ export function createFnShortcutManager() {
 				// Call Rust backend to register the Fn shortcut
 				await invoke('register_fn_shortcut', {
 					accelerator,
-					commandId
+					commandId,
+					gesture: gesture ?? null
 				});
 
-				shortcuts.set(accelerator, { callback, commandId, on });
+				shortcuts.set(registrationKey(accelerator, gesture), {
+					accelerator,
+					callback,
+					commandId,
+					gesture,
+					on
+				});
 				// console.log('[FnShortcut] Registration successful');
 				return Ok(undefined);
 			} catch (error: any) {
@@ -113,23 +162,32 @@ export function createFnShortcutManager() {
 
 				return FnShortcutServiceErr({
 					cause: error,
-					context: { accelerator, commandId },
-					message: `Failed to register Fn shortcut: ${error}`
+					context: { accelerator, commandId, gesture },
+					message: `Failed to register Fn shortcut: ${describeShortcutError(error)}`
 				});
 			}
 		},
 
+		/** Without a gesture, removes every binding of the accelerator */
 		async unregister(
-			accelerator: Accelerator
+			accelerator: Accelerator,
+			gesture?: FnShortcutGesture
 		): Promise<Result<void, FnShortcutServiceError>> {
 			try {
 				// console.log(`[FnShortcut] Unregistering: ${accelerator}`);
 
 				await invoke('unregister_fn_shortcut', {
-					accelerator
+					accelerator,
+					gesture: gesture ?? null
 				});
 
-				shortcuts.delete(accelerator);
+				if (gesture) {
+					shortcuts.delete(registrationKey(accelerator, gesture));
+				} else {
+					for (const [key, shortcut] of shortcuts.entries()) {
+						if (shortcut.accelerator === accelerator) shortcuts.delete(key);
+					}
+				}
 				// console.log('[FnShortcut] Unregistration successful');
 				return Ok(undefined);
 			} catch (error: any) {
@@ -137,10 +195,25 @@ export function createFnShortcutManager() {
 
 				return FnShortcutServiceErr({
 					cause: error,
-					context: { accelerator },
+					context: { accelerator, gesture },
 					message: `Failed to unregister Fn shortcut: ${error}`
 				});
 			}
+		},
+
+		async setGestureTiming(
+			timing: FnGestureTiming
+		): Promise<Result<void, FnShortcutServiceError>> {
+			try {
+				await invoke('set_fn_gesture_timing', { timing });
+				return Ok(undefined);
+			} catch (error) {
+				return FnShortcutServiceErr({
+					cause: error,
+					context: { timing },
+					message: `Failed to set Fn gesture timing: ${error}`
+				});
+			}
 		}
 	};
 }
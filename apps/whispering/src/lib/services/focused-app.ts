@@ -0,0 +1,36 @@
+import { invoke } from '@tauri-apps/api/core';
+import { createTaggedError, extractErrorMessage } from 'wellcrafted/error';
+import { Err, Ok, type Result, tryAsync } from 'wellcrafted/result';
+
+const { FocusedAppErr, FocusedAppError } = createTaggedError('FocusedAppError');
+type FocusedAppError = ReturnType<typeof FocusedAppError>;
+
+/** The application text would be delivered to (desktop) */
+export type FocusedApp = {
+	/** Bundle identifier on macOS, WM_CLASS class on Linux */
+	appId: string;
+	/** App name on macOS, WM_CLASS instance on Linux */
+	name: string | null;
+	title: string | null;
+};
+
+export function createFocusedAppService() {
+	return {
+		/** Frontmost application, or null where it cannot be detected (Windows, Wayland) */
+		async getFocusedApp(): Promise<Result<FocusedApp | null, FocusedAppError>> {
+			const { data, error } = await tryAsync({
+				mapErr: (error) =>
+					FocusedAppErr({
+						cause: error,
+						context: { error },
+						message: `Failed to detect the focused app: ${extractErrorMessage(error)}`,
+					}),
+				try: () => invoke<FocusedApp | null>('get_focused_app'),
+			});
+			if (error) return Err(error);
+			return Ok(data);
+		},
+	};
+}
+
+export const FocusedAppLive = createFocusedAppService();
@@ -7,6 +7,7 @@ import {
 	type AcceleratorModifier,
 	type KeyboardEventSupportedKey,
 } from '$lib/constants/keyboard';
+import { invoke } from '@tauri-apps/api/core';
 import {
 	isRegistered as tauriIsRegistered,
 	register as tauriRegister,
@@ -19,6 +20,8 @@ import { Err, Ok, type Result, tryAsync } from 'wellcrafted/result';
 
 import type { ShortcutTriggerState } from './_shortcut-trigger-state';
 
+import { describeShortcutError } from './shortcut-registry';
+
 const { InvalidAcceleratorErr, InvalidAcceleratorError } = createTaggedError(
 	'InvalidAcceleratorError',
 );
@@ -44,10 +47,12 @@ export function createGlobalShortcutManager() {
 		async register({
 			accelerator,
 			callback,
+			commandId,
 			on,
 		}: {
 			accelerator: Accelerator;
 			callback: () => void;
+			commandId: string;
 			on: ShortcutTriggerState;
 		}): Promise<
 			Result<void, GlobalShortcutServiceError | InvalidAcceleratorError>
@@ -63,6 +68,19 @@ export function createGlobalShortcutManager() {
 				});
 			}
 
+			// Rejects keys held by an Fn shortcut, another command or the system
+			const { error: claimError } = await tryAsync({
+				mapErr: (error) =>
+					GlobalShortcutServiceErr({
+						cause: error,
+						context: { accelerator, commandId, error },
+						message: `Failed to register global shortcut '${accelerator}': ${describeShortcutError(error)}`,
+					}),
+				try: () =>
+					invoke('claim_global_shortcut', { accelerator, commandId }),
+			});
+			if (claimError) return Err(claimError);
+
 			const { error: registerError } = await tryAsync({
 				mapErr: (error) =>
 					GlobalShortcutServiceErr({
@@ -109,6 +127,17 @@ export function createGlobalShortcutManager() {
 		async unregister(
 			accelerator: Accelerator,
 		): Promise<Result<void, GlobalShortcutServiceError>> {
+			const { error: releaseError } = await tryAsync({
+				mapErr: (error) =>
+					GlobalShortcutServiceErr({
+						cause: error,
+						context: { accelerator, error },
+						message: `Failed to release global shortcut '${accelerator}': ${extractErrorMessage(error)}`,
+					}),
+				try: () => invoke('release_global_shortcut', { accelerator }),
+			});
+			if (releaseError) return Err(releaseError);
+
 			const isRegistered = await tauriIsRegistered(accelerator);
 			if (!isRegistered) return Ok(undefined);
 
@@ -141,7 +170,10 @@ export function createGlobalShortcutManager() {
 						context: { error },
 						message: `Failed to unregister all global shortcuts: ${extractErrorMessage(error)}`,
 					}),
-				try: () => tauriUnregisterAll(),
+				try: async () => {
+					await tauriUnregisterAll();
+					await invoke('release_global_shortcut', { accelerator: null });
+				},
 			});
 			if (unregisterAllError) return Err(unregisterAllError);
 			return Ok(undefined);
@@ -6,13 +6,44 @@ import {
 	GlobalShortcutManagerLive,
 	type Accelerator
 } from './global-shortcut-manager';
-import { FnShortcutManagerLive } from './fn-shortcut-manager';
+import {
+	FnShortcutManagerLive,
+	type FnGestureTiming,
+	type FnShortcutGesture
+} from './fn-shortcut-manager';
+
+/**
+ * Modifier keys that can be a shortcut on their own (handled natively on Linux)
+ */
+const MODIFIER_ONLY_KEYS = new Set([
+	'control',
+	'ctrl',
+	'leftcontrol',
+	'rightcontrol',
+	'alt',
+	'option',
+	'leftalt',
+	'rightalt',
+	'altgr',
+	'shift',
+	'leftshift',
+	'rightshift',
+	'super',
+	'meta',
+	'command',
+	'leftsuper',
+	'rightsuper',
+	'capslock'
+]);
 
 /**
- * Determines if an accelerator contains the Fn modifier
+ * Determines if an accelerator contains the Fn modifier or is a modifier alone
  */
-function containsFnModifier(accelerator: Accelerator): boolean {
-	return accelerator.includes('Fn');
+export function containsFnModifier(accelerator: Accelerator): boolean {
+	return (
+		accelerator.includes('Fn') ||
+		MODIFIER_ONLY_KEYS.has(accelerator.trim().toLowerCase())
+	);
 }
 
 /**
@@ -21,6 +52,7 @@ function containsFnModifier(accelerator: Accelerator): boolean {
  *
  * - Regular shortcuts (Cmd+P, Ctrl+Shift+F) → GlobalShortcutManager (Tauri plugin)
  * - Fn shortcuts (Fn, Fn+A, Fn+Cmd+P) → FnShortcutManager (CGEventTap)
+ * - Modifier-only shortcuts (RightControl, Super) → FnShortcutManager (evdev on Linux)
  */
 export function createHybridShortcutManager() {
 	// console.log('[HybridShortcut] Creating hybrid shortcut manager');
@@ -29,11 +61,14 @@ export function createHybridShortcutManager() {
 		async register({
 			accelerator,
 			command,
-			on = 'Pressed'
+			on = 'Pressed',
+			gesture
 		}: {
 			accelerator: Accelerator;
 			command: Command;
 			on?: ShortcutTriggerState;
+			/** Only Fn and modifier-only shortcuts recognize gestures */
+			gesture?: FnShortcutGesture;
 		}): Promise<Result<void, any>> {
 			// Get the global callback that passes initiatedVia: 'global-shortcut'
 			const globalCallback = globalCommandCallbacks[command.id];
@@ -47,13 +82,15 @@ export function createHybridShortcutManager() {
 					accelerator,
 					commandId: command.id,
 					callback: globalCallback,
-					on: command.on
+					on: command.on,
+					gesture
 				});
 			} else {
 				// console.log(`[HybridShortcut] Routing ${accelerator} to global manager`);
 				// Use standard global shortcut manager with global callback
 				return GlobalShortcutManagerLive.register({
 					accelerator,
+					commandId: command.id,
 					callback: () => {
 						// console.log(`[HybridShortcut] Executing ${accelerator} -> ${command.id}`);
 						globalCallback();
@@ -63,15 +100,22 @@ export function createHybridShortcutManager() {
 			}
 		},
 
-		async unregister(accelerator: Accelerator): Promise<Result<void, any>> {
+		async unregister(
+			accelerator: Accelerator,
+			gesture?: FnShortcutGesture
+		): Promise<Result<void, any>> {
 			// Route based on Fn presence
 			if (containsFnModifier(accelerator)) {
 				// console.log(`[HybridShortcut] Unregistering ${accelerator} from Fn manager`);
-				return FnShortcutManagerLive.unregister(accelerator);
+				return FnShortcutManagerLive.unregister(accelerator, gesture);
 			} else {
 				// console.log(`[HybridShortcut] Unregistering ${accelerator} from global manager`);
 				return GlobalShortcutManagerLive.unregister(accelerator);
 			}
+		},
+
+		setGestureTiming(timing: FnGestureTiming): Promise<Result<void, any>> {
+			return FnShortcutManagerLive.setGestureTiming(timing);
 		}
 	};
 }
@@ -4,6 +4,7 @@ import { ClipboardServiceLive } from './clipboard';
 import * as completions from './completion';
 import { DbServiceLive } from './db';
 import { DownloadServiceLive } from './download';
+import { FocusedAppLive } from './focused-app';
 import { GlobalShortcutManagerLive } from './global-shortcut-manager';
 import { HybridShortcutManagerLive } from './hybrid-shortcut-manager';
 import { LocalShortcutManagerLive } from './local-shortcut-manager';
@@ -11,6 +12,7 @@ import { NotificationServiceLive } from './notifications';
 import { OsServiceLive } from './os';
 import { PermissionMonitorLive } from './permission-monitor';
 import { RecorderServiceLive } from './recorder';
+import { ShortcutRegistryLive } from './shortcut-registry';
 import { PlaySoundServiceLive } from './sound';
 import { ToastServiceLive } from './toast';
 import * as transcriptions from './transcription';
@@ -29,6 +31,7 @@ export {
 	completions,
 	DbServiceLive as db,
 	DownloadServiceLive as download,
+	FocusedAppLive as focusedApp,
 	GlobalShortcutManagerLive as globalShortcutManager,
 	HybridShortcutManagerLive as hybridShortcutManager,
 	LocalShortcutManagerLive as localShortcutManager,
@@ -36,6 +39,7 @@ export {
 	OsServiceLive as os,
 	PermissionMonitorLive as permissionMonitor,
 	RecorderServiceLive as recorder,
+	ShortcutRegistryLive as shortcutRegistry,
 	PlaySoundServiceLive as sound,
 	ToastServiceLive as toast,
 	transcriptions,
@@ -22,26 +22,90 @@ type AudioRecording = {
 	channels: number;
 	durationSeconds: number;
 	filePath?: string;
+	mimeType?: string;
 	sampleRate: number;
 };
 
+/**
+ * Input device returned by `enumerate_recording_devices`.
+ *
+ * `id` is built from the host API and device name, with `#2`, `#3`... appended to
+ * devices that share a name. `init_recording_session` also accepts a plain name,
+ * which is what settings saved by older versions contain.
+ */
+export type RecordingDevice = {
+	channelCounts: number[];
+	hostApi: string;
+	id: string;
+	isDefault: boolean;
+	name: string;
+	sampleFormats: string[];
+	sampleRates: number[];
+};
+
+/**
+ * Label shown for a device, numbered when several devices share its name
+ */
+function deviceLabel(device: RecordingDevice) {
+	const duplicate = device.id.match(/#(\d+)$/);
+	return duplicate ? `${device.name} (${duplicate[1]})` : device.name;
+}
+
+/**
+ * Status reported by `get_recorder_status`
+ */
+type RecorderStatusInfo = {
+	recordingId: null | string;
+	status:
+		| 'error'
+		| 'finalizing'
+		| 'idle'
+		| 'paused'
+		| 'recording'
+		| 'sessionReady';
+};
+
 export function createDesktopRecorderService(): RecorderService {
+	/**
+	 * Settings of the open session, which is reused while they stay the same. With a
+	 * pre-roll the session stays open between recordings so the buffer keeps filling.
+	 */
+	let openSession: null | { key: string; keepOpen: boolean } = null;
+
+	/**
+	 * Close the session unless it is kept open for the pre-roll
+	 */
+	const releaseSession = async () => {
+		if (openSession?.keepOpen) return;
+		openSession = null;
+		const { error: closeError } = await invoke<void>(
+			'close_recording_session',
+		);
+		if (closeError) {
+			// Log but don't fail the operation
+			console.error('Failed to close recording session:', closeError);
+		}
+	};
+
 	const enumerateDevices = async (): Promise<
 		Result<Device[], RecorderServiceError>
 	> => {
-		const { data: deviceNames, error: enumerateRecordingDevicesError } =
-			await invoke<string[]>('enumerate_recording_devices');
+		const { data: recordingDevices, error: enumerateRecordingDevicesError } =
+			await invoke<RecordingDevice[]>('enumerate_recording_devices');
 		if (enumerateRecordingDevicesError) {
 			return RecorderServiceErr({
 				cause: enumerateRecordingDevicesError,
 				message: 'Failed to enumerate recording devices',
 			});
 		}
-		// On desktop, device names serve as both ID and label
+		// Default device first, so it is the fallback when the selected one is missing
+		const sorted = [...recordingDevices].sort(
+			(a, b) => Number(b.isDefault) - Number(a.isDefault),
+		);
 		return Ok(
-			deviceNames.map((name) => ({
-				id: asDeviceIdentifier(name),
-				label: name,
+			sorted.map((device) => ({
+				id: asDeviceIdentifier(device.id),
+				label: deviceLabel(device),
 			})),
 		);
 	};
@@ -102,13 +166,7 @@ export function createDesktopRecorderService(): RecorderService {
 				title: '🔄 Closing Session',
 				description: 'Cleaning up recording resources...',
 			});
-			const { error: closeError } = await invoke<void>(
-				'close_recording_session',
-			);
-			if (closeError) {
-				// Log but don't fail the cancel operation
-				console.error('Failed to close recording session:', closeError);
-			}
+			await releaseSession();
 
 			return Ok({ status: 'cancelled' });
 		},
@@ -144,11 +202,25 @@ export function createDesktopRecorderService(): RecorderService {
 				});
 			}
 
-			const { outputFolder, recordingId, sampleRate, selectedDeviceId } =
-				params;
+			const {
+				format,
+				limits,
+				outputFolder,
+				preRollMs,
+				recordingId,
+				sampleRate,
+				selectedDeviceId,
+				vad,
+			} = params;
 			const { data: devices, error: enumerateError } = await enumerateDevices();
 			if (enumerateError) return Err(enumerateError);
 
+			// Settings saved before device identifiers hold the device name, which
+			// is the label of the first device with that name
+			const selectedDevice =
+				devices.find((d) => d.id === selectedDeviceId) ??
+				devices.find((d) => d.label === selectedDeviceId);
+
 			const acquireDevice = (): Result<
 				DeviceAcquisitionOutcome,
 				RecorderServiceError
@@ -179,7 +251,7 @@ export function createDesktopRecorderService(): RecorderService {
 				}
 
 				// Check if the selected device exists in the devices array
-				const deviceExists = deviceIds.includes(selectedDeviceId);
+				const deviceExists = selectedDevice !== undefined;
 
 				if (deviceExists) return Ok({ outcome: 'success' });
 
@@ -200,51 +272,72 @@ export function createDesktopRecorderService(): RecorderService {
 				acquireDevice();
 			if (acquireDeviceError) return Err(acquireDeviceError);
 
-			// Determine which device name to use based on the outcome
+			// Determine which device to use based on the outcome
 			const deviceIdentifier =
 				deviceOutcome.outcome === 'success'
-					? selectedDeviceId
+					? selectedDevice?.id
 					: deviceOutcome.fallbackDeviceId;
 
-			// Now initialize recording with the chosen device
-			sendStatus({
-				title: '🎤 Setting Up',
-				description:
-					'Initializing your recording session and checking microphone access...',
-			});
-
 			// Convert sample rate string to number if provided
 			const sampleRateNum = sampleRate
 				? Number.parseInt(sampleRate, 10)
 				: undefined;
 
-			const { error: initRecordingSessionError } = await invoke(
-				'init_recording_session',
-				{
-					deviceIdentifier,
-					outputFolder: outputFolder || undefined,
-					recordingId,
-					sampleRate: sampleRateNum,
-				},
-			);
-			if (initRecordingSessionError)
-				return RecorderServiceErr({
-					cause: initRecordingSessionError,
-					context: {
-						deviceIdentifier,
-						selectedDeviceId,
-					},
-					message:
-						'We encountered an issue while setting up your recording session. This could be because your microphone is being used by another app, your microphone permissions are denied, or the selected recording device is disconnected',
+			const sessionArgs = {
+				deviceIdentifier,
+				format,
+				limits: limits ?? undefined,
+				outputFolder: outputFolder || undefined,
+				preRollMs,
+				sampleRate: sampleRateNum,
+				vad: vad ?? undefined,
+			};
+			const sessionKey = JSON.stringify(sessionArgs);
+
+			// Reuse the open session if nothing changed and it is still healthy
+			let reuseSession = false;
+			if (openSession?.key === sessionKey) {
+				const { data: recorderStatus } = await invoke<RecorderStatusInfo>(
+					'get_recorder_status',
+				);
+				reuseSession = recorderStatus?.status === 'sessionReady';
+			}
+
+			if (!reuseSession) {
+				// Now initialize recording with the chosen device
+				sendStatus({
+					title: '🎤 Setting Up',
+					description:
+						'Initializing your recording session and checking microphone access...',
 				});
 
+				openSession = null;
+				const { error: initRecordingSessionError } = await invoke(
+					'init_recording_session',
+					{ ...sessionArgs, recordingId },
+				);
+				if (initRecordingSessionError)
+					return RecorderServiceErr({
+						cause: initRecordingSessionError,
+						context: {
+							deviceIdentifier,
+							selectedDeviceId,
+						},
+						message:
+							'We encountered an issue while setting up your recording session. This could be because your microphone is being used by another app, your microphone permissions are denied, or the selected recording device is disconnected',
+					});
+				openSession = { key: sessionKey, keepOpen: preRollMs > 0 };
+			}
+
 			sendStatus({
 				title: '🎙️ Starting Recording',
 				description:
 					'Recording session initialized, now starting to capture audio...',
 			});
-			const { error: startRecordingError } =
-				await invoke<void>('start_recording');
+			const { error: startRecordingError } = await invoke<void>(
+				'start_recording',
+				{ recordingId },
+			);
 			if (startRecordingError)
 				return RecorderServiceErr({
 					cause: startRecordingError,
@@ -283,7 +376,7 @@ export function createDesktopRecorderService(): RecorderService {
 			}
 			// audioRecording is now AudioRecordingWithFile
 
-			// Read the WAV file from disk
+			// Read the recording file from disk
 			sendStatus({
 				title: '📁 Reading Recording',
 				description: 'Loading your recording from disk...',
@@ -298,7 +391,9 @@ export function createDesktopRecorderService(): RecorderService {
 					}),
 				try: async () => {
 					const fileBytes = await readFile(filePath);
-					return new Blob([fileBytes], { type: 'audio/wav' });
+					return new Blob([fileBytes], {
+						type: audioRecording.mimeType ?? 'audio/wav',
+					});
 				},
 			});
 			if (readRecordingFileError) return Err(readRecordingFileError);
@@ -308,13 +403,7 @@ export function createDesktopRecorderService(): RecorderService {
 				title: '🔄 Closing Session',
 				description: 'Cleaning up recording resources...',
 			});
-			const { error: closeError } = await invoke<void>(
-				'close_recording_session',
-			);
-			if (closeError) {
-				// Log but don't fail the stop operation
-				console.error('Failed to close recording session:', closeError);
-			}
+			await releaseSession();
 
 			return Ok(blob);
 		},
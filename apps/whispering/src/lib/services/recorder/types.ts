@@ -1,4 +1,7 @@
-import type { CancelRecordingResult } from '$lib/constants/audio';
+import type {
+	CancelRecordingResult,
+	DesktopRecordingFormat,
+} from '$lib/constants/audio';
 import type { Result } from 'wellcrafted/result';
 
 import { createTaggedError } from 'wellcrafted/error';
@@ -20,9 +23,16 @@ export const { RecorderServiceErr, RecorderServiceError } = createTaggedError(
  * Desktop-specific recording parameters
  */
 export type DesktopRecordingParams = BaseRecordingParams & {
+	format: DesktopRecordingFormat;
+	/** Stop at whichever limit is reached first, null when neither is set */
+	limits: null | { maxBytes?: number; maxSeconds?: number };
 	outputFolder: null | string;
 	platform: 'desktop';
+	/** Milliseconds of audio from before the start; 0 closes the session after each recording */
+	preRollMs: number;
 	sampleRate: string;
+	/** Native voice activity detection, null when disabled */
+	vad: null | { autoStopSilenceMs: null | number; trimSilence: boolean };
 };
 
 /**
@@ -0,0 +1,66 @@
+import { invoke } from '@tauri-apps/api/core';
+import { createTaggedError, extractErrorMessage } from 'wellcrafted/error';
+import { Err, Ok, type Result, tryAsync } from 'wellcrafted/result';
+
+import type { FnShortcutGesture } from './fn-shortcut-manager';
+
+const { ShortcutRegistryErr, ShortcutRegistryError } = createTaggedError(
+	'ShortcutRegistryError',
+);
+type ShortcutRegistryError = ReturnType<typeof ShortcutRegistryError>;
+
+/** A shortcut held by the Fn manager or the global-shortcut plugin */
+export type ShortcutEntry = {
+	accelerator: string;
+	/** Canonical form used to detect conflicts ("Control+Shift+R") */
+	normalized: string;
+	source: 'fn' | 'global';
+	commandId: string;
+	gesture: FnShortcutGesture | null;
+};
+
+/** Returned by `register_fn_shortcut` and `claim_global_shortcut` when a key is taken */
+export type ShortcutConflict =
+	| { kind: 'alreadyBound'; accelerator: string; existing: ShortcutEntry }
+	| { kind: 'reserved'; accelerator: string; reason: string };
+
+export function isShortcutConflict(error: unknown): error is ShortcutConflict {
+	return (
+		typeof error === 'object' &&
+		error !== null &&
+		'kind' in error &&
+		(error.kind === 'alreadyBound' || error.kind === 'reserved')
+	);
+}
+
+/** Human-readable message for a shortcut command error (conflict or plain string) */
+export function describeShortcutError(error: unknown): string {
+	if (!isShortcutConflict(error)) return extractErrorMessage(error);
+	if (error.kind === 'reserved') {
+		return `'${error.accelerator}' is reserved by the system for ${error.reason}`;
+	}
+	return `'${error.accelerator}' is already bound to '${error.existing.commandId}'`;
+}
+
+export function createShortcutRegistry() {
+	return {
+		/** Every shortcut registered through the Fn manager and the global-shortcut plugin */
+		async listShortcuts(): Promise<
+			Result<ShortcutEntry[], ShortcutRegistryError>
+		> {
+			const { data, error } = await tryAsync({
+				mapErr: (error) =>
+					ShortcutRegistryErr({
+						cause: error,
+						context: { error },
+						message: `Failed to list shortcuts: ${extractErrorMessage(error)}`,
+					}),
+				try: () => invoke<ShortcutEntry[]>('list_shortcuts'),
+			});
+			if (error) return Err(error);
+			return Ok(data);
+		},
+	};
+}
+
+export const ShortcutRegistryLive = createShortcutRegistry();
@@ -9,8 +9,8 @@ import type { Result } from 'wellcrafted/result';
  *   - `label`: The human-readable device label (e.g., "Built-in Microphone")
  *
  * On Desktop (CPAL):
- *   - `id`: The device name (e.g., "MacBook Pro Microphone")
- *   - `label`: The same device name (identical to id for desktop)
+ *   - `id`: Host API and platform device id (e.g., "CoreAudio:BuiltInMicrophoneDevice")
+ *   - `label`: The device name, numbered when several devices share it
  *
  * This separation allows for better UX (showing readable names) while maintaining
  * stable identifiers for settings persistence.
@@ -24,7 +24,7 @@ import type { Result } from 'wellcrafted/result';
  *
  * // Desktop device
  * const device: Device = {
- *   id: "MacBook Pro Microphone" as DeviceIdentifier,
+ *   id: "CoreAudio:BuiltInMicrophoneDevice" as DeviceIdentifier,
  *   label: "MacBook Pro Microphone"
  * };
  */
@@ -58,7 +58,7 @@ export type Device = {
  * {
  *   outcome: 'fallback',
  *   reason: 'preferred-device-unavailable',
- *   fallbackDeviceId: 'MacBook Pro Microphone' as DeviceIdentifier
+ *   fallbackDeviceId: 'CoreAudio:BuiltInMicrophoneDevice' as DeviceIdentifier
  * }
  * ```
  */
@@ -80,8 +80,12 @@ export type DeviceAcquisitionOutcome =
  *   - NOT the device label. We use the actual deviceId for uniqueness
  *
  * On Desktop (CPAL):
- *   - This is the device name as a string (e.g., "MacBook Pro Microphone")
- *   - The name serves as both identifier and label
+ *   - This is the host API and the platform's device id: the CoreAudio device UID, the
+ *     WASAPI endpoint id or the ALSA PCM name (e.g., "ALSA:sysdefault:CARD=Yeti")
+ *   - Other hosts use the device name, with "#2", "#3"... appended when several devices
+ *     share a name; those can swap when identical devices are replugged
+ *   - Settings saved by older versions hold the name-based id or the plain device name,
+ *     which are still accepted
  *
  * While these represent different concepts on each platform, they serve the same
  * purpose: uniquely identifying a recording device for selection and persistence.
@@ -91,8 +95,8 @@ export type DeviceAcquisitionOutcome =
  * // Web: Stores the deviceId (unique identifier, NOT the label)
  * const deviceIdentifier: DeviceIdentifier = "8a7b9c..." as DeviceIdentifier;
  *
- * // Desktop: Stores the device name (which is both ID and label)
- * const deviceIdentifier: DeviceIdentifier = "MacBook Pro Microphone" as DeviceIdentifier;
+ * // Desktop: Stores the host API and platform device id
+ * const deviceIdentifier: DeviceIdentifier = "CoreAudio:BuiltInMicrophoneDevice" as DeviceIdentifier;
  */
 export type DeviceIdentifier = Brand<'DeviceIdentifier'> & string;
 
@@ -39,8 +39,10 @@ import type { OpenAIModel } from '$lib/services/transcription/openai';
 import {
 	BITRATE_VALUES_KBPS,
 	DEFAULT_BITRATE_KBPS,
+	DESKTOP_RECORDING_FORMATS,
 	RECORDING_MODES,
 } from '$lib/constants/audio';
+import { SHORTCUT_GESTURES } from '$lib/constants/keyboard';
 import { SUPPORTED_LANGUAGES } from '$lib/constants/languages';
 import { TRANSCRIPTION_SERVICE_IDS } from '$lib/constants/transcription';
 import { ALWAYS_ON_TOP_VALUES } from '$lib/constants/ui';
@@ -89,6 +91,48 @@ export const settingsSchema = z.object({
 		z.ZodDefault<ZodBoolean>
 	>),
 
+	/** How transcripts and transformations are inserted at the cursor (desktop) */
+	'delivery.strategy': z
+		.enum(['clipboardPaste', 'typing', 'directInsert'])
+		.default('clipboardPaste'),
+	'delivery.fallback': z.boolean().default(true),
+	'delivery.typing.charDelayMs': z
+		.string()
+		.regex(/^\d+$/, 'Must be a number')
+		.default('0'),
+	'delivery.typing.unicode': z.enum(['unicode', 'keystrokes']).default('unicode'),
+	/** Per-app overrides, first match wins (see DeliveryProfile) */
+	'delivery.profiles': z
+		.array(
+			z.object({
+				name: z.string(),
+				apps: z.array(z.string()),
+				strategy: z.enum(['clipboardPaste', 'typing', 'directInsert']).nullable(),
+				unicode: z.enum(['unicode', 'keystrokes']).nullable(),
+				pasteKeys: z.enum(['standard', 'controlShiftV', 'shiftInsert']),
+				trailingNewline: z.boolean(),
+			}),
+		)
+		.default([
+			{
+				name: 'Terminals',
+				// X11 window classes; macOS terminals paste with Cmd+V
+				apps: [
+					'Gnome-terminal',
+					'konsole',
+					'Alacritty',
+					'kitty',
+					'org.wezfurlong.wezterm',
+					'Tilix',
+					'Terminator',
+				],
+				strategy: null,
+				unicode: null,
+				pasteKeys: 'controlShiftV',
+				trailingNewline: false,
+			},
+		]),
+
 	// Analytics settings
 	'analytics.enabled': z.boolean().default(true),
 	
@@ -122,6 +166,30 @@ export const settingsSchema = z.object({
 	'recording.desktop.sampleRate': z
 		.enum(['16000', '44100', '48000'])
 		.default('16000'),
+	'recording.desktop.format': z
+		.enum(DESKTOP_RECORDING_FORMATS)
+		.default('wavFloat'),
+	/** Audio kept from before the shortcut is pressed; keeps the microphone open between recordings */
+	'recording.desktop.preRollMs': z
+		.string()
+		.regex(/^\d+$/, 'Must be a number')
+		.default('0'),
+	/** Native voice activity detection: speech events, auto-stop and silence trimming */
+	'recording.desktop.vad.enabled': z.boolean().default(false),
+	'recording.desktop.vad.autoStopSilenceMs': z
+		.string()
+		.regex(/^\d+$/, 'Must be a number')
+		.default('2000'), // 0 = never auto-stop
+	'recording.desktop.vad.trimSilence': z.boolean().default(true),
+	/** Recordings are finalized when they reach either limit (empty = no limit) */
+	'recording.desktop.maxDurationSeconds': z
+		.string()
+		.regex(/^\d*$/, 'Must be a number')
+		.default(''),
+	'recording.desktop.maxSizeMb': z
+		.string()
+		.regex(/^\d*$/, 'Must be a number')
+		.default(''),
 	// Recording mode settings
 	'recording.mode': z.enum(RECORDING_MODES).default('manual'),
 
@@ -143,6 +211,12 @@ export const settingsSchema = z.object({
 	'transcription.clipboard.copyOnSuccess': z.boolean().default(true),
 
 	'transcription.clipboard.pasteOnSuccess': z.boolean().default(true),
+	/** Put back what the user had copied once the transcript was pasted */
+	'transcription.clipboard.restoreAfterPaste': z.boolean().default(false),
+	'transcription.clipboard.restoreDelayMs': z
+		.string()
+		.regex(/^\d+$/, 'Must be a number')
+		.default('500'),
 	'transcription.deepgram.model': z
 		.string()
 		.transform((val) => val as DeepgramModel['name'] | (string & {}))
@@ -226,6 +300,33 @@ export const settingsSchema = z.object({
 		`shortcuts.global.${Command['id']}`,
 		z.ZodDefault<z.ZodNullable<ZodString>>
 	>),
+
+	/** Gesture of a Fn or modifier-only global shortcut ('press' = every press, release and tap) */
+	...({
+		'shortcuts.gesture.cancelManualRecording': z
+			.enum(SHORTCUT_GESTURES)
+			.default('press'),
+		'shortcuts.gesture.pushToTalk': z.enum(SHORTCUT_GESTURES).default('press'),
+		'shortcuts.gesture.toggleManualRecording': z
+			.enum(SHORTCUT_GESTURES)
+			.default('press'),
+	} satisfies Record<
+		`shortcuts.gesture.${Command['id']}`,
+		z.ZodDefault<z.ZodEnum<typeof SHORTCUT_GESTURES>>
+	>),
+	/** Gesture timing in milliseconds; a tap may not last longer than the hold threshold */
+	'shortcuts.gestureTiming.tapMaxMs': z
+		.string()
+		.regex(/^\d+$/, 'Must be a number')
+		.default('300'),
+	'shortcuts.gestureTiming.holdMinMs': z
+		.string()
+		.regex(/^\d+$/, 'Must be a number')
+		.default('300'),
+	'shortcuts.gestureTiming.doubleTapWindowMs': z
+		.string()
+		.regex(/^\d+$/, 'Must be a number')
+		.default('300'),
 });
 
 /**
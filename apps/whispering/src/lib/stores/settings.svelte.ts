@@ -114,7 +114,11 @@ export const settings = (() => {
 			_settings.value = { ..._settings.value, [key]: value };
 			
 			// Sync shortcuts when shortcut settings change
-			if (key.startsWith('shortcuts.global.')) {
+			if (
+				key.startsWith('shortcuts.global.') ||
+				key.startsWith('shortcuts.gesture.') ||
+				key.startsWith('shortcuts.gestureTiming.')
+			) {
 				syncGlobalShortcutsWithSettings();
 			} else if (key.startsWith('shortcuts.local.')) {
 				// syncLocalShortcutsWithSettings(); // Commented out - using global shortcuts only
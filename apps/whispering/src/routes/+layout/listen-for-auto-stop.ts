@@ -0,0 +1,39 @@
+import { rpc } from '$lib/query';
+import { listen } from '@tauri-apps/api/event';
+
+/** Payload of `recording-limit-warning` */
+type LimitWarning = {
+	limit: 'max-duration' | 'max-size';
+	remainingSeconds: number;
+};
+
+const LIMIT_NAMES = {
+	'max-duration': 'maximum length',
+	'max-size': 'maximum file size',
+} satisfies Record<LimitWarning['limit'], string>;
+
+/**
+ * Finishes recordings the desktop recorder ended on its own. The recorder has already
+ * finalized the file; the usual stop flow picks it up and transcribes it.
+ */
+export function listenForAutoStoppedRecordings() {
+	listen('recording-silence-finalized', () => {
+		rpc.commands.stopManualRecording.execute(undefined);
+	});
+
+	listen<LimitWarning>('recording-limit-warning', ({ payload }) => {
+		rpc.notify.info.execute({
+			title: 'Recording limit approaching',
+			description: `The recording stops at its ${LIMIT_NAMES[payload.limit]} in ${Math.ceil(payload.remainingSeconds)} seconds.`,
+		});
+	});
+
+	// A limit cut the recording: transcribe what fits and say what happened
+	listen<{ durationSeconds: number }>('recording-truncated', ({ payload }) => {
+		rpc.notify.warning.execute({
+			title: 'Recording stopped at its limit',
+			description: `Only the first ${Math.round(payload.durationSeconds)} seconds were kept. Raise the limits in the recording settings for longer recordings.`,
+		});
+		rpc.commands.stopManualRecording.execute(undefined);
+	});
+}
@@ -0,0 +1,35 @@
+import { rpc } from '$lib/query';
+import { settings } from '$lib/stores/settings.svelte';
+import { invoke } from '@tauri-apps/api/core';
+
+/** A recording repaired after the app quit mid-recording */
+type RecoveredRecording = {
+	recordingId: string;
+	filePath: string;
+	durationSeconds: number;
+};
+
+/**
+ * Tells the user about recordings an interrupted session left behind. The desktop
+ * recorder repairs them at startup; this asks for the results once the app has loaded.
+ */
+export async function notifyRecoveredRecordings() {
+	try {
+		const recovered = await invoke<RecoveredRecording[]>('get_recovered_recordings', {
+			outputFolder: settings.value['recording.desktop.outputFolder'] || null,
+		});
+		if (recovered.length === 0) return;
+
+		rpc.notify.info.execute({
+			title:
+				recovered.length === 1
+					? 'Recovered an interrupted recording'
+					: `Recovered ${recovered.length} interrupted recordings`,
+			description: recovered
+				.map(({ filePath, durationSeconds }) => `${filePath} (${Math.round(durationSeconds)}s)`)
+				.join('\n'),
+		});
+	} catch (error) {
+		console.error('[Startup] Failed to check for recovered recordings:', error);
+	}
+}
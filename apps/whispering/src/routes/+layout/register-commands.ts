@@ -20,8 +20,10 @@ export function resetGlobalShortcutsToDefaultIfDuplicates(): boolean {
 
 	// Check for duplicates
 	for (const command of commands) {
-		const shortcut = settings.value[`shortcuts.global.${command.id}`];
-		if (shortcut) {
+		const accelerator = settings.value[`shortcuts.global.${command.id}`];
+		if (accelerator) {
+			// One Fn or modifier key can carry several gestures
+			const shortcut = `${accelerator}#${settings.value[`shortcuts.gesture.${command.id}`]}`;
 			if (globalShortcuts.has(shortcut)) {
 				// If duplicates found, reset all global shortcuts to defaults
 				settings.resetShortcuts('global');
@@ -83,6 +85,7 @@ export function resetGlobalShortcutsToDefaultIfDuplicates(): boolean {
  * - Checks accessibility permissions on macOS before registering shortcuts
  * - Registers shortcuts that have key combinations defined in settings
  * - Unregisters shortcuts that don't have key combinations defined
+ * - Applies the gesture timing when a Fn or modifier-only shortcut uses a gesture
  * - Shows error toast if any registration/unregistration fails
  */
 export async function syncGlobalShortcutsWithSettings() {
@@ -95,6 +98,7 @@ export async function syncGlobalShortcutsWithSettings() {
 		if (toggleShortcut) {
 			await rpc.shortcuts.unregisterCommandGlobally.execute({
 				accelerator: toggleShortcut as Accelerator,
+				gesture: settings.value['shortcuts.gesture.toggleManualRecording'],
 			});
 		}
 	} else {
@@ -103,6 +107,22 @@ export async function syncGlobalShortcutsWithSettings() {
 		if (pushToTalkShortcut) {
 			await rpc.shortcuts.unregisterCommandGlobally.execute({
 				accelerator: pushToTalkShortcut as Accelerator,
+				gesture: settings.value['shortcuts.gesture.pushToTalk'],
+			});
+		}
+	}
+
+	// Gesture timing only matters (and is only supported) where a gesture is bound
+	const usesGestures = commands.some(
+		(command) => settings.value[`shortcuts.gesture.${command.id}`] !== 'press',
+	);
+	if (usesGestures) {
+		const { error: timingError } = await rpc.shortcuts.setGestureTiming.execute(undefined);
+		if (timingError) {
+			rpc.notify.error.execute({
+				title: 'Error applying gesture timing',
+				description: timingError.message,
+				action: { error: timingError, type: 'more-details' },
 			});
 		}
 	}